@@ -0,0 +1,101 @@
+use reqwest::Client;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{complete, ProviderError, ProviderSettings};
+
+/// What the devil's advocate made of a verdict, stored on the record apart from the
+/// votes: it never counts toward consensus.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Challenge {
+    pub model: String,
+    /// Whether it found a credible counterargument. When it didn't, the verdict
+    /// stood up to a deliberate attempt to overturn it.
+    pub credible: bool,
+    /// Its case against the verdict, or why it found none.
+    pub argument: String,
+}
+
+/// `--devils-advocate`: after voting, asks a model (through OpenRouter) to argue the
+/// opposite of the majority verdict.
+pub struct DevilsAdvocate {
+    client: Client,
+    model: String,
+    settings: Arc<ProviderSettings>,
+}
+
+impl DevilsAdvocate {
+    pub fn new(model: &str, settings: Arc<ProviderSettings>) -> Self {
+        Self {
+            client: crate::client_builder(settings.proxy.as_ref())
+                .timeout(Duration::from_secs(30))
+                .build()
+                .expect("static client configuration"),
+            model: model.to_string(),
+            settings,
+        }
+    }
+
+    pub async fn challenge(&self, statement: &str, verdict: bool) -> Result<Challenge, ProviderError> {
+        let api_key = env::var("OPENROUTER_API_KEY").map_err(|_| ProviderError::MissingKey("OPENROUTER_API_KEY"))?;
+        let body = json!({
+            "model": self.model,
+            "messages": [{ "role": "user", "content": challenge_prompt(statement, verdict) }],
+        });
+        let request = self
+            .client
+            .post("https://openrouter.ai/api/v1/chat/completions")
+            .header("Authorization", format!("Bearer {}", api_key));
+        let request = self.settings.with_extra_headers("openrouter", request);
+        let completion = complete(request, body, &self.model, &self.settings).await?;
+        let (credible, argument) = parse_challenge(&crate::input::sanitize_statement(&completion.text));
+        Ok(Challenge {
+            model: self.model.clone(),
+            credible,
+            argument,
+        })
+    }
+}
+
+fn challenge_prompt(statement: &str, verdict: bool) -> String {
+    let (found, opposite) = if verdict { ("valid", "not valid") } else { ("not valid", "valid") };
+    format!(
+        "A panel of AI models judged this statement {}: '{}'\n\
+         Act as devil's advocate: make the strongest honest case that the statement is {}. \
+         Do not invent facts. Start your answer with CREDIBLE if a well-informed person could \
+         reasonably accept your case, or NONE if no credible case exists, then give the case \
+         (or why there is none) in one short paragraph.",
+        found, statement, opposite
+    )
+}
+
+/// The leading CREDIBLE/NONE and the paragraph after it. An answer that starts with
+/// neither is taken as credible, so only an explicit NONE strengthens a verdict.
+fn parse_challenge(reply: &str) -> (bool, String) {
+    let reply = reply.trim();
+    let word_end = reply.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(reply.len());
+    let credible = !reply[..word_end].eq_ignore_ascii_case("none");
+    let rest = match reply[..word_end].to_ascii_lowercase().as_str() {
+        "none" | "credible" => reply[word_end..].trim_start_matches([':', '.', '-', ' ', '\n']),
+        _ => reply,
+    };
+    (credible, rest.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_an_explicit_none_finds_no_case() {
+        assert_eq!(parse_challenge("NONE: water is wet by definition."), (false, "water is wet by definition.".to_string()));
+        assert_eq!(parse_challenge("Credible.\nSome argue..."), (true, "Some argue...".to_string()));
+        assert_eq!(parse_challenge("Nonetheless, one could say"), (true, "Nonetheless, one could say".to_string()));
+        assert_eq!(parse_challenge(""), (true, String::new()));
+        assert!(challenge_prompt("Water is wet", true).contains("the statement is not valid"));
+    }
+}
@@ -0,0 +1,66 @@
+use reqwest::Client;
+use serde_json::json;
+use std::time::Duration;
+
+use crate::{redact, Record};
+
+/// Posts a notification for each record that failed to reach consensus and, with
+/// `--alert-on-corruption`, when a periodic chain check fails.
+///
+/// The body carries both `text` (Slack) and `content` (Discord) so either kind of
+/// incoming webhook shows a message, plus the structured tally for other consumers.
+pub struct AlertWebhook {
+    client: Client,
+    url: String,
+}
+
+impl AlertWebhook {
+    pub fn new(url: &str, proxy: Option<&reqwest::Proxy>) -> Self {
+        Self {
+            client: crate::client_builder(proxy)
+                .timeout(Duration::from_secs(3))
+                .build()
+                .expect("static client configuration"),
+            url: url.to_string(),
+        }
+    }
+
+    /// Fire-and-forget: the request runs on its own task and failures are only logged.
+    pub fn notify(&self, record: &Record) {
+        let yes = record.votes.iter().filter(|v| v.is_valid).count();
+        let no = record.votes.len() - yes;
+        let statement = redact::apply(&record.transaction.content);
+        let summary = format!("No consensus on \"{}\" ({} yes / {} no)", statement, yes, no);
+        let body = json!({
+            "text": summary,
+            "content": summary,
+            "record_id": record.id,
+            "statement": statement,
+            "tally": { "yes": yes, "no": no },
+        });
+        self.send(body);
+    }
+
+    /// `--alert-on-corruption`: the periodic chain check failed.
+    pub fn notify_corruption(&self, error: &str) {
+        let summary = format!("Ledger integrity check failed: {}", error);
+        self.send(json!({
+            "text": summary,
+            "content": summary,
+            "integrity_error": error,
+        }));
+    }
+
+    fn send(&self, body: serde_json::Value) {
+        let request = self.client.post(&self.url).json(&body);
+        tokio::spawn(async move {
+            match request.send().await {
+                Ok(response) if !response.status().is_success() => {
+                    tracing::warn!("alert webhook returned HTTP {}", response.status())
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("alert webhook failed: {}", e),
+            }
+        });
+    }
+}
@@ -0,0 +1,495 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::{reputation, AIResponse, Record};
+
+/// Width of each confidence bucket in the calibration report.
+const BUCKET_WIDTH: f32 = 0.2;
+
+#[derive(Debug, Serialize)]
+pub struct CalibrationBucket {
+    pub min_confidence: f32,
+    pub max_confidence: f32,
+    pub votes: usize,
+    pub correct: usize,
+    /// Fraction of votes in this bucket that matched the label; `None` when empty.
+    pub accuracy: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalibrationReport {
+    pub labeled_records: usize,
+    /// Votes on labeled records that reported a confidence.
+    pub votes: usize,
+    pub buckets: Vec<CalibrationBucket>,
+}
+
+/// Reliability-diagram data: for each confidence bucket, how often the votes in it
+/// agreed with the ground-truth label. A well-calibrated agent's 0.8–1.0 bucket is
+/// right about 90% of the time.
+pub fn calibration(records: &[Record]) -> CalibrationReport {
+    let bucket_count = (1.0 / BUCKET_WIDTH).round() as usize;
+    let mut buckets: Vec<CalibrationBucket> = (0..bucket_count)
+        .map(|i| CalibrationBucket {
+            min_confidence: i as f32 * BUCKET_WIDTH,
+            max_confidence: (i + 1) as f32 * BUCKET_WIDTH,
+            votes: 0,
+            correct: 0,
+            accuracy: None,
+        })
+        .collect();
+
+    let mut labeled_records = 0;
+    let mut votes = 0;
+    for record in records {
+        let Some(label) = record.label else { continue };
+        labeled_records += 1;
+        for vote in &record.votes {
+            let Some(confidence) = vote.confidence else { continue };
+            // 1.0 belongs to the top bucket rather than a bucket of its own.
+            let index = ((confidence / BUCKET_WIDTH) as usize).min(bucket_count - 1);
+            buckets[index].votes += 1;
+            if vote.is_valid == label {
+                buckets[index].correct += 1;
+            }
+            votes += 1;
+        }
+    }
+
+    for bucket in &mut buckets {
+        if bucket.votes > 0 {
+            bucket.accuracy = Some(bucket.correct as f64 / bucket.votes as f64);
+        }
+    }
+    CalibrationReport {
+        labeled_records,
+        votes,
+        buckets,
+    }
+}
+
+/// How often one model's votes matched the ground-truth label.
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct ModelAccuracy {
+    pub model: String,
+    /// Votes it cast on labeled records.
+    pub votes: usize,
+    pub correct: usize,
+    /// `correct / votes`; `None` without votes.
+    pub accuracy: Option<f64>,
+}
+
+impl ModelAccuracy {
+    fn count(&mut self, correct: bool) {
+        self.votes += 1;
+        self.correct += usize::from(correct);
+        self.accuracy = Some(self.correct as f64 / self.votes as f64);
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct AccuracyReport {
+    pub labeled_records: usize,
+    /// Every vote on labeled records, whichever model cast it.
+    pub overall: ModelAccuracy,
+    /// The recorded verdicts themselves, one per labeled record.
+    pub verdicts: ModelAccuracy,
+    /// Per model, by name.
+    pub models: Vec<ModelAccuracy>,
+}
+
+/// The ledger as a benchmark: how often each model's votes, all votes and the
+/// verdicts agreed with the labels set through `PUT /api/ledger/{n}/label`.
+/// Abstentions aren't votes and don't count either way.
+pub fn accuracy<'a>(records: impl IntoIterator<Item = &'a Record>) -> AccuracyReport {
+    let mut report = AccuracyReport {
+        labeled_records: 0,
+        overall: ModelAccuracy { model: "all".to_string(), ..Default::default() },
+        verdicts: ModelAccuracy { model: "verdict".to_string(), ..Default::default() },
+        models: Vec::new(),
+    };
+    let mut models: BTreeMap<&str, ModelAccuracy> = BTreeMap::new();
+    for record in records {
+        let Some(label) = record.label else { continue };
+        report.labeled_records += 1;
+        report.verdicts.count(record.consensus == label);
+        for vote in &record.votes {
+            let correct = vote.is_valid == label;
+            report.overall.count(correct);
+            models
+                .entry(&vote.model)
+                .or_insert_with(|| ModelAccuracy { model: vote.model.clone(), ..Default::default() })
+                .count(correct);
+        }
+    }
+    report.models = models.into_values().collect();
+    report
+}
+
+/// How often one model appears in the ledger.
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct ModelUsage {
+    pub model: String,
+    pub votes: usize,
+    pub abstentions: usize,
+}
+
+/// How stable the verdict was across repeated rounds on one statement.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Agreement {
+    pub rounds: usize,
+    pub consensus_rounds: usize,
+    /// Fraction of rounds that reached the more common verdict.
+    pub majority_share: f64,
+    pub mean_truth_score: f64,
+    pub truth_score_stddev: f64,
+}
+
+pub fn agreement(records: &[Record]) -> Agreement {
+    let rounds = records.len();
+    let consensus_rounds = records.iter().filter(|r| r.consensus).count();
+    let n = rounds.max(1) as f64;
+    let mean = records.iter().map(|r| r.truth_score as f64).sum::<f64>() / n;
+    let variance = records.iter().map(|r| (r.truth_score as f64 - mean).powi(2)).sum::<f64>() / n;
+    Agreement {
+        rounds,
+        consensus_rounds,
+        majority_share: consensus_rounds.max(rounds - consensus_rounds) as f64 / n,
+        mean_truth_score: mean,
+        truth_score_stddev: variance.sqrt(),
+    }
+}
+
+/// Whether high-agreement rounds became more or less common between two windows.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Trend {
+    pub window: usize,
+    /// Minimum share of votes on the majority side for a record to count.
+    pub threshold: f64,
+    /// Fraction of high-agreement records among the last `window`.
+    pub recent: Option<f64>,
+    /// The same fraction over the `window` records before those.
+    pub prior: Option<f64>,
+    pub change: Option<f64>,
+    /// `rising`, `falling`, `steady`, or `insufficient data` without both windows.
+    pub direction: &'static str,
+    /// Under `--half-life-days`, the rate over the whole ledger with each record
+    /// weighted by its age relative to the newest.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decayed: Option<f64>,
+}
+
+/// Share of a record's votes on the majority side; `None` for a record without votes.
+fn majority_share(record: &Record) -> Option<f64> {
+    let total = record.votes.len();
+    if total == 0 {
+        return None;
+    }
+    let yes = record.votes.iter().filter(|v| v.is_valid).count();
+    Some(yes.max(total - yes) as f64 / total as f64)
+}
+
+fn high_agreement_rate(records: &[Record], threshold: f64) -> Option<f64> {
+    let shares: Vec<f64> = records.iter().filter_map(majority_share).collect();
+    if shares.is_empty() {
+        return None;
+    }
+    Some(shares.iter().filter(|&&s| s >= threshold).count() as f64 / shares.len() as f64)
+}
+
+/// Time-decayed high-agreement rate: each record's weight halves for every
+/// `half_life_days` it is older than the newest record.
+fn decayed_rate(records: &[Record], threshold: f64, half_life_days: f64) -> Option<f64> {
+    let newest = records.iter().map(|r| r.timestamp).max()?;
+    let (mut high, mut total) = (0.0, 0.0);
+    for record in records {
+        let Some(share) = majority_share(record) else { continue };
+        let weight = reputation::decay(newest - record.timestamp, Some(half_life_days));
+        total += weight;
+        if share >= threshold {
+            high += weight;
+        }
+    }
+    (total > 0.0).then(|| high / total)
+}
+
+/// Compares how often agents agreed (at least `threshold` of votes on one side; 1.0
+/// means unanimous) in the last `window` records against the `window` before them.
+pub fn trend(records: &[Record], window: usize, threshold: f64, half_life_days: Option<f64>) -> Trend {
+    let split = records.len().saturating_sub(window);
+    let recent = high_agreement_rate(&records[split..], threshold);
+    let prior = high_agreement_rate(&records[split.saturating_sub(window)..split], threshold);
+    let change = recent.zip(prior).map(|(r, p)| r - p);
+    let direction = match change {
+        None => "insufficient data",
+        Some(c) if c > 1e-9 => "rising",
+        Some(c) if c < -1e-9 => "falling",
+        Some(_) => "steady",
+    };
+    Trend {
+        window,
+        threshold,
+        recent,
+        prior,
+        change,
+        direction,
+        decayed: half_life_days.and_then(|days| decayed_rate(records, threshold, days)),
+    }
+}
+
+/// A record whose verdict differs under the what-if strategy.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Flip {
+    /// 1-based ledger position.
+    pub index: usize,
+    pub id: String,
+    pub statement: String,
+    pub recorded: bool,
+    pub recomputed: bool,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct WhatIf {
+    pub strategy: String,
+    pub records: usize,
+    pub flipped: usize,
+    pub flips: Vec<Flip>,
+}
+
+/// Recomputes every record's verdict from its stored votes with `decide` and
+/// reports the ones that come out differently from what was recorded.
+pub fn what_if(records: &[Record], strategy: &str, decide: impl Fn(&Record) -> bool) -> WhatIf {
+    let flips: Vec<Flip> = records
+        .iter()
+        .enumerate()
+        .filter_map(|(i, record)| {
+            let recomputed = decide(record);
+            (recomputed != record.consensus).then(|| Flip {
+                index: i + 1,
+                id: record.id.clone(),
+                statement: record.transaction.content.clone(),
+                recorded: record.consensus,
+                recomputed,
+            })
+        })
+        .collect();
+    WhatIf {
+        strategy: strategy.to_string(),
+        records: records.len(),
+        flipped: flips.len(),
+        flips,
+    }
+}
+
+/// How often a verdict survives bootstrap resampling of its own votes.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Robustness {
+    /// 1-based ledger position.
+    pub index: usize,
+    pub id: String,
+    pub recorded: bool,
+    /// Votes resampled from, after `--error-policy no` failures are counted.
+    pub votes: usize,
+    pub resamples: usize,
+    /// Resamples that still reached the recorded verdict.
+    pub same: usize,
+    /// `same / resamples`: near 1 the verdict is stable, near 0.5 it hinges on
+    /// which agents happened to answer.
+    pub stability: f64,
+    /// Standard error of `stability` as an estimate from this many resamples.
+    pub std_error: f64,
+}
+
+/// Draws `resamples` vote sets of the record's size, agents picked with
+/// replacement, and decides each one with `decide`. Nothing is queried.
+pub fn robustness<R: rand::Rng>(
+    index: usize,
+    record: &Record,
+    resamples: usize,
+    rng: &mut R,
+    decide: impl Fn(&[AIResponse]) -> bool,
+) -> Robustness {
+    let votes = record.tally();
+    let mut sample = Vec::with_capacity(votes.len());
+    let mut same = 0;
+    for _ in 0..resamples {
+        sample.clear();
+        sample.extend((0..votes.len()).map(|_| votes[rng.gen_range(0..votes.len())].clone()));
+        if decide(&sample) == record.consensus {
+            same += 1;
+        }
+    }
+    let stability = if resamples == 0 { 0.0 } else { same as f64 / resamples as f64 };
+    let std_error = if resamples == 0 { 0.0 } else { (stability * (1.0 - stability) / resamples as f64).sqrt() };
+    Robustness {
+        index,
+        id: record.id.clone(),
+        recorded: record.consensus,
+        votes: votes.len(),
+        resamples,
+        same,
+        stability,
+        std_error,
+    }
+}
+
+/// Positions (0-based) of the records `/api/retry-failed` reruns: for each statement,
+/// its latest record, when that one failed consensus. A statement whose retry
+/// passed isn't retried again, and one that failed twice is retried once.
+pub fn latest_failed(records: &[Record]) -> Vec<usize> {
+    let mut latest: BTreeMap<&str, usize> = BTreeMap::new();
+    for (i, record) in records.iter().enumerate() {
+        latest.insert(&record.transaction.content, i);
+    }
+    let mut failed: Vec<usize> = latest.into_values().filter(|&i| !records[i].consensus).collect();
+    failed.sort_unstable();
+    failed
+}
+
+fn usage_for<'a, 'b>(usage: &'b mut BTreeMap<&'a str, ModelUsage>, model: &'a str) -> &'b mut ModelUsage {
+    usage.entry(model).or_insert_with(|| ModelUsage {
+        model: model.to_string(),
+        ..Default::default()
+    })
+}
+
+/// Every distinct model that voted or abstained anywhere in the ledger, by name.
+/// Unlike the configured pool, this reflects which backends actually took part.
+pub fn model_usage(records: &[Record]) -> Vec<ModelUsage> {
+    let mut usage: BTreeMap<&str, ModelUsage> = BTreeMap::new();
+    for record in records {
+        for vote in &record.votes {
+            usage_for(&mut usage, &vote.model).votes += 1;
+        }
+        for abstention in &record.abstentions {
+            usage_for(&mut usage, &abstention.model).abstentions += 1;
+        }
+    }
+    usage.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::tests::record;
+    use crate::Abstention;
+    use rand::SeedableRng;
+
+    #[test]
+    fn trend_compares_the_last_window_with_the_one_before() {
+        let split = |yes: usize, no: usize| {
+            let mut r = record("r");
+            let vote = r.votes[0].clone();
+            r.votes = std::iter::repeat_n(true, yes)
+                .chain(std::iter::repeat_n(false, no))
+                .map(|is_valid| AIResponse { is_valid, ..vote.clone() })
+                .collect();
+            r
+        };
+        // Prior window: one unanimous of two. Recent window: both unanimous.
+        let records = vec![split(3, 0), split(2, 1), split(3, 0), split(0, 3)];
+        let t = trend(&records, 2, 1.0, None);
+        assert_eq!((t.prior, t.recent, t.direction, t.decayed), (Some(0.5), Some(1.0), "rising", None));
+        assert_eq!(trend(&records, 2, 0.6, None).direction, "steady");
+        assert_eq!(trend(&records[..1], 2, 1.0, None).direction, "insufficient data");
+        // Same-time records weigh the same: three of four unanimous.
+        assert_eq!(trend(&records, 2, 1.0, Some(7.0)).decayed, Some(0.75));
+    }
+
+    #[test]
+    fn what_if_lists_records_whose_verdict_would_flip() {
+        let mut split = record("b");
+        let vote = split.votes[0].clone();
+        split.votes = vec![vote.clone(), AIResponse { is_valid: false, ..vote }];
+        let records = vec![record("a"), split];
+        let unanimous = |record: &Record| record.votes.iter().all(|v| v.is_valid);
+        let report = what_if(&records, "unanimous", unanimous);
+        assert_eq!((report.records, report.flipped), (2, 1));
+        assert_eq!((report.flips[0].index, report.flips[0].recorded, report.flips[0].recomputed), (2, true, false));
+    }
+
+    #[test]
+    fn robustness_counts_resamples_that_keep_the_verdict() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let majority = |votes: &[AIResponse]| votes.iter().filter(|v| v.is_valid).count() * 2 > votes.len();
+        let mut unanimous = record("a");
+        let vote = unanimous.votes[0].clone();
+        unanimous.votes = vec![vote.clone(); 3];
+        let report = robustness(1, &unanimous, 200, &mut rng, majority);
+        assert_eq!((report.votes, report.same, report.stability, report.std_error), (3, 200, 1.0, 0.0));
+
+        // 2 of 3: a resample keeps the verdict when it draws two or three "yes"
+        // agents, which happens with probability 20/27.
+        let mut split = unanimous.clone();
+        split.votes[2].is_valid = false;
+        let report = robustness(2, &split, 4000, &mut rng, majority);
+        assert!((report.stability - 20.0 / 27.0).abs() < 4.0 * report.std_error, "{:?}", report);
+        assert!(report.std_error > 0.0 && report.std_error < 0.01);
+        assert_eq!(robustness(2, &split, 0, &mut rng, majority).stability, 0.0);
+    }
+
+    #[test]
+    fn only_each_statements_latest_failure_is_retried() {
+        let verdict = |content: &str, consensus: bool| Record { consensus, ..record(content) };
+        let records = vec![
+            verdict("a", false),
+            verdict("b", false),
+            verdict("a", true),
+            verdict("c", false),
+            verdict("b", false),
+        ];
+        assert_eq!(latest_failed(&records), [3, 4]);
+        assert!(latest_failed(&records[..1]).iter().eq([&0]));
+    }
+
+    #[test]
+    fn agreement_summarizes_repeated_rounds() {
+        let mut runs = vec![record("a"), record("a"), record("a"), record("a")];
+        runs[3].consensus = false;
+        runs[3].truth_score = 60.0;
+        let summary = agreement(&runs);
+        assert_eq!(summary.rounds, 4);
+        assert_eq!(summary.consensus_rounds, 3);
+        assert_eq!(summary.majority_share, 0.75);
+        assert_eq!(summary.mean_truth_score, 90.0);
+        assert!((summary.truth_score_stddev - 17.3205).abs() < 1e-3);
+    }
+
+    #[test]
+    fn accuracy_counts_votes_on_labeled_records_only() {
+        let mut right = record("Water is wet");
+        right.label = Some(true);
+        let mut wrong = record("The moon is cheese");
+        wrong.label = Some(false);
+        let mut grok = wrong.votes[0].clone();
+        grok.model = "x/grok".to_string();
+        grok.is_valid = false;
+        wrong.votes.push(grok);
+        let report = accuracy(&[right, wrong, record("Unlabeled")]);
+        assert_eq!(report.labeled_records, 2);
+        assert_eq!((report.overall.votes, report.overall.correct), (3, 2));
+        assert_eq!((report.verdicts.votes, report.verdicts.correct), (2, 1));
+        let gpt = &report.models[0];
+        assert_eq!((gpt.model.as_str(), gpt.votes, gpt.correct, gpt.accuracy), ("openai/gpt-4o", 2, 1, Some(0.5)));
+        assert_eq!(report.models[1].accuracy, Some(1.0));
+    }
+
+    #[test]
+    fn model_usage_counts_votes_and_abstentions_per_model() {
+        let mut first = record("a");
+        first.abstentions.push(Abstention {
+            agent_name: "x/grok".to_string(),
+            model: "x/grok".to_string(),
+            reason: "timeout".to_string(),
+            refusal: false,
+        });
+        let usage = model_usage(&[first, record("b")]);
+        assert_eq!(
+            usage,
+            [
+                ModelUsage { model: "openai/gpt-4o".to_string(), votes: 2, abstentions: 0 },
+                ModelUsage { model: "x/grok".to_string(), votes: 0, abstentions: 1 },
+            ]
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::ledger;
+use crate::solana::{SolanaClient, SolanaError};
+use crate::store::StoreError;
+use crate::{persist, AppState, Record, LEDGER};
+
+/// One Solana memo transaction covering a batch of records.
+///
+/// The memo carries the Merkle root of the batch's record hashes, in ledger order,
+/// so any single record can later be proven part of the batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Anchor {
+    pub signature: String,
+    pub merkle_root: String,
+    /// Ids of the covered records, in the order their hashes were rooted.
+    pub record_ids: Vec<String>,
+    pub anchored_at: DateTime<Utc>,
+    /// Set once the cluster reports the transaction finalized.
+    #[serde(default)]
+    pub finalized: bool,
+    /// Slot the transaction landed in, once a status poll has seen it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u64>,
+}
+
+/// `/api/anchors/status` entry: an anchor with its latest known cluster status.
+#[derive(Debug, Serialize)]
+pub struct AnchorStatus {
+    pub signature: String,
+    pub merkle_root: String,
+    pub records: usize,
+    pub anchored_at: DateTime<Utc>,
+    /// "processed", "confirmed" or "finalized"; "unknown" when the cluster hasn't
+    /// seen it or couldn't be asked.
+    pub confirmation: String,
+    pub slot: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AnchorError {
+    #[error(transparent)]
+    Solana(#[from] SolanaError),
+    #[error("anchor not persisted: {0}")]
+    Store(#[from] StoreError),
+}
+
+fn memo(merkle_root: &str, count: usize) -> String {
+    format!("truth-terminal anchor {} ({} records)", merkle_root, count)
+}
+
+/// `--anchor`: writes one committed record's hash to Solana in its own memo
+/// transaction and returns the signature, which an explorer shows with the memo.
+pub async fn anchor_record(solana: &SolanaClient, record: &Record) -> Result<String, SolanaError> {
+    let memo = format!("truth-terminal record {} {}", record.id, record.hash);
+    solana.send_memo_once(&memo, &record.hash).await
+}
+
+/// Anchors every record not yet covered by an anchor in one memo transaction.
+/// Returns `None` when there is nothing new.
+pub async fn anchor_pending(state: &AppState) -> Result<Option<Anchor>, AnchorError> {
+    let Some(solana) = &state.solana else {
+        return Ok(None);
+    };
+    let (record_ids, hashes): (Vec<String>, Vec<String>) = {
+        let anchors = state.anchors.read().await;
+        let covered: HashSet<&str> = anchors.iter().flat_map(|a| a.record_ids.iter().map(String::as_str)).collect();
+        LEDGER
+            .read()
+            .await
+            .records()
+            .iter()
+            .filter(|r| !covered.contains(r.id.as_str()))
+            .map(|r| (r.id.clone(), r.hash.clone()))
+            .unzip()
+    };
+    if record_ids.is_empty() {
+        return Ok(None);
+    }
+
+    let merkle_root = ledger::merkle_root(&hashes);
+    // The root doubles as the idempotency marker: a retried batch has the same one.
+    let signature = solana
+        .send_memo_once(&memo(&merkle_root, record_ids.len()), &merkle_root)
+        .await?;
+    let anchor = Anchor {
+        signature,
+        merkle_root,
+        record_ids,
+        anchored_at: Utc::now(),
+        finalized: false,
+        slot: None,
+    };
+    tracing::info!("anchored {} records in {}", anchor.record_ids.len(), anchor.signature);
+    state.audit(|| {
+        format!(
+            "anchor: {} records, merkle root {}, solana signature {}",
+            anchor.record_ids.len(),
+            anchor.merkle_root,
+            anchor.signature
+        )
+    });
+
+    // The transaction is already on its way, so keep the anchor in memory even if
+    // persisting it fails.
+    state.anchors.write().await.push(anchor.clone());
+    let stored = anchor.clone();
+    persist(&state.store, move |store| store.append_anchor(&stored)).await?;
+    Ok(Some(anchor))
+}
+
+/// Background task: every `interval`, anchors whatever has been committed since the
+/// last anchor.
+pub async fn run(state: Arc<AppState>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        if let Err(e) = anchor_pending(&state).await {
+            tracing::warn!("auto-anchor failed: {}", e);
+        }
+    }
+}
+
+/// Records that an anchor finalized (in `slot`, if known) and persists it.
+async fn mark_finalized(state: &AppState, signature: &str, slot: Option<u64>) {
+    let finalized = {
+        let mut anchors = state.anchors.write().await;
+        let anchor = anchors.iter_mut().find(|a| a.signature == signature);
+        anchor.map(|a| {
+            a.finalized = true;
+            a.slot = slot.or(a.slot);
+            a.clone()
+        })
+    };
+    if let Some(anchor) = finalized {
+        if let Err(e) = persist(&state.store, move |store| store.update_anchor(&anchor)).await {
+            tracing::warn!("finalized anchor {} not persisted: {}", signature, e);
+        }
+    }
+}
+
+/// The `limit` most recent anchors, newest first, polling the cluster for any not
+/// yet finalized. Anchors seen finalizing are persisted as such.
+pub async fn status(state: &AppState, limit: usize) -> Vec<AnchorStatus> {
+    let recent: Vec<Anchor> = state.anchors.read().await.iter().rev().take(limit).cloned().collect();
+    let mut statuses = Vec::with_capacity(recent.len());
+    for anchor in recent {
+        let (confirmation, slot, error) = if anchor.finalized {
+            (Some("finalized".to_string()), anchor.slot, None)
+        } else {
+            match &state.solana {
+                None => (None, anchor.slot, Some("Solana is not configured".to_string())),
+                Some(solana) => match solana.landed(&anchor.signature).await {
+                    Ok(Some(landed)) => {
+                        if landed.confirmation.as_deref() == Some("finalized") {
+                            mark_finalized(state, &anchor.signature, landed.slot).await;
+                        }
+                        (landed.confirmation, landed.slot, None)
+                    }
+                    Ok(None) => (None, None, None),
+                    Err(e) => (None, anchor.slot, Some(e.to_string())),
+                },
+            }
+        };
+        statuses.push(AnchorStatus {
+            signature: anchor.signature,
+            merkle_root: anchor.merkle_root,
+            records: anchor.record_ids.len(),
+            anchored_at: anchor.anchored_at,
+            confirmation: confirmation.unwrap_or_else(|| "unknown".to_string()),
+            slot,
+            error,
+        });
+    }
+    statuses
+}
+
+/// Polls every unfinalized anchor until all are finalized or `timeout` passes,
+/// printing progress and persisting each anchor as it finalizes. Used at shutdown.
+pub async fn await_confirmations(state: &AppState, timeout: Duration) {
+    let Some(solana) = &state.solana else {
+        return;
+    };
+    let deadline = Instant::now() + timeout;
+    loop {
+        let pending: Vec<String> = state
+            .anchors
+            .read()
+            .await
+            .iter()
+            .filter(|a| !a.finalized)
+            .map(|a| a.signature.clone())
+            .collect();
+        if pending.is_empty() {
+            println!("All anchors finalized");
+            return;
+        }
+        if Instant::now() >= deadline {
+            println!("Gave up waiting: {} anchor(s) not finalized", pending.len());
+            return;
+        }
+        println!("Waiting for {} anchor(s) to finalize...", pending.len());
+
+        for signature in pending {
+            match solana.landed(&signature).await {
+                Ok(Some(landed)) if landed.confirmation.as_deref() == Some("finalized") => {
+                    mark_finalized(state, &signature, landed.slot).await
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("status of {}: {}", signature, e),
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(2).min(deadline.saturating_duration_since(Instant::now()))).await;
+    }
+}
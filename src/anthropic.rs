@@ -0,0 +1,158 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::env;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::profile::{self, Profile, Stage};
+use crate::retry;
+use crate::{client_builder, complete_as, parse_vote, AIProvider, AIResponse, Api, ProviderError, ProviderReply, ProviderSettings};
+
+const MESSAGES_URL: &str = "https://api.anthropic.com/v1/messages";
+const API_VERSION: &str = "2023-06-01";
+/// The Messages API requires a cap; a verdict, a confidence and a one-sentence
+/// reason fit well within it.
+const MAX_TOKENS: u32 = 256;
+
+/// Calls Anthropic's Messages API directly for `anthropic/*` models when
+/// `--anthropic-direct` is set, instead of going through OpenRouter.
+pub struct AnthropicProvider {
+    client: Client,
+    agent_name: String,
+    model: String,
+    settings: Arc<ProviderSettings>,
+}
+
+impl AnthropicProvider {
+    pub fn new(agent_name: &str, model: &str, settings: Arc<ProviderSettings>) -> Self {
+        Self {
+            client: client_builder(settings.proxy.as_ref())
+                .build()
+                .expect("static client configuration"),
+            agent_name: agent_name.to_string(),
+            model: model.to_string(),
+            settings,
+        }
+    }
+}
+
+#[async_trait]
+impl AIProvider for AnthropicProvider {
+    fn agent_name(&self) -> &str {
+        &self.agent_name
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn validate(&self, text: &str, debate: Option<&str>) -> Result<AIResponse, ProviderError> {
+        let api_key = env::var("ANTHROPIC_API_KEY").map_err(|_| ProviderError::MissingKey("ANTHROPIC_API_KEY"))?;
+        let prompt = self.settings.budget.fit(&self.model, text, debate)?;
+        let body = json!({
+            "model": api_model(&self.model),
+            "max_tokens": MAX_TOKENS,
+            "messages": [{ "role": "user", "content": prompt }],
+        });
+        let request = self
+            .client
+            .post(MESSAGES_URL)
+            .header("x-api-key", api_key)
+            .header("anthropic-version", API_VERSION);
+        let request = self.settings.with_extra_headers("anthropic", request);
+        let completion = complete_as(Api::Messages, request, body, &self.model, &self.settings).await?;
+
+        self.settings.check_refusal(&completion.text)?;
+        let (is_valid, confidence, rationale) = parse_vote(&completion.text)?;
+        Ok(AIResponse {
+            agent_name: self.agent_name.clone(),
+            model: self.model.clone(),
+            is_valid,
+            confidence,
+            attempts: completion.attempts,
+            lang: self.settings.budget.vote_lang(),
+            system_fingerprint: None,
+            fallback_from: None,
+            self_consistency: None,
+            rationale,
+        })
+    }
+}
+
+/// Anthropic's id for a pool model: the pool names models without the date suffix
+/// the Messages API wants. Other `anthropic/` ids pass through without the prefix.
+fn api_model(model: &str) -> &str {
+    match model.trim_start_matches("anthropic/") {
+        "claude-3-haiku" => "claude-3-haiku-20240307",
+        other => other,
+    }
+}
+
+/// Reads a Messages API response for `complete_as`, timing it like `read_reply`.
+pub(crate) async fn read_response(
+    response: reqwest::Response,
+    profile: Option<&Profile>,
+) -> Result<ProviderReply, ProviderError> {
+    let status = response.status();
+    let retry_after = retry::retry_after(response.headers());
+    let started = Instant::now();
+    let bytes = response.bytes().await.map_err(ProviderError::Interrupted)?;
+    profile::record(profile, Stage::AgentHttp, started);
+    let started = Instant::now();
+    let text = read_message(status, retry_after, &bytes);
+    profile::record(profile, Stage::JsonParse, started);
+    Ok(ProviderReply { text: text?, fingerprint: None })
+}
+
+/// The text of a Messages API response; errors as `read_reply` reports them for
+/// chat completions.
+fn read_message(
+    status: reqwest::StatusCode,
+    retry_after: Option<std::time::Duration>,
+    bytes: &[u8],
+) -> Result<String, ProviderError> {
+    let parsed = serde_json::from_slice::<Value>(bytes);
+    if !status.is_success() {
+        let message = parsed
+            .ok()
+            .and_then(|body| body["error"]["message"].as_str().map(str::to_string))
+            .unwrap_or_else(|| String::from_utf8_lossy(bytes).chars().take(200).collect());
+        return Err(ProviderError::Status { status, message, retry_after });
+    }
+    let body = parsed.map_err(|_| ProviderError::Truncated(bytes.len()))?;
+    if let Some(error) = body.get("error") {
+        let message = error["message"].as_str().map_or_else(|| error.to_string(), str::to_string);
+        return Err(ProviderError::Malformed(message));
+    }
+    let text: String = body["content"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|block| block["type"] == "text")
+        .filter_map(|block| block["text"].as_str())
+        .collect();
+    if text.is_empty() {
+        return Err(ProviderError::Malformed("no completion in response".to_string()));
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn reads_text_blocks_and_reports_errors() {
+        let ok = br#"{"type":"message","content":[{"type":"text","text":"Yes, 0.9"}],"stop_reason":"end_turn"}"#;
+        assert_eq!(read_message(StatusCode::OK, None, ok).unwrap(), "Yes, 0.9");
+        let failed = br#"{"type":"error","error":{"type":"api_error","message":"Internal error"}}"#;
+        let err = read_message(StatusCode::INTERNAL_SERVER_ERROR, None, failed).unwrap_err();
+        assert_eq!(err.to_string(), "HTTP 500 Internal Server Error: Internal error");
+        assert!(matches!(read_message(StatusCode::OK, None, br#"{"content":[{"type":"te"#), Err(ProviderError::Truncated(_))));
+        assert!(matches!(read_message(StatusCode::OK, None, br#"{"content":[]}"#), Err(ProviderError::Malformed(_))));
+        assert_eq!(api_model("anthropic/claude-3-haiku"), "claude-3-haiku-20240307");
+        assert_eq!(api_model("anthropic/claude-3-5-sonnet-latest"), "claude-3-5-sonnet-latest");
+    }
+}
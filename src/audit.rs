@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::{redact, AIResponse, Abstention};
+
+/// `--audit-log`: an append-only, human-readable trail of what the server did, one
+/// timestamped line per event. Separate from the ledger, which holds verdicts, and
+/// from tracing output, which is for debugging.
+pub struct AuditLog {
+    file: Mutex<File>,
+}
+
+impl AuditLog {
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file: Mutex::new(file) })
+    }
+
+    /// Appends one event and flushes it. A failed write is logged, never fatal.
+    pub fn record(&self, event: &str) {
+        let mut file = self.file.lock().unwrap();
+        let written = file
+            .write_all(line(Utc::now(), event).as_bytes())
+            .and_then(|()| file.flush());
+        if let Err(e) = written {
+            tracing::warn!("audit log write failed: {}", e);
+        }
+    }
+}
+
+/// Statements are quoted after `--redact`, like everything else shown to people.
+pub fn quote(text: &str) -> String {
+    format!("{:?}", redact::apply(text))
+}
+
+/// One line per agent: its vote and confidence, or why it abstained.
+pub fn agent_results(id: &str, round: usize, votes: &[AIResponse], abstentions: &[Abstention]) -> Vec<String> {
+    let votes = votes.iter().map(|v| {
+        let confidence = v.confidence.map(|c| format!(" ({:.2})", c)).unwrap_or_default();
+        let verdict = if v.is_valid { "yes" } else { "no" };
+        format!("vote {} round {}: {} [{}] {}{}", id, round, v.agent_name, v.model, verdict, confidence)
+    });
+    let abstentions = abstentions
+        .iter()
+        .map(|a| format!("abstain {} round {}: {} [{}] {}", id, round, a.agent_name, a.model, a.reason));
+    votes.chain(abstentions).collect()
+}
+
+fn line(at: DateTime<Utc>, event: &str) -> String {
+    // Events are single lines; a stray break would forge the next entry.
+    format!("{} {}\n", at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true), event.replace(['\n', '\r'], " "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_single_timestamped_lines() {
+        let at = DateTime::parse_from_rfc3339("2026-10-14T09:30:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(line(at, "submit r1 \"x\"\nforged"), "2026-10-14T09:30:00.000Z submit r1 \"x\" forged\n");
+        let vote = AIResponse {
+            agent_name: "agent-1".to_string(),
+            model: "x/grok".to_string(),
+            is_valid: false,
+            confidence: Some(0.7),
+            attempts: 1,
+            lang: None,
+            system_fingerprint: None,
+            fallback_from: None,
+            self_consistency: None,
+            rationale: None,
+        };
+        assert_eq!(agent_results("r1", 2, &[vote], &[]), ["vote r1 round 2: agent-1 [x/grok] no (0.70)"]);
+    }
+}
@@ -0,0 +1,198 @@
+use async_trait::async_trait;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::{AIProvider, AIResponse, ProviderError};
+
+struct Inner {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    trial_in_flight: bool,
+}
+
+/// Where a breaker stands, as reported by `/api/status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum BreakerState {
+    Closed,
+    Open { retry_in_secs: u64 },
+    /// The cooldown is over; the next request is the trial.
+    HalfOpen,
+}
+
+/// Stops traffic to a provider after `threshold` consecutive failures, for `cooldown`.
+/// Once the cooldown ends the breaker is half-open: one trial request is let through,
+/// and its outcome closes the breaker or reopens it.
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            cooldown,
+            inner: Mutex::new(Inner {
+                consecutive_failures: 0,
+                open_until: None,
+                trial_in_flight: false,
+            }),
+        }
+    }
+
+    /// Admits a request unless the breaker is open or a half-open trial is already
+    /// in flight.
+    fn permit(&self) -> Option<Permit<'_>> {
+        let mut inner = self.inner.lock().unwrap();
+        let trial = match inner.open_until {
+            None => false,
+            Some(until) if Instant::now() < until => return None,
+            Some(_) if inner.trial_in_flight => return None,
+            Some(_) => true,
+        };
+        inner.trial_in_flight |= trial;
+        Some(Permit { breaker: self, trial })
+    }
+
+    pub fn state(&self) -> BreakerState {
+        match self.inner.lock().unwrap().open_until {
+            None => BreakerState::Closed,
+            Some(until) => match until.checked_duration_since(Instant::now()) {
+                Some(remaining) if !remaining.is_zero() => BreakerState::Open {
+                    retry_in_secs: remaining.as_secs_f64().ceil() as u64,
+                },
+                _ => BreakerState::HalfOpen,
+            },
+        }
+    }
+
+    fn record_success(&self, model: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.open_until.take().is_some() {
+            tracing::info!("circuit breaker for {} closed", model);
+        }
+        inner.consecutive_failures = 0;
+    }
+
+    fn record_failure(&self, model: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures += 1;
+        // A failed half-open trial reopens immediately.
+        if inner.consecutive_failures >= self.threshold || inner.open_until.is_some() {
+            inner.open_until = Some(Instant::now() + self.cooldown);
+            tracing::warn!(
+                "circuit breaker for {} opened after {} consecutive failures; pausing for {:?}",
+                model,
+                inner.consecutive_failures,
+                self.cooldown
+            );
+        }
+    }
+}
+
+/// A request admitted by `CircuitBreaker::permit`. Dropping a trial permit without
+/// an outcome (e.g. a cancelled request) frees the slot for the next trial.
+struct Permit<'a> {
+    breaker: &'a CircuitBreaker,
+    trial: bool,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if self.trial {
+            self.breaker.inner.lock().unwrap().trial_in_flight = false;
+        }
+    }
+}
+
+/// Wraps a provider so it abstains without a network call while its breaker is open.
+pub struct BreakerProvider {
+    inner: Box<dyn AIProvider>,
+    breaker: CircuitBreaker,
+}
+
+impl BreakerProvider {
+    pub fn new(inner: Box<dyn AIProvider>, breaker: CircuitBreaker) -> Self {
+        Self { inner, breaker }
+    }
+}
+
+#[async_trait]
+impl AIProvider for BreakerProvider {
+    fn agent_name(&self) -> &str {
+        self.inner.agent_name()
+    }
+
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+
+    fn breaker_state(&self) -> Option<BreakerState> {
+        Some(self.breaker.state())
+    }
+
+    async fn validate(&self, text: &str, debate: Option<&str>) -> Result<AIResponse, ProviderError> {
+        let Some(_permit) = self.breaker.permit() else {
+            return Err(ProviderError::CircuitOpen);
+        };
+        let result = self.inner.validate(text, debate).await;
+        match &result {
+            // A refusal is an answer: the provider is up.
+            Err(e) if !e.is_refusal() => self.breaker.record_failure(self.model()),
+            _ => self.breaker.record_success(self.model()),
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_after_threshold_consecutive_failures() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        breaker.record_failure("m");
+        assert!(breaker.permit().is_some());
+        breaker.record_failure("m");
+        assert!(breaker.permit().is_none());
+        assert_eq!(breaker.state(), BreakerState::Open { retry_in_secs: 60 });
+    }
+
+    #[test]
+    fn success_resets_the_failure_count() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        breaker.record_failure("m");
+        breaker.record_success("m");
+        breaker.record_failure("m");
+        assert!(breaker.permit().is_some());
+    }
+
+    #[test]
+    fn half_open_admits_a_single_trial() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+        breaker.record_failure("m");
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        let trial = breaker.permit().expect("first request is the trial");
+        assert!(trial.trial);
+        assert!(breaker.permit().is_none());
+        drop(trial);
+        assert!(breaker.permit().is_some());
+    }
+
+    #[test]
+    fn successful_trial_closes_the_breaker() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+        breaker.record_failure("m");
+        let trial = breaker.permit().unwrap();
+        breaker.record_success("m");
+        drop(trial);
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        let first = breaker.permit().unwrap();
+        let second = breaker.permit().unwrap();
+        assert!(!first.trial && !second.trial);
+    }
+}
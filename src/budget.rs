@@ -0,0 +1,176 @@
+use std::sync::{Arc, OnceLock};
+use tiktoken_rs::CoreBPE;
+
+use crate::config::{Lang, Overflow, TokenizerKind};
+use crate::context::Context;
+use crate::{guard, validation_prompt, ProviderError};
+
+/// Counts and trims prompt tokens for one model family.
+pub trait Tokenizer: Send + Sync {
+    fn count(&self, text: &str) -> usize;
+    /// The longest prefix of `text` that fits in `max` tokens.
+    fn truncate(&self, text: &str, max: usize) -> String;
+}
+
+/// OpenAI's BPE encodings; other providers' tokenizers are close enough to
+/// cl100k for a context-limit guard.
+struct Tiktoken(CoreBPE);
+
+impl Tokenizer for Tiktoken {
+    fn count(&self, text: &str) -> usize {
+        self.0.encode_with_special_tokens(text).len()
+    }
+
+    fn truncate(&self, text: &str, max: usize) -> String {
+        let mut tokens = self.0.encode_with_special_tokens(text);
+        tokens.truncate(max);
+        // A cut through a multi-byte character fails to decode; back off until it doesn't.
+        while !tokens.is_empty() {
+            if let Ok(prefix) = self.0.decode(tokens.clone()) {
+                return prefix;
+            }
+            tokens.pop();
+        }
+        String::new()
+    }
+}
+
+/// The character heuristic used by `--estimate`: about four characters per token.
+struct CharEstimate;
+
+impl Tokenizer for CharEstimate {
+    fn count(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(4)
+    }
+
+    fn truncate(&self, text: &str, max: usize) -> String {
+        text.chars().take(max * 4).collect()
+    }
+}
+
+fn tokenizer_for(kind: TokenizerKind, model: &str) -> Arc<dyn Tokenizer> {
+    static CL100K: OnceLock<Arc<dyn Tokenizer>> = OnceLock::new();
+    static O200K: OnceLock<Arc<dyn Tokenizer>> = OnceLock::new();
+    match kind {
+        TokenizerKind::Chars => Arc::new(CharEstimate),
+        TokenizerKind::Tiktoken if model.contains("gpt-4o") => O200K
+            .get_or_init(|| Arc::new(Tiktoken(tiktoken_rs::o200k_base().expect("bundled encoding"))))
+            .clone(),
+        TokenizerKind::Tiktoken => CL100K
+            .get_or_init(|| Arc::new(Tiktoken(tiktoken_rs::cl100k_base().expect("bundled encoding"))))
+            .clone(),
+    }
+}
+
+/// Per-model prompt token limits from `--max-prompt-tokens`.
+#[derive(Debug, Default)]
+pub struct PromptBudget {
+    pub tokenizer: TokenizerKind,
+    /// `(None, n)` applies to every model without its own entry.
+    pub limits: Vec<(Option<String>, usize)>,
+    pub overflow: Overflow,
+    /// `--prompt-guard`: fence the statement off as data.
+    pub guard: bool,
+    /// `--lang`: the language of the prompt's framing.
+    pub lang: Lang,
+    /// `--context`: the reference document every prompt is grounded in.
+    pub context: Option<Context>,
+}
+
+impl PromptBudget {
+    fn prompt(&self, text: &str, debate: Option<&str>) -> String {
+        let prompt = if self.guard {
+            guard::guarded_prompt(self.lang, text, debate)
+        } else {
+            validation_prompt(self.lang, text, debate)
+        };
+        match &self.context {
+            Some(context) => context.ground(self.lang, &prompt),
+            None => prompt,
+        }
+    }
+
+    /// The language recorded on votes: `None` for the default English.
+    pub fn vote_lang(&self) -> Option<String> {
+        (self.lang != Lang::En).then(|| self.lang.code().to_string())
+    }
+
+    fn limit(&self, model: &str) -> Option<usize> {
+        let exact = self.limits.iter().find(|(m, _)| m.as_deref() == Some(model));
+        exact.or_else(|| self.limits.iter().find(|(m, _)| m.is_none())).map(|(_, n)| *n)
+    }
+
+    /// Builds the validation prompt for `text` (with a debate round's context, if
+    /// any) and checks it against the model's limit, trimming the statement or
+    /// refusing per `--prompt-overflow`.
+    pub fn fit(&self, model: &str, text: &str, debate: Option<&str>) -> Result<String, ProviderError> {
+        let prompt = self.prompt(text, debate);
+        let Some(max) = self.limit(model) else {
+            return Ok(prompt);
+        };
+        let tokenizer = tokenizer_for(self.tokenizer, model);
+        let tokens = tokenizer.count(&prompt);
+        tracing::debug!("{} prompt is {} tokens (limit {})", model, tokens, max);
+        if tokens <= max {
+            return Ok(prompt);
+        }
+        match self.overflow {
+            Overflow::Refuse => Err(ProviderError::PromptTooLong { tokens, max }),
+            Overflow::Trim => {
+                let overhead = tokenizer.count(&self.prompt("", debate));
+                // Not even an empty statement fits: nothing to trim down to.
+                let room = max
+                    .checked_sub(overhead)
+                    .filter(|&n| n > 0)
+                    .ok_or(ProviderError::PromptTooLong { tokens, max })?;
+                let trimmed = tokenizer.truncate(text, room);
+                tracing::debug!("{} statement trimmed to {} tokens", model, room);
+                Ok(self.prompt(&trimmed, debate))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiktoken_counts_and_truncates() {
+        let tokenizer = tokenizer_for(TokenizerKind::Tiktoken, "openai/gpt-3.5-turbo");
+        assert_eq!(tokenizer.count("hello world"), 2);
+        assert_eq!(tokenizer.truncate("hello world", 1), "hello");
+    }
+
+    #[test]
+    fn model_limits_override_the_default() {
+        let budget = PromptBudget {
+            tokenizer: TokenizerKind::Chars,
+            limits: vec![(None, 1000), (Some("x/grok".to_string()), 10)],
+            overflow: Overflow::Refuse,
+            guard: false,
+            lang: Lang::En,
+            context: None,
+        };
+        assert!(budget.fit("openai/gpt-4o", "short", None).is_ok());
+        assert!(matches!(
+            budget.fit("x/grok", "short", None),
+            Err(ProviderError::PromptTooLong { max: 10, .. })
+        ));
+    }
+
+    #[test]
+    fn trim_keeps_the_prompt_within_the_limit() {
+        let overhead = CharEstimate.count(&validation_prompt(Lang::En, "", None));
+        let budget = PromptBudget {
+            tokenizer: TokenizerKind::Chars,
+            limits: vec![(None, overhead + 2)],
+            overflow: Overflow::Trim,
+            guard: false,
+            lang: Lang::En,
+            context: None,
+        };
+        let prompt = budget.fit("any", "a very long statement", None).unwrap();
+        assert!(prompt.contains("'a very l'"));
+    }
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+use crate::claim::Claim;
+use crate::input::sanitize_statement;
+use crate::Record;
+
+/// `--resume`: how far a batch got, written after every question.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Hash of the questions, so a checkpoint is never applied to another file.
+    pub questions_sha256: String,
+    /// Questions finished, whether or not they recorded anything.
+    pub next: usize,
+    /// The ledger's last record hash when the checkpoint was written.
+    pub head: Option<String>,
+}
+
+impl Checkpoint {
+    pub fn new(questions: &[String], next: usize, records: &[Record]) -> Self {
+        Self {
+            questions_sha256: digest(questions),
+            next,
+            head: records.last().map(|r| r.hash.clone()),
+        }
+    }
+}
+
+/// The checkpoint file for an `--input` file: `questions.txt.progress`.
+pub fn path_for(input: &Path) -> PathBuf {
+    let mut path = input.as_os_str().to_owned();
+    path.push(".progress");
+    PathBuf::from(path)
+}
+
+/// The saved checkpoint; none if the batch hasn't been started with `--resume`.
+pub fn load(path: &Path) -> std::io::Result<Option<Checkpoint>> {
+    match std::fs::read(path) {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Replaces the checkpoint through a temporary file, so a crash mid-write leaves
+/// the previous one intact.
+pub fn save(path: &Path, checkpoint: &Checkpoint) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, serde_json::to_vec(checkpoint).expect("checkpoints serialize"))?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Where a resumed batch picks up. The checkpoint is written after each question's
+/// records are committed, so a crash in between leaves records the checkpoint
+/// doesn't know of: those past its `head` must be the next questions' (or repeats of
+/// the last one's), and the position moves past them instead of asking again.
+pub fn resume_position(questions: &[String], checkpoint: &Checkpoint, records: &[Record]) -> Result<usize, String> {
+    if checkpoint.questions_sha256 != digest(questions) {
+        return Err("the questions file changed since the checkpoint was written".to_string());
+    }
+    let after = match &checkpoint.head {
+        None => 0,
+        Some(head) => match records.iter().rposition(|r| &r.hash == head) {
+            Some(i) => i + 1,
+            None => return Err(format!("the ledger no longer holds the checkpoint's head record {}", head)),
+        },
+    };
+    let mut next = checkpoint.next.min(questions.len());
+    for record in &records[after..] {
+        if next < questions.len() && content(&questions[next]) == record.transaction.content {
+            next += 1;
+        } else if next == 0 || content(&questions[next - 1]) != record.transaction.content {
+            return Err(format!("record {} was committed after the checkpoint but isn't from this batch", record.id));
+        }
+    }
+    Ok(next)
+}
+
+/// The transaction content a question line is recorded under.
+fn content(question: &str) -> String {
+    match Claim::from_line(question).and_then(|c| c.sanitized()) {
+        Some(claim) => claim.statement(),
+        None => sanitize_statement(question),
+    }
+}
+
+fn digest(questions: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    for question in questions {
+        hasher.update(question.as_bytes());
+        hasher.update(b"\n");
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::tests::record;
+
+    #[test]
+    fn resume_skips_questions_committed_after_the_checkpoint() {
+        let questions: Vec<String> = ["Water is wet", "Fire is cold", "Ice is hot"].map(String::from).to_vec();
+        let mut records = vec![record("Water is wet")];
+        records[0].hash = "h1".to_string();
+        let checkpoint = Checkpoint::new(&questions, 1, &records);
+        assert_eq!(resume_position(&questions, &checkpoint, &records), Ok(1));
+
+        // Crashed after "Fire is cold" committed (twice, under --repeat) but before
+        // the checkpoint caught up.
+        records.extend([record("Fire is cold"), record("Fire is cold")]);
+        assert_eq!(resume_position(&questions, &checkpoint, &records), Ok(2));
+
+        records.push(record("Unrelated"));
+        assert!(resume_position(&questions, &checkpoint, &records).unwrap_err().contains("isn't from this batch"));
+        let other: Vec<String> = vec!["Water is wet".to_string()];
+        assert!(resume_position(&other, &checkpoint, &records).is_err());
+        assert_eq!(resume_position(&questions, &Checkpoint::new(&questions, 0, &[]), &records[..2]), Ok(2));
+        assert_eq!(path_for(Path::new("q.txt")), Path::new("q.txt.progress"));
+    }
+}
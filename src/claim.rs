@@ -0,0 +1,68 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::input::sanitize_statement;
+
+/// A structured fact, e.g. `{"subject":"Paris","predicate":"capital_of","object":"France"}`.
+/// Agents are asked about its rendered statement; the claim itself is kept on the
+/// transaction so verified records can be read back as knowledge-graph edges.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Claim {
+    pub subject: String,
+    pub predicate: String,
+    pub object: String,
+}
+
+impl Claim {
+    /// Parses a console line written as a claim's JSON object; other lines are statements.
+    pub fn from_line(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if !line.starts_with('{') {
+            return None;
+        }
+        serde_json::from_str(line).ok()
+    }
+
+    /// The claim with each part sanitized like a statement, or `None` if a part ends
+    /// up empty.
+    pub fn sanitized(&self) -> Option<Self> {
+        let claim = Self {
+            subject: sanitize_statement(&self.subject),
+            predicate: sanitize_statement(&self.predicate),
+            object: sanitize_statement(&self.object),
+        };
+        let empty = claim.subject.is_empty() || claim.predicate.is_empty() || claim.object.is_empty();
+        (!empty).then_some(claim)
+    }
+
+    /// The natural-language statement agents are asked about: "Paris capital of France".
+    pub fn statement(&self) -> String {
+        let predicate = self.predicate.replace(['_', '-'], " ");
+        format!("{} {} {}", self.subject, predicate.trim(), self.object)
+    }
+
+    /// `subject · predicate · object`, for display.
+    pub fn triple(&self) -> String {
+        format!("{} · {} · {}", self.subject, self.predicate, self.object)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claims_render_into_statements() {
+        let claim = Claim::from_line(r#" {"subject":"Paris","predicate":"capital_of","object":"France"}"#).unwrap();
+        assert_eq!(claim.statement(), "Paris capital of France");
+        assert_eq!(claim.triple(), "Paris · capital_of · France");
+        assert_eq!(Claim::from_line("Paris is the capital of France"), None);
+        assert_eq!(Claim::from_line(r#"{"subject":"Paris"}"#), None);
+
+        let blank = Claim {
+            object: "\x1B[2J ".to_string(),
+            ..claim
+        };
+        assert_eq!(blank.sanitized(), None);
+    }
+}
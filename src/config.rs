@@ -0,0 +1,785 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use crate::consensus::TrustWeights;
+use crate::solana::Commitment;
+use crate::context::Context;
+use crate::refusal::DEFAULT_PATTERNS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StoreKind {
+    /// Keep the ledger in memory only; it is lost on exit.
+    Memory,
+    /// JSON Lines on disk, one record appended per commit.
+    Json,
+    /// An SQLite database with indexed queries.
+    Sqlite,
+}
+
+/// Which tokenizer measures prompts against `--max-prompt-tokens`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TokenizerKind {
+    /// OpenAI's BPE encodings (o200k for gpt-4o, cl100k otherwise).
+    #[default]
+    Tiktoken,
+    /// About four characters per token.
+    Chars,
+}
+
+/// What to do with a prompt over its model's token limit.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Overflow {
+    /// Don't send it; the agent abstains.
+    #[default]
+    Refuse,
+    /// Cut the statement down until the prompt fits.
+    Trim,
+}
+
+/// Language of the prompt wording around a statement; statements are never translated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Lang {
+    #[default]
+    En,
+    Es,
+    Ja,
+    Fr,
+    De,
+}
+
+impl Lang {
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::Es => "es",
+            Lang::Ja => "ja",
+            Lang::Fr => "fr",
+            Lang::De => "de",
+        }
+    }
+}
+
+/// How votes are turned into a verdict.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ConsensusStrategy {
+    /// More than half of the agents vote "yes"; a tie fails.
+    #[default]
+    Majority,
+    /// Enough distinct providers (the part of the model id before `/`) agree "yes".
+    ProviderQuorum,
+    /// More than half of the vote weight says "yes", each model weighing its
+    /// reputation (see `/api/reputation`).
+    WeightedMajority,
+    /// The confidence- and reputation-weighted truth score reaches
+    /// `--min-truth-score`.
+    TruthScore,
+    /// Majority, unless a `--veto-model` votes "no" or a `--require-yes-model`
+    /// doesn't vote "yes".
+    Veto,
+}
+
+/// What an agent whose call failed (timeout, HTTP or parse error, open breaker)
+/// means for the verdict. Refusals under `--detect-refusals` always abstain.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ErrorPolicy {
+    /// Record it as an abstention that doesn't count either way.
+    #[default]
+    Abstain,
+    /// Still record it as an abstention, but count it as a certain "no" in the tally.
+    No,
+    /// Abort the round; nothing is recorded.
+    Fail,
+}
+
+/// How the console prints agent progress and results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Colored, indented lines for people.
+    Pretty,
+    /// The same lines without color or symbols.
+    Plain,
+    /// One JSON event object per line.
+    Json,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Validate one statement, print the verdict, record it and exit.
+    Ask {
+        statement: String,
+    },
+    /// Print one stored record, by 1-based index or by record or transaction id.
+    Show {
+        record: String,
+    },
+    /// Print one-line summaries of the most recent stored records.
+    List {
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Print summaries of the stored records whose statement contains TEXT, ignoring case.
+    Search {
+        text: String,
+    },
+}
+
+impl Command {
+    /// `show`, `list` and `search` only read the stored ledger.
+    pub fn is_query(&self) -> bool {
+        !matches!(self, Command::Ask { .. })
+    }
+}
+
+/// Command-line options for the Truth Terminal server.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Config {
+    /// Run a one-off command instead of serving the HTTP API.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Refuse to record a verdict when fewer than this many agents return a vote.
+    #[arg(
+        long,
+        env = "TRUTH_MIN_RESPONSES",
+        default_value_t = 1,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub min_responses: usize,
+
+    /// Agents per round, sampled from the model pool. Defaults to every model.
+    #[arg(long, env = "TRUTH_AGENTS")]
+    pub agents: Option<usize>,
+
+    /// Relative sampling weight for a model, as MODEL=WEIGHT. Unlisted models weigh 1.0.
+    #[arg(long = "sample-weight", value_name = "MODEL=WEIGHT", value_parser = parse_model_weight)]
+    pub sample_weights: Vec<(String, f64)>,
+
+    /// Merge records from another ledger file (array or export snapshot) at startup,
+    /// skipping ones already present.
+    #[arg(long, value_name = "LEDGER")]
+    pub import: Option<PathBuf>,
+
+    /// Send every agent's request to this model (e.g. openai/gpt-4-turbo), keeping the
+    /// agents distinct, to measure intra-model variance.
+    #[arg(long, env = "TRUTH_MODEL_OVERRIDE", value_parser = crate::models::parse_model)]
+    pub model_override: Option<String>,
+
+    /// Choose this session's models, and for openai/* ones their backend, from a
+    /// menu at startup.
+    #[arg(long, conflicts_with = "model_override")]
+    pub pick_models: bool,
+
+    /// Send openai/* models straight to the OpenAI API (keys from OPENAI_API_KEYS or
+    /// OPENAI_API_KEY) instead of through OpenRouter.
+    #[arg(long, env = "TRUTH_OPENAI_DIRECT")]
+    pub openai_direct: bool,
+
+    /// Send anthropic/* models straight to Anthropic's Messages API (key from
+    /// ANTHROPIC_API_KEY) instead of through OpenRouter.
+    #[arg(long, env = "TRUTH_ANTHROPIC_DIRECT")]
+    pub anthropic_direct: bool,
+
+    /// Base URL of the OpenAI-compatible API that `--openai-direct` calls, such as
+    /// `http://localhost:11434/v1` for Ollama. A server other than OpenAI's takes
+    /// every agent's model, not only openai/* ones, and needs no API key.
+    #[arg(long, value_name = "URL", env = "OPENAI_BASE_URL")]
+    pub openai_base_url: Option<reqwest::Url>,
+
+    /// Request streamed (SSE) replies and log each agent's output as it arrives
+    /// (visible with RUST_LOG=debug); the verdict is parsed once the stream ends.
+    #[arg(long, env = "TRUTH_STREAM")]
+    pub stream: bool,
+
+    /// Fail an agent's call, naming the JSON path, when its reply departs in any way
+    /// from the chat-completions schema, instead of reading whatever is usable.
+    #[arg(long, env = "TRUTH_STRICT_JSON", conflicts_with = "stream")]
+    pub strict_json: bool,
+
+    /// Requests per agent call, the first included, when it hits a rate limit (429),
+    /// a server error (5xx), a network error or a cut-off body. Other failures, such
+    /// as a rejected key, are never retried.
+    #[arg(long, value_name = "N", env = "TRUTH_MAX_ATTEMPTS", default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..=10))]
+    pub max_attempts: u32,
+
+    /// Milliseconds to wait before the first retry, doubling with each one after
+    /// (with jitter, at most 30 s). A `Retry-After` header overrides it.
+    #[arg(long, value_name = "MS", env = "TRUTH_BACKOFF_MS", default_value_t = 500)]
+    pub backoff_ms: u64,
+
+    /// Send every outbound request (providers, Solana, alerts) through this proxy,
+    /// e.g. http://proxy.corp:3128. Without it HTTPS_PROXY/HTTP_PROXY are honored.
+    #[arg(long, env = "TRUTH_PROXY")]
+    pub proxy: Option<String>,
+
+    /// Sampling temperature for every model without its own `--model-temperature`.
+    /// Unset, each provider's default applies (0.1 for Grok).
+    #[arg(long, env = "TRUTH_TEMPERATURE", value_parser = parse_temperature)]
+    pub temperature: Option<f32>,
+
+    /// Sampling temperature for one model, as MODEL=T. Repeatable.
+    #[arg(long = "model-temperature", value_name = "MODEL=T", value_parser = parse_model_temperature)]
+    pub model_temperatures: Vec<(String, f32)>,
+
+    /// Response-time SLA for one model, as MODEL=MS. A slot whose model hasn't
+    /// answered within it asks its `--fallback-model` instead. Repeatable.
+    #[arg(long = "model-sla", value_name = "MODEL=MS", value_parser = parse_model_sla)]
+    pub model_slas: Vec<(String, u64)>,
+
+    /// Backup for a model with a `--model-sla`, as MODEL=BACKUP. Repeatable.
+    #[arg(long = "fallback-model", value_name = "MODEL=BACKUP", value_parser = parse_fallback_model)]
+    pub fallback_models: Vec<(String, String)>,
+
+    /// Ask each agent's model K times and vote the majority of its own answers,
+    /// recording how many agreed. Needs a nonzero temperature, and multiplies the
+    /// paid calls by K.
+    #[arg(
+        long,
+        env = "TRUTH_SELF_CONSISTENCY",
+        value_name = "K",
+        value_parser = clap::value_parser!(u32).range(2..=25),
+        conflicts_with = "openai_seed"
+    )]
+    pub self_consistency: Option<u32>,
+
+    /// Send this `seed` with every chat-completions request so OpenAI-compatible
+    /// backends sample reproducibly; each vote records the `system_fingerprint`
+    /// returned alongside.
+    #[arg(long, env = "TRUTH_OPENAI_SEED", value_name = "N")]
+    pub openai_seed: Option<u64>,
+
+    /// Extra HTTP header for one backend's requests, as BACKEND:NAME=VALUE where
+    /// BACKEND is openai, openrouter, grok or anthropic (e.g. openrouter:X-Title=truth).
+    /// Repeatable.
+    #[arg(long = "extra-header", value_name = "BACKEND:NAME=VALUE", value_parser = parse_extra_header)]
+    pub extra_headers: Vec<(String, String, String)>,
+
+    /// Prompt token limit, as N for every model or MODEL=N for one. Repeatable.
+    #[arg(long = "max-prompt-tokens", value_name = "[MODEL=]N", value_parser = parse_model_limit)]
+    pub max_prompt_tokens: Vec<(Option<String>, usize)>,
+
+    /// What to do with prompts over their limit.
+    #[arg(long, value_enum, env = "TRUTH_PROMPT_OVERFLOW", default_value_t = Overflow::Refuse)]
+    pub prompt_overflow: Overflow,
+
+    /// Language for the prompt's framing (the question and answer format); the
+    /// statement itself is sent as written. Votes record the language used.
+    #[arg(long, value_enum, env = "TRUTH_LANG")]
+    pub lang: Option<Lang>,
+
+    /// Mask matches of this regex as [redacted] wherever statements are displayed
+    /// (console, rendered ledger, alerts, logs); stored records keep the full text.
+    /// Repeatable.
+    #[arg(long = "redact", value_name = "REGEX", value_parser = parse_regex)]
+    pub redact: Vec<regex::Regex>,
+
+    /// Count replies that decline to answer ("I can't help with that") as
+    /// abstentions tagged as refusals, instead of as "no" votes.
+    #[arg(long, env = "TRUTH_DETECT_REFUSALS")]
+    pub detect_refusals: bool,
+
+    /// A reply phrase (case-insensitive) that marks a refusal. Repeatable; it works
+    /// on its own, and `--detect-refusals` adds the built-in phrases.
+    #[arg(long = "refusal-pattern", value_name = "TEXT")]
+    pub extra_refusal_patterns: Vec<String>,
+
+    /// Reference document agents must judge every statement against, instead of
+    /// their own knowledge. Its SHA-256 is recorded in each record's provenance.
+    #[arg(long, env = "TRUTH_CONTEXT", value_name = "FILE", value_parser = Context::parse)]
+    pub context: Option<Context>,
+
+    /// Fence each statement off in delimiters and tell models to treat it strictly
+    /// as data, so text like "ignore previous instructions" cannot steer the vote.
+    #[arg(long, env = "TRUTH_PROMPT_GUARD")]
+    pub prompt_guard: bool,
+
+    /// Log a warning and note it in the record's details when a statement matches a
+    /// known prompt-injection pattern.
+    #[arg(long, env = "TRUTH_FLAG_INJECTION")]
+    pub flag_injection: bool,
+
+    /// How prompt tokens are counted.
+    #[arg(long, value_enum, env = "TRUTH_TOKENIZER", default_value_t = TokenizerKind::Tiktoken)]
+    pub tokenizer: TokenizerKind,
+
+    /// Debate rounds per statement: after the first independent vote, agents see the
+    /// previous round's anonymized votes and vote again. The last round decides.
+    #[arg(long, env = "TRUTH_ROUNDS", default_value_t = 1, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub rounds: usize,
+
+    /// How votes are turned into a verdict.
+    #[arg(long, value_enum, env = "TRUTH_CONSENSUS", default_value_t = ConsensusStrategy::Majority)]
+    pub consensus: ConsensusStrategy,
+
+    /// Decay old rounds in `/api/reputation` scores and `/api/trend`: a round counts
+    /// half as much for every this many days it is older than the latest one.
+    #[arg(long, value_name = "DAYS", env = "TRUTH_HALF_LIFE_DAYS", value_parser = parse_half_life)]
+    pub half_life_days: Option<f64>,
+
+    /// What a failed agent call means for the verdict.
+    #[arg(long, value_enum, env = "TRUTH_ERROR_POLICY", default_value_t = ErrorPolicy::Abstain)]
+    pub error_policy: ErrorPolicy,
+
+    /// Under provider-quorum, how many distinct providers must agree "yes".
+    #[arg(long, env = "TRUTH_QUORUM_PROVIDERS", default_value_t = 2, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    pub quorum_providers: usize,
+
+    /// Under provider-quorum, a provider (e.g. openai) that must be among those
+    /// agreeing. Repeatable.
+    #[arg(long = "require-provider", value_name = "PROVIDER")]
+    pub required_providers: Vec<String>,
+
+    /// Under veto, a trusted model (e.g. a fact-checker) whose "no" fails consensus
+    /// whatever the majority says. Repeatable.
+    #[arg(long = "veto-model", value_name = "MODEL", value_parser = crate::models::parse_model)]
+    pub veto_models: Vec<String>,
+
+    /// Under veto, a model that must vote "yes" for consensus; its "no" or its
+    /// abstention fails it. Repeatable.
+    #[arg(long = "require-yes-model", value_name = "MODEL", value_parser = crate::models::parse_model)]
+    pub required_yes_models: Vec<String>,
+
+    /// Under majority and veto, the share of "yes" votes consensus needs, in
+    /// (0, 1], e.g. 0.66. Unset, more than half must say yes.
+    #[arg(long, value_name = "RATIO", env = "TRUTH_QUORUM", value_parser = parse_quorum)]
+    pub quorum: Option<f64>,
+
+    /// Under majority and veto, count each vote as its model's weight from this JSON
+    /// file of model names to weights (e.g. `{"gpt-4o": 2, "mistral": 0.5}`), so the
+    /// verdict follows the weighted share of "yes". Unlisted models weigh 1.0.
+    #[arg(long, value_name = "FILE", env = "TRUTH_WEIGHTS", value_parser = TrustWeights::parse)]
+    pub weights: Option<TrustWeights>,
+
+    /// Under truth-score, the weighted truth score (0–100) consensus needs.
+    #[arg(long, env = "TRUTH_MIN_TRUTH_SCORE", default_value_t = 70.0, value_parser = parse_truth_score)]
+    pub min_truth_score: f32,
+
+    /// After voting, ask a summarizer model (through OpenRouter) for a one-paragraph
+    /// rationale of the majority verdict and store it on the record.
+    #[arg(long, env = "TRUTH_EXPLAIN_CONSENSUS")]
+    pub explain_consensus: bool,
+
+    /// OpenRouter model that writes `--explain-consensus` rationales.
+    #[arg(long, env = "TRUTH_EXPLAIN_MODEL", default_value = "openai/gpt-4o-mini", value_parser = crate::models::parse_model)]
+    pub explain_model: String,
+
+    /// After voting, ask a model (through OpenRouter) to argue against the verdict and
+    /// store its case on the record, apart from the votes.
+    #[arg(long, env = "TRUTH_DEVILS_ADVOCATE")]
+    pub devils_advocate: bool,
+
+    /// OpenRouter model that plays `--devils-advocate`.
+    #[arg(long, env = "TRUTH_DEVILS_ADVOCATE_MODEL", default_value = "openai/gpt-4o-mini", value_parser = crate::models::parse_model)]
+    pub devils_advocate_model: String,
+
+    /// Consecutive failures after which a provider's circuit breaker opens.
+    #[arg(long, env = "TRUTH_BREAKER_THRESHOLD", default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    pub breaker_threshold: u32,
+
+    /// Seconds an open circuit breaker skips its provider before trying it again.
+    #[arg(long, env = "TRUTH_BREAKER_COOLDOWN_SECS", default_value_t = 60)]
+    pub breaker_cooldown_secs: u64,
+
+    /// Append a timestamped, human-readable line to this file for every significant
+    /// event: start, submissions, agent results, verdicts, anchors, annotations,
+    /// purges and exit. Flushed per event.
+    #[arg(long, value_name = "PATH", env = "TRUTH_AUDIT_LOG")]
+    pub audit_log: Option<PathBuf>,
+
+    /// On exit, write a summary of the run (transactions, verdicts, per-model
+    /// agreement and accuracy, projected cost, time, chain head) here: JSON for a
+    /// `.json` path, Markdown otherwise.
+    #[arg(long, value_name = "PATH", env = "TRUTH_REPORT")]
+    pub report: Option<PathBuf>,
+
+    /// Where committed records are persisted.
+    #[arg(long, value_enum, env = "TRUTH_STORE", default_value_t = StoreKind::Json)]
+    pub store: StoreKind,
+
+    /// Path for the json/sqlite store. Defaults to ledger.jsonl or ledger.db under
+    /// `--data-dir`; `TRUTH_STORE_PATH` is still read when this is unset.
+    #[arg(long, env = "TRUTH_LEDGER_PATH")]
+    pub store_path: Option<PathBuf>,
+
+    /// Directory holding the default store file, created on first use.
+    #[arg(long, value_name = "DIR", env = "TRUTH_DATA_DIR", default_value = "data")]
+    pub data_dir: PathBuf,
+
+    /// Keep the ledger in this named workspace under `--workspaces-dir`: an isolated
+    /// chain with its own store file. Restart with another name to switch.
+    #[arg(long, value_name = "NAME", env = "TRUTH_WORKSPACE", value_parser = crate::workspace::parse_name, conflicts_with = "store_path")]
+    pub workspace: Option<String>,
+
+    /// Directory holding one subdirectory per workspace.
+    #[arg(long, value_name = "DIR", env = "TRUTH_WORKSPACES_DIR", default_value = "workspaces")]
+    pub workspaces_dir: PathBuf,
+
+    /// Serve `POST /rpc`, a JSON-RPC 2.0 interface to the REST API's submit, ledger,
+    /// block, verify and status operations.
+    #[arg(long, env = "TRUTH_JSONRPC")]
+    pub jsonrpc: bool,
+
+    /// Gzip the json store's ledger file, adding `.gz` to its path if missing. Any
+    /// `.gz` ledger path (including `--import`) is read compressed regardless.
+    #[arg(long, env = "TRUTH_COMPRESS")]
+    pub compress: bool,
+
+    /// Guarantee the ledger only grows: purging (`/api/purge`, `--retention-days`) and
+    /// `--rehash` are refused. Records are fsynced to the store as they commit.
+    #[arg(long, env = "TRUTH_APPEND_ONLY")]
+    pub append_only: bool,
+
+    /// Drop unpinned records older than this many days, at startup and hourly,
+    /// re-linking the remaining chain.
+    #[arg(long, env = "TRUTH_RETENTION_DAYS")]
+    pub retention_days: Option<u64>,
+
+    /// POST a JSON alert to this URL whenever a statement fails to reach consensus.
+    #[arg(long, env = "TRUTH_ALERT_WEBHOOK")]
+    pub alert_webhook: Option<String>,
+
+    /// Re-verify the whole hash chain every this many minutes while running; a
+    /// failure is logged as an error and counted in `/api/status`.
+    #[arg(long, env = "TRUTH_VERIFY_INTERVAL_MINS", value_name = "MINUTES", value_parser = clap::value_parser!(u64).range(1..))]
+    pub verify_interval_mins: Option<u64>,
+
+    /// Also POST to `--alert-webhook` when a periodic chain check first fails.
+    #[arg(long, env = "TRUTH_ALERT_ON_CORRUPTION", requires_all = ["alert_webhook", "verify_interval_mins"])]
+    pub alert_on_corruption: bool,
+
+    /// Shell command run after each record is committed, with the record's JSON on
+    /// stdin. It runs in the background; a non-zero exit is logged.
+    #[arg(long, env = "TRUTH_ON_COMMIT", value_name = "COMMAND")]
+    pub on_commit: Option<String>,
+
+    /// Seconds an `--on-commit` command may run before it is killed.
+    #[arg(long, env = "TRUTH_ON_COMMIT_TIMEOUT_SECS", default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+    pub on_commit_timeout_secs: u64,
+
+    /// Derive record and transaction ids, and `--agents` sampling, from this seed so a
+    /// run can be reproduced exactly. Timestamps and agent replies still vary.
+    #[arg(long, env = "TRUTH_SEED")]
+    pub seed: Option<u64>,
+
+    /// Time each pipeline stage (agent HTTP, JSON parsing, consensus, Solana RPC,
+    /// persistence) and print a per-stage summary on exit.
+    #[arg(long, env = "TRUTH_PROFILE")]
+    pub profile: bool,
+
+    /// Validate at most N statements' agent rounds at once, queueing the rest, and
+    /// commit records in the order their rounds started.
+    #[arg(
+        long,
+        env = "TRUTH_WORKER_THREADS",
+        value_name = "N",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub worker_threads: Option<usize>,
+
+    /// Validate statements typed at a console prompt instead of serving the HTTP API.
+    #[arg(long)]
+    pub interactive: bool,
+
+    /// Validate every question in this file (one per line, `#` comments skipped)
+    /// in order, then exit.
+    #[arg(long, value_name = "QUESTIONS", conflicts_with = "interactive")]
+    pub input: Option<PathBuf>,
+
+    /// Like `--input`, with the questions file downloaded from this URL first.
+    #[arg(long, value_name = "URL", env = "TRUTH_INPUT_URL", conflicts_with_all = ["interactive", "input"])]
+    pub input_url: Option<reqwest::Url>,
+
+    /// Checkpoint an `--input` batch to `QUESTIONS.progress` after every question and,
+    /// when that file exists, pick up where it left off.
+    #[arg(long, requires = "input")]
+    pub resume: bool,
+
+    /// Read `{"content": "..."}` (or `{"claim": {...}}`) objects from stdin, one per
+    /// line, and write each committed record as a JSON line to stdout, with no prompts
+    /// or banner; logs go to stderr.
+    #[arg(long, conflicts_with_all = ["interactive", "input", "input_url", "live_banner"])]
+    pub stdin_jsonl: bool,
+
+    /// Exit nonzero when any question of an `--input` batch recorded a round that
+    /// did not reach consensus, not only when nothing was recorded; for CI.
+    #[arg(long, env = "TRUTH_FAIL_ON_NO_CONSENSUS")]
+    pub fail_on_no_consensus: bool,
+
+    /// Milliseconds to wait between questions of an `--input` batch, to stay under
+    /// per-minute rate limits. Agents within a round are still queried in parallel.
+    #[arg(long, default_value_t = 0)]
+    pub delay_ms: u64,
+
+    /// Validate each submitted statement this many times, recording every round and
+    /// printing how stable the verdict was.
+    #[arg(long, env = "TRUTH_REPEAT", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub repeat: u32,
+
+    /// Before each console submission, show the projected cost and ask to proceed.
+    #[arg(long, env = "TRUTH_CONFIRM_COST")]
+    pub confirm_cost: bool,
+
+    /// Stop accepting statements once the session's projected spend would pass this
+    /// many USD (projected from token counts, as in `--estimate`).
+    #[arg(long, env = "TRUTH_MAX_COST_USD", value_parser = parse_cost_cap)]
+    pub max_cost_usd: Option<f64>,
+
+    /// End an interactive session after it has committed this many blocks, guarding
+    /// against piped input or a script that never sends exit.
+    #[arg(long, value_name = "N", env = "TRUTH_MAX_SESSION_BLOCKS", value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_session_blocks: Option<u32>,
+
+    /// Write the console's rendered records and summaries to this file, keeping only
+    /// the prompt and live progress on the terminal. A `.jsonl` path gets each
+    /// committed record as one bare JSON line instead, whatever `--format`.
+    #[arg(long, value_name = "PATH", env = "TRUTH_OUTPUT")]
+    pub output: Option<PathBuf>,
+
+    /// How the console prints agent progress and results.
+    #[arg(long, value_enum, env = "TRUTH_FORMAT", default_value_t = OutputFormat::Pretty)]
+    pub format: OutputFormat,
+
+    /// Print verdicts without ANSI colors, as `NO_COLOR` does. Colors are also left
+    /// out whenever stdout isn't a terminal.
+    #[arg(long, env = "TRUTH_NO_COLOR")]
+    pub no_color: bool,
+
+    /// At startup, ask every provider a probe statement at once and print the model
+    /// list marked with which ones answered. Each probe is a paid call.
+    #[arg(long, env = "TRUTH_LIVE_BANNER")]
+    pub live_banner: bool,
+
+    /// Check that every provider answers, the Solana RPC (if set) is reachable and the
+    /// store is writable, print a report, then exit without recording anything.
+    #[arg(long)]
+    pub validate_only: bool,
+
+    /// Send the probe statement to every provider, check each reply against the
+    /// chat-completions schema as `--strict-json` does, report any mismatch with the
+    /// raw body, then exit without recording anything. Each probe is a paid call.
+    #[arg(long, conflicts_with_all = ["stream", "validate_only"])]
+    pub check_providers: bool,
+
+    /// Recompute prev_hash/hash for every stored record in order (migrating a ledger
+    /// written before hashing), verify the chain, write it back, then exit.
+    #[arg(long)]
+    pub rehash: bool,
+
+    /// Open this ledger file in a read-only viewer (/show, /search, /stats, /verify)
+    /// instead of serving; nothing is queried or written.
+    #[arg(long, value_name = "LEDGER")]
+    pub readonly: Option<PathBuf>,
+
+    /// Save every provider response into this directory, keyed by model and
+    /// statement, for later `--replay`.
+    #[arg(long = "record", value_name = "DIR", conflicts_with = "replay_dir")]
+    pub record_dir: Option<PathBuf>,
+
+    /// Answer from responses saved by `--record` instead of the network; a statement
+    /// without a saved response is an abstention.
+    #[arg(long = "replay", value_name = "DIR")]
+    pub replay_dir: Option<PathBuf>,
+
+    /// Print projected token usage and cost for the questions in this file, then exit.
+    #[arg(long, value_name = "QUESTIONS")]
+    pub estimate: Option<PathBuf>,
+
+    /// Make no Solana calls at all, ignoring any configured RPC URL or keypair;
+    /// records are left unanchored.
+    #[arg(long, env = "TRUTH_NO_SOLANA")]
+    pub no_solana: bool,
+
+    /// Solana JSON-RPC endpoint used to observe the current slot (e.g.
+    /// https://api.devnet.solana.com); records only note the slot unless --anchor
+    /// is set. Records are left unanchored when unset.
+    #[arg(long, env = "SOLANA_RPC_URL")]
+    pub solana_url: Option<String>,
+
+    /// Solana CLI keypair file that pays for and signs anchor transactions.
+    /// `SOLANA_KEYPAIR` is still read when this is unset.
+    #[arg(long, env = "SOLANA_KEYPAIR_PATH")]
+    pub solana_keypair: Option<PathBuf>,
+
+    /// Every this many seconds, anchor the Merkle root of all not-yet-anchored
+    /// records in a single Solana memo transaction.
+    #[arg(long, env = "TRUTH_ANCHOR_INTERVAL_SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub anchor_interval_secs: Option<u64>,
+
+    /// Write each committed record's hash to Solana in its own memo transaction and
+    /// store the signature on the record. Needs --solana-url and --solana-keypair, and
+    /// pays a fee per record.
+    #[arg(long, env = "TRUTH_ANCHOR")]
+    pub anchor: bool,
+
+    /// Commitment level for Solana reads and for the blockhash memos are built on.
+    #[arg(long, value_enum, env = "TRUTH_COMMITMENT", default_value_t = Commitment::Finalized)]
+    pub commitment: Commitment,
+
+    /// On shutdown, wait up to this many seconds for submitted anchors to finalize.
+    #[arg(long, value_name = "SECS", env = "TRUTH_WAIT_CONFIRMATIONS")]
+    pub wait_confirmations: Option<u64>,
+}
+
+/// OpenAI's own API, the default `--openai-base-url`.
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+impl Config {
+    pub fn sample_weight(&self, model: &str) -> f64 {
+        self.sample_weights
+            .iter()
+            .find(|(m, _)| m == model)
+            .map_or(1.0, |(_, w)| *w)
+    }
+
+    /// The chat-completions endpoint of a `--openai-base-url` server other than
+    /// OpenAI's. It is only called under `--openai-direct`; main rejects it otherwise.
+    pub fn openai_server(&self) -> Option<String> {
+        let base = self.openai_base_url.as_ref()?.as_str().trim_end_matches('/');
+        (base != OPENAI_BASE_URL).then(|| format!("{}/chat/completions", base))
+    }
+
+    /// Calls each agent makes per vote: `--self-consistency`, or one.
+    pub fn samples(&self) -> u64 {
+        self.self_consistency.map_or(1, u64::from)
+    }
+
+    /// `--model-sla` and `--fallback-model` for `model`, when it has both.
+    pub fn sla(&self, model: &str) -> Option<(std::time::Duration, &str)> {
+        let (_, ms) = self.model_slas.iter().find(|(m, _)| m == model)?;
+        let (_, backup) = self.fallback_models.iter().find(|(m, _)| m == model)?;
+        Some((std::time::Duration::from_millis(*ms), backup))
+    }
+
+    /// `--store-path` (or the older `TRUTH_STORE_PATH`), the selected workspace's
+    /// store file, or the store file under `--data-dir`.
+    pub fn ledger_path(&self) -> PathBuf {
+        let legacy = std::env::var_os("TRUTH_STORE_PATH").map(PathBuf::from);
+        let workspace = self.workspace.as_deref();
+        let in_workspace = workspace.map(|name| crate::workspace::store_path(&self.workspaces_dir, name, self.store));
+        let in_data_dir = || self.data_dir.join(crate::workspace::store_file(self.store));
+        self.store_path.clone().or(legacy).or(in_workspace).unwrap_or_else(in_data_dir)
+    }
+
+    /// `--solana-keypair`, or the older `SOLANA_KEYPAIR`.
+    pub fn keypair_path(&self) -> Option<PathBuf> {
+        let legacy = std::env::var_os("SOLANA_KEYPAIR").map(PathBuf::from);
+        self.solana_keypair.clone().or(legacy)
+    }
+
+    /// Every phrase that marks a reply as a refusal; empty when detection is off.
+    pub fn refusal_patterns(&self) -> Vec<String> {
+        let builtin = DEFAULT_PATTERNS.iter().filter(|_| self.detect_refusals).map(|p| p.to_string());
+        builtin.chain(self.extra_refusal_patterns.iter().cloned()).collect()
+    }
+}
+
+fn parse_model_weight(s: &str) -> Result<(String, f64), String> {
+    let (model, weight) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected MODEL=WEIGHT, got '{}'", s))?;
+    let weight: f64 = weight
+        .parse()
+        .map_err(|_| format!("invalid weight '{}' for {}", weight, model))?;
+    if !(weight > 0.0 && weight.is_finite()) {
+        return Err(format!("weight for {} must be positive", model));
+    }
+    Ok((crate::models::resolve(model), weight))
+}
+
+fn parse_truth_score(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(score) if (0.0..=100.0).contains(&score) => Ok(score),
+        _ => Err(format!("'{}' is not a truth score between 0 and 100", s)),
+    }
+}
+
+fn parse_quorum(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(ratio) if ratio > 0.0 && ratio <= 1.0 => Ok(ratio),
+        _ => Err(format!("'{}' is not a share of votes in (0, 1]", s)),
+    }
+}
+
+fn parse_regex(s: &str) -> Result<regex::Regex, String> {
+    regex::Regex::new(s).map_err(|e| e.to_string())
+}
+
+fn parse_cost_cap(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(usd) if usd.is_finite() && usd > 0.0 => Ok(usd),
+        _ => Err(format!("'{}' is not a positive USD amount", s)),
+    }
+}
+
+fn parse_half_life(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(days) if days.is_finite() && days > 0.0 => Ok(days),
+        _ => Err(format!("'{}' is not a positive number of days", s)),
+    }
+}
+
+fn parse_temperature(s: &str) -> Result<f32, String> {
+    let temperature: f32 = s.parse().map_err(|_| format!("invalid temperature '{}'", s))?;
+    if !(0.0..=2.0).contains(&temperature) {
+        return Err(format!("temperature {} is outside 0.0–2.0", temperature));
+    }
+    Ok(temperature)
+}
+
+fn parse_model_temperature(s: &str) -> Result<(String, f32), String> {
+    let (model, temperature) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected MODEL=T, got '{}'", s))?;
+    Ok((crate::models::resolve(model), parse_temperature(temperature)?))
+}
+
+fn parse_model_sla(s: &str) -> Result<(String, u64), String> {
+    let (model, ms) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected MODEL=MS, got '{}'", s))?;
+    match ms.parse::<u64>() {
+        Ok(ms) if ms > 0 => Ok((crate::models::resolve(model), ms)),
+        _ => Err(format!("'{}' is not a positive number of milliseconds for {}", ms, model)),
+    }
+}
+
+fn parse_fallback_model(s: &str) -> Result<(String, String), String> {
+    let resolved = s.split_once('=').map(|(m, b)| (crate::models::resolve(m), crate::models::resolve(b)));
+    match resolved {
+        Some((model, backup)) if model == backup => Err(format!("{} cannot be its own fallback", model)),
+        Some((model, backup)) if !model.is_empty() && !backup.is_empty() => Ok((model, backup)),
+        _ => Err(format!("expected MODEL=BACKUP, got '{}'", s)),
+    }
+}
+
+/// Backends `--extra-header` can target.
+pub const HEADER_BACKENDS: [&str; 4] = ["openai", "openrouter", "grok", "anthropic"];
+
+fn parse_extra_header(s: &str) -> Result<(String, String, String), String> {
+    let (backend, header) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected BACKEND:NAME=VALUE, got '{}'", s))?;
+    if !HEADER_BACKENDS.contains(&backend) {
+        return Err(format!("unknown backend '{}' (expected one of {})", backend, HEADER_BACKENDS.join(", ")));
+    }
+    let (name, value) = header
+        .split_once('=')
+        .ok_or_else(|| format!("expected BACKEND:NAME=VALUE, got '{}'", s))?;
+    reqwest::header::HeaderName::from_bytes(name.as_bytes()).map_err(|_| format!("invalid header name '{}'", name))?;
+    reqwest::header::HeaderValue::from_str(value).map_err(|_| format!("invalid value for header {}", name))?;
+    Ok((backend.to_string(), name.to_string(), value.to_string()))
+}
+
+fn parse_model_limit(s: &str) -> Result<(Option<String>, usize), String> {
+    let (model, limit) = match s.split_once('=') {
+        Some((model, limit)) => (Some(crate::models::resolve(model)), limit),
+        None => (None, s),
+    };
+    let limit: usize = limit
+        .parse()
+        .map_err(|_| format!("invalid token limit '{}'", limit))?;
+    if limit == 0 {
+        return Err("token limit must be positive".to_string());
+    }
+    Ok((model, limit))
+}
@@ -0,0 +1,491 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::config::ConsensusStrategy;
+use crate::{AIResponse, Abstention};
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConsensusResult {
+    pub consensus: bool,
+    /// 0–100 estimate that the statement is true; see `truth_score`.
+    pub truth_score: f32,
+    /// Agents that voted with the verdict, in vote order.
+    pub majority_agents: Vec<String>,
+    /// Agents that voted against it, in vote order.
+    pub dissenting_agents: Vec<String>,
+    pub details: String,
+    /// Under veto, the trusted models that overturned a majority "yes".
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vetoed_by: Vec<String>,
+}
+
+/// `--weights`: fixed per-model trust weights for majority and veto, read from a
+/// JSON object of model names to positive weights. Unlisted models weigh 1.0.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(transparent)]
+pub struct TrustWeights(pub BTreeMap<String, f64>);
+
+impl TrustWeights {
+    /// Reads a weights file; model names resolve as in model flags.
+    pub fn parse(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let raw: BTreeMap<String, f64> =
+            serde_json::from_str(&text).map_err(|e| format!("{}: expected {{\"model\": weight, ...}}: {}", path, e))?;
+        let mut weights = BTreeMap::new();
+        for (model, weight) in raw {
+            if !(weight > 0.0 && weight.is_finite()) {
+                return Err(format!("{}: weight for {} must be positive", path, model));
+            }
+            weights.insert(crate::models::resolve(&model), weight);
+        }
+        Ok(Self(weights))
+    }
+
+    pub fn get(&self, model: &str) -> f64 {
+        self.0.get(model).copied().unwrap_or(1.0)
+    }
+}
+
+/// Names of the agents that voted `verdict`.
+fn side(votes: &[AIResponse], verdict: bool) -> Vec<String> {
+    votes.iter().filter(|v| v.is_valid == verdict).map(|v| v.agent_name.clone()).collect()
+}
+
+/// Settings a strategy may draw on besides the votes themselves.
+pub struct Rules<'a> {
+    /// Provider-quorum: distinct providers that must agree "yes".
+    pub min_providers: usize,
+    /// Provider-quorum: providers that must be among those agreeing.
+    pub required: &'a [String],
+    /// Truth-score: the weighted score (0–100) consensus needs.
+    pub min_truth_score: f32,
+    /// Majority and veto: the share of "yes" votes needed instead of more than half.
+    pub quorum: Option<f64>,
+    /// Veto: models whose "no" fails consensus.
+    pub veto: &'a [String],
+    /// Veto: models that must vote "yes" for consensus.
+    pub required_yes: &'a [String],
+    /// Majority and veto: `--weights`, counting each vote as its model's weight.
+    pub trust: Option<&'a TrustWeights>,
+    /// Weighted-majority and truth-score: a model's vote weight.
+    pub weight: &'a dyn Fn(&str) -> f64,
+}
+
+/// Verdict under `strategy`.
+pub fn apply(strategy: ConsensusStrategy, votes: &[AIResponse], rules: &Rules) -> ConsensusResult {
+    match strategy {
+        ConsensusStrategy::Majority => majority(votes, rules.quorum, rules.trust),
+        ConsensusStrategy::ProviderQuorum => provider_quorum(votes, rules.min_providers, rules.required),
+        ConsensusStrategy::WeightedMajority => weighted_majority(votes, rules.weight),
+        ConsensusStrategy::TruthScore => truth_score_threshold(votes, rules.min_truth_score, rules.weight),
+        ConsensusStrategy::Veto => veto(votes, rules.quorum, rules.trust, rules.veto, rules.required_yes),
+    }
+}
+
+/// `--error-policy no`: the votes plus a certain "no" from every agent whose call
+/// failed. Refusals are answers, not failures, so they still abstain.
+pub fn with_failures_as_no(votes: &[AIResponse], abstentions: &[Abstention]) -> Vec<AIResponse> {
+    let failed = abstentions.iter().filter(|a| !a.refusal).map(|a| AIResponse {
+        agent_name: a.agent_name.clone(),
+        model: a.model.clone(),
+        is_valid: false,
+        confidence: None,
+        attempts: 1,
+        lang: None,
+        system_fingerprint: None,
+        fallback_from: None,
+        self_consistency: None,
+        rationale: None,
+    });
+    votes.iter().cloned().chain(failed).collect()
+}
+
+/// Consensus holds when the confidence- and reputation-weighted truth score reaches
+/// `min`. The recorded truth score is that weighted score.
+pub fn truth_score_threshold(votes: &[AIResponse], min: f32, weight: &dyn Fn(&str) -> f64) -> ConsensusResult {
+    let truth_score = weighted_truth_score(votes, weight);
+    let consensus = !votes.is_empty() && truth_score >= min;
+    ConsensusResult {
+        consensus,
+        truth_score,
+        majority_agents: side(votes, consensus),
+        dissenting_agents: side(votes, !consensus),
+        details: format!(
+            "reputation-weighted truth score {:.1} {} the {:.1} needed ({} votes)",
+            truth_score,
+            if consensus { "meets" } else { "is below" },
+            min,
+            votes.len()
+        ),
+        vetoed_by: Vec::new(),
+    }
+}
+
+/// Consensus holds when the "yes" votes carry more than half of the total weight.
+pub fn weighted_majority(votes: &[AIResponse], weight: &dyn Fn(&str) -> f64) -> ConsensusResult {
+    let total: f64 = votes.iter().map(|v| weight(&v.model)).sum();
+    let yes: f64 = votes.iter().filter(|v| v.is_valid).map(|v| weight(&v.model)).sum();
+    let consensus = total > 0.0 && yes > total / 2.0;
+    let truth_score = truth_score(votes);
+    ConsensusResult {
+        consensus,
+        truth_score,
+        majority_agents: side(votes, consensus),
+        dissenting_agents: side(votes, !consensus),
+        details: format!(
+            "{:.2}/{:.2} of reputation-weighted votes said yes, truth score {:.1}",
+            yes, total, truth_score
+        ),
+        vetoed_by: Vec::new(),
+    }
+}
+
+/// Simple majority: consensus holds when more than half of the votes are "yes".
+/// An even split is not a majority: a tie is committed with `consensus: false`,
+/// like any other failed vote. No votes fail too, though `--min-responses` keeps
+/// such a round from being recorded.
+pub fn form_consensus(votes: &[AIResponse]) -> ConsensusResult {
+    let valid_count = votes.iter().filter(|v| v.is_valid).count();
+    let total_count = votes.len();
+    let consensus = valid_count > total_count / 2;
+    let truth_score = truth_score(votes);
+    ConsensusResult {
+        consensus,
+        truth_score,
+        majority_agents: side(votes, consensus),
+        dissenting_agents: side(votes, !consensus),
+        details: format!(
+            "{}/{} agents voted yes, truth score {:.1}",
+            valid_count, total_count, truth_score
+        ),
+        vetoed_by: Vec::new(),
+    }
+}
+
+/// Majority (or `--quorum`, either optionally `--weights`-weighted), overruled by trusted
+/// models: consensus fails when any model in `veto` votes "no", or any in `required_yes`
+/// doesn't vote "yes" (abstaining included). `vetoed_by` names them only when they
+/// turned a majority "yes" around.
+pub fn veto(
+    votes: &[AIResponse],
+    quorum: Option<f64>,
+    trust: Option<&TrustWeights>,
+    veto: &[String],
+    required_yes: &[String],
+) -> ConsensusResult {
+    let mut result = majority(votes, quorum, trust);
+    let voted = |model: &str, verdict: bool| votes.iter().any(|v| v.model == model && v.is_valid == verdict);
+    let mut vetoed_by: Vec<String> = veto.iter().filter(|m| voted(m, false)).cloned().collect();
+    for model in required_yes {
+        if !voted(model, true) && !vetoed_by.contains(model) {
+            vetoed_by.push(model.clone());
+        }
+    }
+    if result.consensus && !vetoed_by.is_empty() {
+        result.consensus = false;
+        result.majority_agents = side(votes, false);
+        result.dissenting_agents = side(votes, true);
+        result.details.push_str(&format!("; vetoed by {}", vetoed_by.join(", ")));
+        result.vetoed_by = vetoed_by;
+    }
+    result
+}
+
+/// `form_consensus`, or under `--quorum` a `quorum_share` of at least `quorum`; with
+/// `--weights`, the same rule over the `trust_share`.
+fn majority(votes: &[AIResponse], quorum: Option<f64>, trust: Option<&TrustWeights>) -> ConsensusResult {
+    match (trust, quorum) {
+        (Some(trust), quorum) => trust_share(votes, quorum, trust),
+        (None, Some(quorum)) => quorum_share(votes, quorum),
+        (None, None) => form_consensus(votes),
+    }
+}
+
+/// Consensus holds when the "yes" voters' share of the total trust weight reaches
+/// `quorum`, or without one is more than half. The truth score is weighted the same way.
+pub fn trust_share(votes: &[AIResponse], quorum: Option<f64>, trust: &TrustWeights) -> ConsensusResult {
+    let total: f64 = votes.iter().map(|v| trust.get(&v.model)).sum();
+    let yes: f64 = votes.iter().filter(|v| v.is_valid).map(|v| trust.get(&v.model)).sum();
+    let share = if total > 0.0 { yes / total } else { 0.0 };
+    let consensus = total > 0.0 && quorum.map_or(share > 0.5, |q| share >= q);
+    let truth_score = weighted_truth_score(votes, &|model| trust.get(model));
+    let needed = quorum.map_or("more than half".to_string(), |q| format!("{:.0}%", q * 100.0));
+    ConsensusResult {
+        consensus,
+        truth_score,
+        majority_agents: side(votes, consensus),
+        dissenting_agents: side(votes, !consensus),
+        details: format!(
+            "weighted {:.1}/{:.1} = {:.0}% yes ({} needed), truth score {:.1}",
+            yes,
+            total,
+            share * 100.0,
+            needed,
+            truth_score
+        ),
+        vetoed_by: Vec::new(),
+    }
+}
+
+/// Consensus holds when at least `quorum` of the votes are "yes"; no votes never
+/// reach it.
+pub fn quorum_share(votes: &[AIResponse], quorum: f64) -> ConsensusResult {
+    let valid_count = votes.iter().filter(|v| v.is_valid).count();
+    let total_count = votes.len();
+    let consensus = total_count > 0 && valid_count as f64 / total_count as f64 >= quorum;
+    let truth_score = truth_score(votes);
+    ConsensusResult {
+        consensus,
+        truth_score,
+        majority_agents: side(votes, consensus),
+        dissenting_agents: side(votes, !consensus),
+        details: format!(
+            "{}/{} agents voted yes ({:.0}% needed), truth score {:.1}",
+            valid_count,
+            total_count,
+            quorum * 100.0,
+            truth_score
+        ),
+        vetoed_by: Vec::new(),
+    }
+}
+
+/// The provider half of a model id: `openai` for `openai/gpt-4o`.
+pub fn provider_of(model: &str) -> &str {
+    model.split_once('/').map_or(model, |(provider, _)| provider)
+}
+
+/// Agreement across providers rather than agents: each provider takes the majority
+/// position of its own agents (a tie counts for neither side), and consensus holds
+/// when more providers say "yes" than "no", at least `min_providers` of them do,
+/// and every provider in `required` is among them.
+pub fn provider_quorum(votes: &[AIResponse], min_providers: usize, required: &[String]) -> ConsensusResult {
+    let mut tallies: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for v in votes {
+        let tally = tallies.entry(provider_of(&v.model)).or_default();
+        if v.is_valid { tally.0 += 1 } else { tally.1 += 1 }
+    }
+    let yes: Vec<&str> = tallies.iter().filter(|(_, (y, n))| y > n).map(|(p, _)| *p).collect();
+    let no = tallies.values().filter(|(y, n)| n > y).count();
+    let missing: Vec<&str> = required
+        .iter()
+        .map(String::as_str)
+        .filter(|p| !yes.contains(p))
+        .collect();
+    let consensus = yes.len() > no && yes.len() >= min_providers && missing.is_empty();
+    let truth_score = truth_score(votes);
+
+    let mut details = format!(
+        "{}/{} providers agreed yes ({}), {} needed, truth score {:.1}",
+        yes.len(),
+        tallies.len(),
+        yes.join(", "),
+        min_providers,
+        truth_score
+    );
+    if !missing.is_empty() {
+        details.push_str(&format!("; required but not agreeing: {}", missing.join(", ")));
+    }
+    ConsensusResult {
+        consensus,
+        truth_score,
+        majority_agents: side(votes, consensus),
+        dissenting_agents: side(votes, !consensus),
+        details,
+        vetoed_by: Vec::new(),
+    }
+}
+
+/// Mean probability, scaled to 0–100, that the statement is true.
+///
+/// A "yes" at confidence c counts as c, a "no" at confidence c as 1 - c; a vote
+/// without a confidence counts as certain. No votes score a neutral 50.
+pub fn truth_score(votes: &[AIResponse]) -> f32 {
+    weighted_truth_score(votes, &|_| 1.0)
+}
+
+/// `truth_score` with each vote counted `weight(model)` times. A panel whose weights
+/// sum to zero also scores a neutral 50.
+pub fn weighted_truth_score(votes: &[AIResponse], weight: &dyn Fn(&str) -> f64) -> f32 {
+    let total: f64 = votes.iter().map(|v| weight(&v.model)).sum();
+    if total <= 0.0 {
+        return 50.0;
+    }
+    let sum: f64 = votes
+        .iter()
+        .map(|v| {
+            let confidence = v.confidence.unwrap_or(1.0) as f64;
+            let p = if v.is_valid { confidence } else { 1.0 - confidence };
+            p * weight(&v.model)
+        })
+        .sum();
+    (100.0 * sum / total) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truth_score_strategy_weighs_confidence_by_reputation() {
+        let mut doubtful = vote_from("x/grok", false);
+        doubtful.confidence = Some(0.8);
+        let votes = [vote_from("openai/gpt-4o", true), doubtful];
+        let weight = |model: &str| if model == "openai/gpt-4o" { 0.75 } else { 0.25 };
+        // (1.0 * 0.75 + 0.2 * 0.25) / 1.0 = 0.8
+        let result = truth_score_threshold(&votes, 80.0, &weight);
+        assert!(result.consensus && (result.truth_score - 80.0).abs() < 1e-4, "{:?}", result);
+        assert!(!truth_score_threshold(&votes, 80.0, &|_| 1.0).consensus);
+        assert!(!truth_score_threshold(&[], 0.0, &weight).consensus);
+    }
+
+    #[test]
+    fn weighted_majority_lets_trusted_models_outvote_more_numerous_ones() {
+        let votes = [vote_from("openai/gpt-4o", false), vote_from("x/grok", true), vote_from("meta/llama", true)];
+        let weight = |model: &str| if model == "openai/gpt-4o" { 0.9 } else { 0.3 };
+        assert!(!weighted_majority(&votes, &weight).consensus);
+        assert!(weighted_majority(&votes, &|_| 1.0).consensus);
+        assert!(!weighted_majority(&[], &weight).consensus);
+    }
+
+    fn vote(is_valid: bool, confidence: Option<f32>) -> AIResponse {
+        AIResponse {
+            agent_name: "agent".to_string(),
+            model: "model".to_string(),
+            is_valid,
+            confidence,
+            attempts: 1,
+            lang: None,
+            system_fingerprint: None,
+            fallback_from: None,
+            self_consistency: None,
+            rationale: None,
+        }
+    }
+
+    fn vote_from(model: &str, is_valid: bool) -> AIResponse {
+        AIResponse {
+            model: model.to_string(),
+            ..vote(is_valid, None)
+        }
+    }
+
+    #[test]
+    fn failures_count_as_no_but_refusals_abstain() {
+        let abstention = |agent: &str, refusal| Abstention {
+            agent_name: agent.to_string(),
+            model: "model".to_string(),
+            reason: "timed out".to_string(),
+            refusal,
+        };
+        let tally = with_failures_as_no(&[vote(true, None)], &[abstention("a", false), abstention("b", true)]);
+        assert_eq!(tally.len(), 2);
+        assert!(!tally[1].is_valid && tally[1].agent_name == "a");
+        assert!(!form_consensus(&tally).consensus);
+    }
+
+    #[test]
+    fn majority_needs_more_than_half() {
+        assert!(form_consensus(&[vote(true, None), vote(true, None), vote(false, None)]).consensus);
+        assert!(!form_consensus(&[vote(true, None), vote(false, None)]).consensus);
+        let tie = [vote(true, None), vote(true, None), vote(false, None), vote(false, None)];
+        assert!(!form_consensus(&tie).consensus);
+        let empty = form_consensus(&[]);
+        assert!(!empty.consensus && empty.details.starts_with("0/0 agents voted yes"));
+        let votes = [vote_from("a/1", true), vote_from("b/2", false), vote_from("c/3", true)];
+        let votes = votes.map(|v| AIResponse { agent_name: v.model.clone(), ..v });
+        let result = form_consensus(&votes);
+        assert_eq!(result.majority_agents, ["a/1", "c/3"]);
+        assert_eq!(result.dissenting_agents, ["b/2"]);
+    }
+
+    #[test]
+    fn a_trusted_model_overrules_the_majority() {
+        let votes = [vote_from("openai/gpt-4o", true), vote_from("meta/llama", true), vote_from("x/grok", false)];
+        let grok = ["x/grok".to_string()];
+        let result = veto(&votes, None, None, &grok, &[]);
+        assert!(!result.consensus);
+        assert_eq!(result.vetoed_by, grok);
+        assert!(result.details.ends_with("; vetoed by x/grok"), "{}", result.details);
+        assert_eq!(result.majority_agents, ["agent"]);
+
+        assert!(veto(&votes, None, None, &["openai/gpt-4o".to_string()], &[]).consensus);
+        let absent = veto(&votes, None, None, &[], &["google/gemini-pro".to_string()]);
+        assert_eq!((absent.consensus, absent.vetoed_by.as_slice()), (false, ["google/gemini-pro".to_string()].as_slice()));
+        // Already a "no": nothing to overturn, so no veto is recorded.
+        let no = veto(&votes[1..], None, None, &grok, &[]);
+        assert!(!no.consensus && no.vetoed_by.is_empty());
+    }
+
+    #[test]
+    fn quorum_is_a_share_of_yes_votes() {
+        let votes = [vote(true, None), vote(true, None), vote(false, None)];
+        assert!(quorum_share(&votes, 0.66).consensus);
+        let result = quorum_share(&votes, 0.75);
+        assert!(!result.consensus);
+        assert!(result.details.starts_with("2/3 agents voted yes (75% needed)"), "{}", result.details);
+        // Unlike the default majority, a quorum of 0.5 accepts a tie.
+        assert!(quorum_share(&votes[1..], 0.5).consensus);
+        assert!(!quorum_share(&[], 0.1).consensus);
+        assert!(!veto(&votes, Some(0.75), None, &[], &[]).consensus);
+    }
+
+    #[test]
+    fn trust_weights_scale_each_vote() {
+        let votes = [
+            vote_from("openai/gpt-4o", true),
+            vote_from("mistral/mistral-7b-instruct", false),
+            vote_from("meta/llama-3-70b-instruct", false),
+        ];
+        let trust = TrustWeights(BTreeMap::from([
+            ("openai/gpt-4o".to_string(), 3.5),
+            ("mistral/mistral-7b-instruct".to_string(), 0.5),
+        ]));
+        let result = trust_share(&votes, None, &trust);
+        assert!(result.consensus, "{}", result.details);
+        assert!(result.details.starts_with("weighted 3.5/5.0 = 70% yes (more than half needed)"), "{}", result.details);
+        assert!(!trust_share(&votes, Some(0.75), &trust).consensus);
+        assert!(!trust_share(&[], None, &trust).consensus);
+        // Equal weights are the plain majority.
+        let equal = TrustWeights::default();
+        assert_eq!(trust_share(&votes, None, &equal).consensus, form_consensus(&votes).consensus);
+        assert!(!veto(&votes, None, Some(&trust), &["mistral/mistral-7b-instruct".to_string()], &[]).consensus);
+    }
+
+    #[test]
+    fn truth_score_weighs_confidence() {
+        assert_eq!(truth_score(&[]), 50.0);
+        assert_eq!(truth_score(&[vote(true, None), vote(false, None)]), 50.0);
+        let score = truth_score(&[vote(true, Some(0.9)), vote(false, Some(0.6))]);
+        assert!((score - 65.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn one_provider_is_not_a_quorum() {
+        let votes = [
+            vote_from("openai/gpt-4o", true),
+            vote_from("openai/gpt-3.5-turbo", true),
+            vote_from("openai/gpt-4o", true),
+            vote_from("anthropic/claude-3-haiku", false),
+        ];
+        assert!(form_consensus(&votes).consensus);
+        let result = provider_quorum(&votes, 2, &[]);
+        assert!(!result.consensus);
+        assert!(result.details.starts_with("1/2 providers agreed yes (openai)"));
+    }
+
+    #[test]
+    fn quorum_needs_every_required_provider() {
+        let votes = [
+            vote_from("openai/gpt-4o", true),
+            vote_from("google/gemini-pro", true),
+            vote_from("anthropic/claude-3-haiku", true),
+            vote_from("anthropic/claude-3-haiku", false),
+        ];
+        assert!(provider_quorum(&votes, 2, &[]).consensus);
+        let result = provider_quorum(&votes, 2, &["anthropic".to_string()]);
+        assert!(!result.consensus);
+        assert!(result.details.ends_with("required but not agreeing: anthropic"));
+    }
+}
@@ -0,0 +1,127 @@
+use async_trait::async_trait;
+use futures::future::join_all;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::breaker::BreakerState;
+use crate::{AIProvider, AIResponse, ProviderError};
+
+/// How far an agent agreed with itself under `--self-consistency`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SelfConsistency {
+    /// Times the model was asked.
+    pub samples: u32,
+    /// Samples that came back with a verdict; the rest failed.
+    pub answered: u32,
+    /// Answered samples that gave the majority verdict, i.e. the vote.
+    pub agreeing: u32,
+}
+
+/// `--self-consistency K`: asks the slot's model `samples` times at once and votes
+/// its majority verdict, so one unlucky sample can't flip the agent. An even split
+/// is no verdict and the agent abstains.
+pub struct SelfConsistentProvider {
+    inner: Box<dyn AIProvider>,
+    samples: u32,
+}
+
+impl SelfConsistentProvider {
+    pub fn new(inner: Box<dyn AIProvider>, samples: u32) -> Self {
+        Self { inner, samples }
+    }
+}
+
+#[async_trait]
+impl AIProvider for SelfConsistentProvider {
+    fn agent_name(&self) -> &str {
+        self.inner.agent_name()
+    }
+
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+
+    fn breaker_state(&self) -> Option<BreakerState> {
+        self.inner.breaker_state()
+    }
+
+    async fn validate(&self, text: &str, debate: Option<&str>) -> Result<AIResponse, ProviderError> {
+        let samples = join_all((0..self.samples).map(|_| self.inner.validate(text, debate))).await;
+        majority(samples)
+    }
+}
+
+/// The majority verdict of `samples`, with the mean confidence of the samples that
+/// gave it. Fails with the first sample's error when none answered.
+fn majority(samples: Vec<Result<AIResponse, ProviderError>>) -> Result<AIResponse, ProviderError> {
+    let asked = samples.len() as u32;
+    let mut first_error = None;
+    let mut votes = Vec::new();
+    for sample in samples {
+        match sample {
+            Ok(vote) => votes.push(vote),
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    if votes.is_empty() {
+        return Err(first_error.expect("at least one sample"));
+    }
+    let yes = votes.iter().filter(|v| v.is_valid).count() as u32;
+    let no = votes.len() as u32 - yes;
+    if yes == no {
+        return Err(ProviderError::SelfSplit { yes, no });
+    }
+    let verdict = yes > no;
+    let attempts = votes.iter().map(|v| v.attempts).sum();
+    let agreeing: Vec<&AIResponse> = votes.iter().filter(|v| v.is_valid == verdict).collect();
+    let confidences: Vec<f32> = agreeing.iter().filter_map(|v| v.confidence).collect();
+    let confidence = (!confidences.is_empty()).then(|| confidences.iter().sum::<f32>() / confidences.len() as f32);
+    let consistency = SelfConsistency {
+        samples: asked,
+        answered: votes.len() as u32,
+        agreeing: agreeing.len() as u32,
+    };
+    Ok(AIResponse {
+        is_valid: verdict,
+        confidence,
+        attempts,
+        self_consistency: Some(consistency),
+        ..agreeing[0].clone()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(is_valid: bool, confidence: f32) -> Result<AIResponse, ProviderError> {
+        Ok(AIResponse {
+            agent_name: "agent-1".to_string(),
+            model: "openai/gpt-4o".to_string(),
+            is_valid,
+            confidence: Some(confidence),
+            attempts: 1,
+            lang: None,
+            system_fingerprint: None,
+            fallback_from: None,
+            self_consistency: None,
+            rationale: None,
+        })
+    }
+
+    #[test]
+    fn votes_the_majority_of_answered_samples() {
+        let failed = || Err(ProviderError::Malformed("empty".to_string()));
+        let vote = majority(vec![sample(true, 0.9), sample(false, 0.4), sample(true, 0.7), failed()]).unwrap();
+        assert!(vote.is_valid);
+        assert!((vote.confidence.unwrap() - 0.8).abs() < 1e-6);
+        assert_eq!(vote.attempts, 3);
+        assert_eq!(vote.self_consistency, Some(SelfConsistency { samples: 4, answered: 3, agreeing: 2 }));
+
+        let split = majority(vec![sample(true, 0.9), sample(false, 0.9)]).unwrap_err();
+        assert_eq!(split.to_string(), "split 1–1 across its own samples");
+        assert_eq!(majority(vec![failed(), failed()]).unwrap_err().to_string(), "unusable response: empty");
+    }
+}
@@ -0,0 +1,309 @@
+use serde::Deserialize;
+use serde_json::json;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, BufReader};
+
+use crate::checkpoint::{self, Checkpoint};
+use crate::claim::Claim;
+use crate::{analytics, cost};
+use crate::input::{Line, LineReader, MAX_LINE_BYTES};
+use crate::progress::{self, Progress};
+use crate::{run_round, AppState, Record, LEDGER};
+
+const MENU_HELP: &str = "type continue (c, y, yes, cont) or exit (q, quit, exit)";
+
+/// An answer to the continue/exit prompt.
+#[derive(Debug, PartialEq, Eq)]
+enum MenuChoice {
+    Continue,
+    Exit,
+    Invalid,
+}
+
+/// Reads the answer to the continue/exit prompt, accepting aliases in any case.
+fn parse_menu_choice(input: &str) -> MenuChoice {
+    match input.trim().to_lowercase().as_str() {
+        "c" | "y" | "yes" | "cont" | "continue" => MenuChoice::Continue,
+        "q" | "quit" | "exit" => MenuChoice::Exit,
+        _ => MenuChoice::Invalid,
+    }
+}
+
+/// Only an explicit yes confirms a paid round; anything else declines.
+fn confirms(input: &str) -> bool {
+    matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+fn prompt(text: &str) -> std::io::Result<()> {
+    print!("{}", text);
+    std::io::stdout().flush()
+}
+
+/// Consecutive read failures after which the console gives up.
+const MAX_READ_ERRORS: u32 = 3;
+
+/// Shows `text` and reads an answer, repeating the prompt after a read error or an
+/// over-long or binary line. `None` at end of input.
+async fn read_answer<R: AsyncBufRead + Unpin>(
+    lines: &mut LineReader<R>,
+    text: &str,
+) -> std::io::Result<Option<String>> {
+    let mut errors = 0;
+    loop {
+        prompt(text)?;
+        match lines.next_line().await {
+            Ok(None) => return Ok(None),
+            Ok(Some(Line::Text(line))) => return Ok(Some(line)),
+            Ok(Some(Line::TooLong)) => println!("Input is longer than {} bytes; ignored.", MAX_LINE_BYTES),
+            Ok(Some(Line::NotText)) => println!("Input is not UTF-8 text; ignored."),
+            Err(e) => {
+                errors += 1;
+                if errors >= MAX_READ_ERRORS {
+                    return Err(e);
+                }
+                println!("Could not read input ({}); try again.", e);
+            }
+        }
+    }
+}
+
+/// Runs `config.repeat` rounds on `statement`, printing each record and, for repeats,
+/// the agreement summary. A statement written as a claim's JSON object is validated
+/// as that claim. Returns how many rounds were recorded.
+async fn validate(state: &AppState, progress: &Progress, statement: &str) -> Vec<Record> {
+    validate_rounds(state, progress, statement, state.config.repeat).await
+}
+
+/// Runs `rounds` rounds on `statement` and returns the records they committed.
+async fn validate_rounds(state: &AppState, progress: &Progress, statement: &str, rounds: u32) -> Vec<Record> {
+    let claim = Claim::from_line(statement);
+    let mut recorded = Vec::new();
+    for _ in 0..rounds {
+        match run_round(state, statement, claim.as_ref(), Some(progress)).await {
+            Ok(response) => {
+                let record = response.record;
+                let index = LEDGER
+                    .read()
+                    .await
+                    .records()
+                    .iter()
+                    .position(|r| r.id == record.id)
+                    .map_or(0, |i| i + 1);
+                progress.recorded(index, &record);
+                recorded.push(record);
+            }
+            Err(e) => progress.failed(&e.message),
+        }
+    }
+    if rounds > 1 && !recorded.is_empty() {
+        progress.summary(&analytics::agreement(&recorded));
+    }
+    recorded
+}
+
+/// `ask`: validates a single statement from the command line; `false` if nothing
+/// was recorded.
+pub async fn ask(state: &AppState, statement: &str) -> std::io::Result<bool> {
+    let progress = Progress::new(state.config.format, state.config.output.as_deref(), state.config.no_color)?;
+    Ok(!validate(state, &progress, statement).await.is_empty())
+}
+
+/// What an `--input` batch recorded, by question.
+#[derive(Debug, Default)]
+pub struct BatchOutcome {
+    /// Questions that recorded at least one round, counting those a resumed run skipped.
+    pub answered: usize,
+    /// Questions this run recorded a round without consensus for.
+    pub without_consensus: usize,
+}
+
+/// `--input`/`--input-url`: validates each question in order, pausing `--delay-ms`
+/// between them. With a `--resume` checkpoint file, starts after the questions it
+/// records as done and updates it after each one.
+pub async fn batch(state: &AppState, questions: &[String], checkpoint: Option<&Path>) -> std::io::Result<BatchOutcome> {
+    let progress = Progress::new(state.config.format, state.config.output.as_deref(), state.config.no_color)?;
+    let delay = Duration::from_millis(state.config.delay_ms);
+    let mut start = 0;
+    if let Some(path) = checkpoint {
+        if let Some(saved) = checkpoint::load(path)? {
+            start = checkpoint::resume_position(questions, &saved, LEDGER.read().await.records())
+                .map_err(|e| std::io::Error::other(format!("cannot resume from {}: {}", path.display(), e)))?;
+            println!("Resuming {}: {} of {} questions already done.", path.display(), start, questions.len());
+        }
+    }
+    let mut outcome = BatchOutcome { answered: start, without_consensus: 0 };
+    for (i, question) in questions.iter().enumerate().skip(start) {
+        if i > start && !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        let recorded = validate(state, &progress, question).await;
+        outcome.answered += usize::from(!recorded.is_empty());
+        outcome.without_consensus += usize::from(recorded.iter().any(|r| !r.consensus));
+        if let Some(path) = checkpoint {
+            let done = Checkpoint::new(questions, i + 1, LEDGER.read().await.records());
+            checkpoint::save(path, &done)?;
+        }
+        if state.spend_cap.as_ref().is_some_and(|cap| cap.halted()) {
+            println!("Cost cap reached; {} of {} questions left unasked.", questions.len() - i - 1, questions.len());
+            break;
+        }
+    }
+    Ok(outcome)
+}
+
+/// One `--stdin-jsonl` input line: a statement as `content`, or a structured `claim`.
+#[derive(Debug, Deserialize)]
+struct JsonlInput {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    claim: Option<Claim>,
+}
+
+fn parse_jsonl(line: &str) -> Result<JsonlInput, String> {
+    let input: JsonlInput = serde_json::from_str(line).map_err(|e| format!("not a JSON object: {}", e))?;
+    match input.content.is_some() == input.claim.is_some() {
+        true => Err("send exactly one of content or claim".to_string()),
+        false => Ok(input),
+    }
+}
+
+/// `--stdin-jsonl`: a Unix filter. Each non-blank stdin line is one JSON input and
+/// gets exactly one line on stdout, in order: the committed record, or
+/// `{"line": N, "error": "..."}` when it recorded nothing. Nothing else is printed.
+/// Returns how many records were committed.
+pub async fn jsonl(state: &AppState) -> std::io::Result<usize> {
+    let mut lines = LineReader::new(BufReader::new(tokio::io::stdin()));
+    let mut stdout = std::io::stdout();
+    let mut committed = 0;
+    let mut number = 0;
+    while let Some(line) = lines.next_line().await? {
+        number += 1;
+        let input = match line {
+            Line::Text(text) if text.trim().is_empty() => continue,
+            Line::Text(text) => parse_jsonl(&text),
+            Line::TooLong => Err(format!("line is longer than {} bytes", MAX_LINE_BYTES)),
+            Line::NotText => Err("line is not UTF-8 text".to_string()),
+        };
+        let outcome = match input {
+            Ok(input) => {
+                let statement = input.content.as_deref().unwrap_or_default();
+                run_round(state, statement, input.claim.as_ref(), None).await.map_err(|e| e.message)
+            }
+            Err(e) => Err(e),
+        };
+        let out = match outcome {
+            Ok(response) => {
+                committed += 1;
+                serde_json::to_string(&progress::redacted(&response.record)).expect("records serialize")
+            }
+            Err(error) => json!({ "line": number, "error": error }).to_string(),
+        };
+        writeln!(stdout, "{}", out)?;
+        stdout.flush()?;
+    }
+    Ok(committed)
+}
+
+/// Rounds the next submission may run under `--max-session-blocks`, given how many
+/// blocks the session has already committed.
+fn rounds_allowed(repeat: u32, limit: Option<u32>, committed: u32) -> u32 {
+    limit.map_or(repeat, |limit| repeat.min(limit.saturating_sub(committed)))
+}
+
+/// Interactive session: validates each statement typed at the prompt and prints the
+/// committed record, until the user exits or stdin closes.
+pub async fn run(state: &AppState) -> std::io::Result<()> {
+    let mut lines = LineReader::new(BufReader::new(tokio::io::stdin()));
+    let progress = Progress::new(state.config.format, state.config.output.as_deref(), state.config.no_color)?;
+    let mut committed = 0;
+    loop {
+        let Some(statement) = read_answer(&mut lines, "Enter a statement to validate: ").await? else {
+            return Ok(());
+        };
+        if state.config.confirm_cost {
+            let config = &state.config;
+            let rounds = match config.repeat {
+                1 => String::new(),
+                n => format!(" in {} rounds", n),
+            };
+            let question = format!(
+                "This will query {} models{} (~${:.4}). Proceed? [y/N] ",
+                cost::agents_per_round(config),
+                rounds,
+                cost::statement_cost(&statement, config)
+            );
+            let Some(answer) = read_answer(&mut lines, &question).await? else {
+                return Ok(());
+            };
+            if !confirms(&answer) {
+                println!("Skipped; nothing was sent.");
+                continue;
+            }
+        }
+        let rounds = rounds_allowed(state.config.repeat, state.config.max_session_blocks, committed);
+        committed += validate_rounds(state, &progress, &statement, rounds).await.len() as u32;
+        if let Some(limit) = state.config.max_session_blocks.filter(|&limit| committed >= limit) {
+            println!("Session limit of {} blocks reached (--max-session-blocks); no more statements are accepted.", limit);
+            read_answer(&mut lines, "Press Enter to exit. ").await?;
+            return Ok(());
+        }
+        if state.spend_cap.as_ref().is_some_and(|cap| cap.halted()) {
+            println!("Cost cap reached; ending the session.");
+            return Ok(());
+        }
+
+        loop {
+            let Some(answer) = read_answer(&mut lines, "Continue or exit? ").await? else {
+                return Ok(());
+            };
+            match parse_menu_choice(&answer) {
+                MenuChoice::Continue => break,
+                MenuChoice::Exit => return Ok(()),
+                MenuChoice::Invalid => println!("Unrecognized '{}': {}", answer.trim(), MENU_HELP),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{confirms, parse_jsonl, parse_menu_choice, rounds_allowed, MenuChoice};
+
+    #[test]
+    fn jsonl_lines_carry_content_or_a_claim() {
+        assert_eq!(parse_jsonl(r#"{"content": "Water is wet"}"#).unwrap().content.as_deref(), Some("Water is wet"));
+        let claim = parse_jsonl(r#"{"claim": {"subject": "Paris", "predicate": "capital_of", "object": "France"}}"#);
+        assert_eq!(claim.unwrap().claim.unwrap().statement(), "Paris capital of France");
+        assert_eq!(parse_jsonl("{}").unwrap_err(), "send exactly one of content or claim");
+        assert!(parse_jsonl("Water is wet").unwrap_err().starts_with("not a JSON object"));
+    }
+
+    #[test]
+    fn menu_accepts_aliases_in_any_case() {
+        for answer in ["c", "Y", "yes", "CONT", " continue "] {
+            assert_eq!(parse_menu_choice(answer), MenuChoice::Continue, "{}", answer);
+        }
+        for answer in ["q", "Quit", "EXIT"] {
+            assert_eq!(parse_menu_choice(answer), MenuChoice::Exit, "{}", answer);
+        }
+        assert_eq!(parse_menu_choice("contnue"), MenuChoice::Invalid);
+        assert_eq!(parse_menu_choice(""), MenuChoice::Invalid);
+    }
+
+    #[test]
+    fn cost_confirmation_defaults_to_no() {
+        assert!(confirms("y") && confirms(" YES "));
+        assert!(!confirms("") && !confirms("n") && !confirms("sure"));
+    }
+
+    #[test]
+    fn session_limit_caps_the_last_submission() {
+        assert_eq!(rounds_allowed(3, None, 100), 3);
+        assert_eq!(rounds_allowed(3, Some(10), 2), 3);
+        assert_eq!(rounds_allowed(3, Some(10), 8), 2);
+        assert_eq!(rounds_allowed(1, Some(10), 10), 0);
+    }
+}
@@ -0,0 +1,55 @@
+use sha2::{Digest, Sha256};
+
+use crate::config::Lang;
+use crate::lang;
+
+const OPEN_TAG: &str = "<reference>";
+const CLOSE_TAG: &str = "</reference>";
+
+/// `--context FILE`: a reference document agents judge statements against instead
+/// of their own knowledge. Its hash goes into each record's provenance so the
+/// grounding can be reproduced.
+#[derive(Debug, Clone)]
+pub struct Context {
+    pub text: String,
+    /// SHA-256 of the file's contents, hex.
+    pub sha256: String,
+}
+
+impl Context {
+    /// Reads the document, as a clap value parser.
+    pub fn parse(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+        if text.trim().is_empty() {
+            return Err(format!("{} is empty", path));
+        }
+        let sha256 = format!("{:x}", Sha256::digest(text.as_bytes()));
+        Ok(Self { text, sha256 })
+    }
+
+    /// `prompt` preceded by the document, fenced in tags, and the instruction to
+    /// judge only against it.
+    pub fn ground(&self, lang: Lang, prompt: &str) -> String {
+        let grounding = lang::framing(lang).grounding;
+        format!("{}\n{}\n{}\n{}\n\n{}", grounding, OPEN_TAG, self.text.trim(), CLOSE_TAG, prompt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_and_fences_the_document() {
+        let path = std::env::temp_dir().join(format!("truth-context-{}.txt", std::process::id()));
+        std::fs::write(&path, "Paris is the capital of France.\n").unwrap();
+        let context = Context::parse(path.to_str().unwrap()).unwrap();
+        assert_eq!(context.sha256.len(), 64);
+        let prompt = context.ground(Lang::En, "Is the following statement valid? 'x'");
+        assert!(prompt.contains("<reference>\nParis is the capital of France.\n</reference>\n\nIs the"), "{}", prompt);
+
+        std::fs::write(&path, " \n").unwrap();
+        assert!(Context::parse(path.to_str().unwrap()).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,253 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::config::Config;
+use crate::{validation_prompt, AI_MODELS};
+
+/// USD per million (input, output) tokens, from each provider's public price list.
+const PRICES: [(&str, f64, f64); 9] = [
+    ("openai/gpt-4o", 2.50, 10.00),
+    ("openai/gpt-3.5-turbo", 0.50, 1.50),
+    ("anthropic/claude-3-haiku", 0.25, 1.25),
+    ("google/gemini-pro", 0.50, 1.50),
+    ("mistral/mistral-7b-instruct", 0.06, 0.06),
+    ("meta/llama-3-70b-instruct", 0.59, 0.79),
+    ("cohere/command-r-plus", 2.50, 10.00),
+    ("deepseek/deepseek-coder", 0.14, 0.28),
+    ("x/grok", 2.00, 10.00),
+];
+
+/// Tokens budgeted for each reply; agents answer with a verdict, a confidence and
+/// a one-sentence reason such as "yes 0.9 Water is wet by definition."
+pub const REPLY_TOKENS: u64 = 40;
+
+/// Per-million-token (input, output) price for a model, if it is in the table.
+pub fn price(model: &str) -> Option<(f64, f64)> {
+    PRICES
+        .iter()
+        .find(|(m, _, _)| *m == model)
+        .map(|(_, input, output)| (*input, *output))
+}
+
+/// Rough token count: about four characters per token for English text.
+pub fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(4)
+}
+
+/// Projected cost in USD of one call with the given token counts, or `None` for
+/// an unpriced model.
+pub fn call_cost(model: &str, input_tokens: u64, output_tokens: u64) -> Option<f64> {
+    let (input, output) = price(model)?;
+    Some((input_tokens as f64 * input + output_tokens as f64 * output) / 1_000_000.0)
+}
+
+/// Non-empty, non-comment lines of a questions file.
+pub fn read_questions(path: &Path) -> std::io::Result<Vec<String>> {
+    Ok(parse_questions(&fs::read_to_string(path)?))
+}
+
+/// The questions in a questions file's text, one per line, `#` comments skipped.
+pub fn parse_questions(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Agents queried per round.
+pub fn agents_per_round(config: &Config) -> usize {
+    config.agents.unwrap_or(AI_MODELS.len()).min(AI_MODELS.len())
+}
+
+/// Projected USD of one round in which every agent gets these token counts: the sum
+/// over the pool when every model votes, otherwise the weight-averaged per-model
+/// cost times the agent count. Unpriced models count as free.
+pub fn round_cost(input_tokens: u64, output_tokens: u64, config: &Config) -> f64 {
+    let mut total_usd = 0.0;
+    let mut weighted_usd = 0.0;
+    let mut total_weight = 0.0;
+    for slot in AI_MODELS.iter() {
+        let model = config.model_override.as_deref().unwrap_or(slot);
+        let usd = call_cost(model, input_tokens, output_tokens).unwrap_or(0.0);
+        let weight = config.sample_weight(model);
+        total_usd += usd;
+        weighted_usd += usd * weight;
+        total_weight += weight;
+    }
+    let per_round = agents_per_round(config);
+    if per_round == AI_MODELS.len() {
+        total_usd
+    } else {
+        weighted_usd / total_weight * per_round as f64
+    }
+}
+
+/// The first-round prompt for `statement`, including any `--context` document.
+fn first_prompt(statement: &str, config: &Config) -> String {
+    let lang = config.lang.unwrap_or_default();
+    let prompt = validation_prompt(lang, statement, None);
+    match &config.context {
+        Some(context) => context.ground(lang, &prompt),
+        None => prompt,
+    }
+}
+
+/// Projected USD of one repeat on a statement: `config.rounds` debate rounds of
+/// `--self-consistency` samples each (later rounds' longer prompts not counted).
+pub fn repeat_cost(statement: &str, config: &Config) -> f64 {
+    let input_tokens = estimate_tokens(&first_prompt(statement, config)) * config.samples();
+    round_cost(input_tokens, REPLY_TOKENS * config.samples(), config) * config.rounds as f64
+}
+
+/// Projected USD of validating one statement, across `config.repeat` repeats.
+pub fn statement_cost(statement: &str, config: &Config) -> f64 {
+    repeat_cost(statement, config) * config.repeat as f64
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("cost cap of ${cap:.4} reached (${spent:.4} spent, next ~${next:.4}); no further statements are accepted")]
+pub struct CapReached {
+    pub cap: f64,
+    pub spent: f64,
+    pub next: f64,
+}
+
+#[derive(Debug, Default)]
+struct Spend {
+    spent: f64,
+    halted: bool,
+}
+
+/// `--max-cost-usd`: the session's projected spend, which may not pass the cap.
+/// Once a round is refused the session stays halted, even for cheaper statements.
+#[derive(Debug)]
+pub struct SpendCap {
+    cap: f64,
+    spend: Mutex<Spend>,
+}
+
+impl SpendCap {
+    pub fn new(cap: f64) -> Self {
+        Self {
+            cap,
+            spend: Mutex::new(Spend::default()),
+        }
+    }
+
+    /// Counts `usd` towards the session unless that would pass the cap.
+    pub fn reserve(&self, usd: f64) -> Result<(), CapReached> {
+        let mut spend = self.spend.lock().unwrap();
+        if spend.halted || spend.spent + usd > self.cap {
+            spend.halted = true;
+            return Err(CapReached {
+                cap: self.cap,
+                spent: spend.spent,
+                next: usd,
+            });
+        }
+        spend.spent += usd;
+        Ok(())
+    }
+
+    pub fn halted(&self) -> bool {
+        self.spend.lock().unwrap().halted
+    }
+}
+
+/// Prints projected token usage and cost of validating every question in `path`,
+/// without calling any provider.
+pub fn print_estimate(path: &Path, config: &Config) -> std::io::Result<()> {
+    let questions = read_questions(path)?;
+    let input_tokens: u64 = questions.iter().map(|q| estimate_tokens(&first_prompt(q, config))).sum::<u64>() * config.samples();
+    let output_tokens = REPLY_TOKENS * questions.len() as u64 * config.samples();
+    let per_round = agents_per_round(config);
+
+    println!("{} questions, {} agents per round", questions.len(), per_round);
+    println!("{:<30} {:>12} {:>12}", "model", "tokens", "usd");
+    let mut unpriced = Vec::new();
+    for slot in AI_MODELS.iter() {
+        let model = config.model_override.as_deref().unwrap_or(slot);
+        let tokens = input_tokens + output_tokens;
+        match call_cost(model, input_tokens, output_tokens) {
+            Some(usd) => println!("{:<30} {:>12} {:>12.6}", model, tokens, usd),
+            None => {
+                println!("{:<30} {:>12} {:>12}", model, tokens, "unpriced");
+                if !unpriced.contains(&model) {
+                    unpriced.push(model);
+                }
+            }
+        }
+    }
+
+    let total_usd = round_cost(input_tokens, output_tokens, config);
+    if per_round == AI_MODELS.len() {
+        println!("projected total: ${:.6}", total_usd);
+    } else {
+        println!(
+            "projected total: ~${:.6} (sampling {} of {} models)",
+            total_usd,
+            per_round,
+            AI_MODELS.len()
+        );
+    }
+    println!(
+        "projected tokens: {}",
+        (input_tokens + output_tokens) * per_round as u64
+    );
+    if !unpriced.is_empty() {
+        eprintln!(
+            "warning: no price known for {}; the total leaves them out",
+            unpriced.join(", ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn questions_skip_blanks_and_comments() {
+        let text = "# claims\r\nWater is wet\n\n   \n  The moon is cheese  \n#done";
+        assert_eq!(parse_questions(text), ["Water is wet", "The moon is cheese"]);
+    }
+
+    #[test]
+    fn unknown_models_have_no_price() {
+        assert_eq!(call_cost("openai/gpt-4o", 1_000_000, 0), Some(2.50));
+        assert_eq!(call_cost("acme/unknown", 1_000_000, 0), None);
+    }
+
+    #[test]
+    fn sampled_rounds_scale_the_weighted_mean() {
+        use clap::Parser;
+        let every = Config::parse_from(["truth"]);
+        let full = round_cost(1_000_000, 0, &every);
+        assert!((full - PRICES.iter().map(|p| p.1).sum::<f64>()).abs() < 1e-9);
+        let three = Config::parse_from(["truth", "--agents", "3"]);
+        assert!((round_cost(1_000_000, 0, &three) - full / 3.0).abs() < 1e-9);
+        let repeated = Config::parse_from(["truth", "--repeat", "2"]);
+        assert!((statement_cost("x", &repeated) - 2.0 * statement_cost("x", &every)).abs() < 1e-12);
+        let sampled = Config::parse_from(["truth", "--self-consistency", "3"]);
+        assert!((statement_cost("x", &sampled) - 3.0 * statement_cost("x", &every)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn spend_cap_halts_once_the_next_round_would_pass_it() {
+        let cap = SpendCap::new(1.0);
+        assert!(cap.reserve(0.6).is_ok());
+        let refused = cap.reserve(0.5).unwrap_err();
+        assert_eq!((refused.spent, refused.next), (0.6, 0.5));
+        assert!(cap.halted());
+        assert!(cap.reserve(0.1).is_err());
+    }
+
+    #[test]
+    fn tokens_round_up() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+}
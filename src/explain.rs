@@ -0,0 +1,104 @@
+use reqwest::Client;
+use serde_json::json;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{complete, AIResponse, ProviderError, ProviderSettings};
+
+/// `--explain-consensus`: asks a summarizer model for a one-paragraph rationale of
+/// the majority verdict after voting, stored on the record as `consensus_rationale`.
+pub struct Explainer {
+    client: Client,
+    model: String,
+    settings: Arc<ProviderSettings>,
+}
+
+impl Explainer {
+    pub fn new(model: &str, settings: Arc<ProviderSettings>) -> Self {
+        Self {
+            client: crate::client_builder(settings.proxy.as_ref())
+                .timeout(Duration::from_secs(30))
+                .build()
+                .expect("static client configuration"),
+            model: model.to_string(),
+            settings,
+        }
+    }
+
+    /// The rationale for `verdict` on `statement`, through OpenRouter.
+    pub async fn explain(&self, statement: &str, verdict: bool, votes: &[AIResponse]) -> Result<String, ProviderError> {
+        let api_key = env::var("OPENROUTER_API_KEY").map_err(|_| ProviderError::MissingKey("OPENROUTER_API_KEY"))?;
+        let body = json!({
+            "model": self.model,
+            "messages": [{ "role": "user", "content": explanation_prompt(statement, verdict, votes) }],
+        });
+        let request = self
+            .client
+            .post("https://openrouter.ai/api/v1/chat/completions")
+            .header("Authorization", format!("Bearer {}", api_key));
+        let request = self.settings.with_extra_headers("openrouter", request);
+        let completion = complete(request, body, &self.model, &self.settings).await?;
+        Ok(crate::input::sanitize_statement(&completion.text))
+    }
+}
+
+/// Agents give a verdict, a confidence and at most a sentence of reasoning, so the
+/// summarizer is given the majority's votes and asked to draw them into one account.
+fn explanation_prompt(statement: &str, verdict: bool, votes: &[AIResponse]) -> String {
+    let majority: Vec<&AIResponse> = votes.iter().filter(|v| v.is_valid == verdict).collect();
+    let mut prompt = format!(
+        "A panel of {} AI models judged whether this statement is valid: '{}'\n\
+         {} of them answered '{}':",
+        votes.len(),
+        statement,
+        majority.len(),
+        if verdict { "yes" } else { "no" }
+    );
+    for vote in &majority {
+        let confidence = vote.confidence.map(|c| format!(" (confidence {:.2})", c)).unwrap_or_default();
+        prompt.push_str(&format!("\n- {}{}", vote.model, confidence));
+        if let Some(rationale) = &vote.rationale {
+            prompt.push_str(&format!(": {}", rationale));
+        }
+    }
+    prompt.push_str(&format!(
+        "\n\nIn one short paragraph, explain the reasoning that most likely leads to judging the \
+         statement {}. Answer with the paragraph only.",
+        if verdict { "valid" } else { "not valid" }
+    ));
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vote(model: &str, is_valid: bool) -> AIResponse {
+        AIResponse {
+            agent_name: model.to_string(),
+            model: model.to_string(),
+            is_valid,
+            confidence: Some(0.9),
+            attempts: 1,
+            lang: None,
+            system_fingerprint: None,
+            fallback_from: None,
+            self_consistency: None,
+            rationale: None,
+        }
+    }
+
+    #[test]
+    fn the_prompt_lists_only_the_majority() {
+        let mut votes = [vote("openai/gpt-4o", true), vote("x/grok", false), vote("cohere/command-r-plus", true)];
+        votes[2].rationale = Some("Boiling point depends on pressure.".to_string());
+        let prompt = explanation_prompt("Water boils at 100C at sea level", true, &votes);
+        assert!(prompt.contains("A panel of 3 AI models"), "{}", prompt);
+        assert!(prompt.contains("2 of them answered 'yes'"), "{}", prompt);
+        assert!(prompt.contains("- openai/gpt-4o (confidence 0.90)"), "{}", prompt);
+        assert!(prompt.contains("- cohere/command-r-plus (confidence 0.90): Boiling point depends on pressure."), "{}", prompt);
+        assert!(!prompt.contains("x/grok"), "{}", prompt);
+        assert!(prompt.ends_with("Answer with the paragraph only."));
+    }
+}
@@ -0,0 +1,181 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+use crate::{AIProvider, AIResponse, ProviderError};
+
+/// Whether a `FixtureProvider` saves live responses or serves saved ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureMode {
+    /// `--record`: query the wrapped provider and save each response.
+    Record,
+    /// `--replay`: answer from saved responses; the wrapped provider is never called.
+    Replay,
+}
+
+/// One saved response. The statement and debate context are kept for whoever
+/// reads the file; only the key in the file name is used for lookups.
+#[derive(Debug, Serialize, Deserialize)]
+struct Fixture {
+    model: String,
+    statement: String,
+    #[serde(default)]
+    debate: Option<String>,
+    is_valid: bool,
+    confidence: Option<f32>,
+    attempts: u32,
+    #[serde(default)]
+    lang: Option<String>,
+    #[serde(default)]
+    system_fingerprint: Option<String>,
+    #[serde(default)]
+    rationale: Option<String>,
+}
+
+/// `{model}-{hash}.json`, where the hash covers the statement and any debate context
+/// so each debate round gets its own fixture.
+pub(crate) fn fixture_path(dir: &Path, model: &str, text: &str, debate: Option<&str>) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(text.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(debate.unwrap_or_default().as_bytes());
+    let hash = format!("{:x}", hasher.finalize());
+    let model: String = model
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect();
+    dir.join(format!("{}-{}.json", model, &hash[..16]))
+}
+
+/// Wraps a provider to record its responses to, or replay them from, a directory
+/// keyed by (model, statement hash), for deterministic runs without the network.
+pub struct FixtureProvider {
+    inner: Box<dyn AIProvider>,
+    dir: PathBuf,
+    mode: FixtureMode,
+}
+
+impl FixtureProvider {
+    pub fn new(inner: Box<dyn AIProvider>, dir: &Path, mode: FixtureMode) -> Self {
+        Self {
+            inner,
+            dir: dir.to_path_buf(),
+            mode,
+        }
+    }
+}
+
+#[async_trait]
+impl AIProvider for FixtureProvider {
+    fn agent_name(&self) -> &str {
+        self.inner.agent_name()
+    }
+
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+
+    async fn validate(&self, text: &str, debate: Option<&str>) -> Result<AIResponse, ProviderError> {
+        let path = fixture_path(&self.dir, self.model(), text, debate);
+        match self.mode {
+            FixtureMode::Replay => {
+                let bytes = tokio::fs::read(&path)
+                    .await
+                    .map_err(|_| ProviderError::MissingFixture(path.display().to_string()))?;
+                let fixture: Fixture = serde_json::from_slice(&bytes)
+                    .map_err(|e| ProviderError::Malformed(format!("{}: {}", path.display(), e)))?;
+                Ok(AIResponse {
+                    agent_name: self.agent_name().to_string(),
+                    model: self.model().to_string(),
+                    is_valid: fixture.is_valid,
+                    confidence: fixture.confidence,
+                    attempts: fixture.attempts,
+                    lang: fixture.lang,
+                    system_fingerprint: fixture.system_fingerprint,
+                    fallback_from: None,
+                    self_consistency: None,
+                    rationale: fixture.rationale,
+                })
+            }
+            FixtureMode::Record => {
+                let response = self.inner.validate(text, debate).await?;
+                let fixture = Fixture {
+                    model: response.model.clone(),
+                    statement: text.to_string(),
+                    debate: debate.map(str::to_string),
+                    is_valid: response.is_valid,
+                    confidence: response.confidence,
+                    attempts: response.attempts,
+                    lang: response.lang.clone(),
+                    system_fingerprint: response.system_fingerprint.clone(),
+                    rationale: response.rationale.clone(),
+                };
+                let json = serde_json::to_vec_pretty(&fixture).expect("fixtures serialize");
+                // A fixture that can't be saved doesn't cost the live vote.
+                if let Err(e) = tokio::fs::write(&path, json).await {
+                    tracing::warn!("could not record fixture {}: {}", path.display(), e);
+                }
+                Ok(response)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(bool);
+
+    #[async_trait]
+    impl AIProvider for Fixed {
+        fn agent_name(&self) -> &str {
+            "agent"
+        }
+
+        fn model(&self) -> &str {
+            "openai/gpt-4o"
+        }
+
+        async fn validate(&self, _text: &str, _debate: Option<&str>) -> Result<AIResponse, ProviderError> {
+            Ok(AIResponse {
+                agent_name: "agent".to_string(),
+                model: "openai/gpt-4o".to_string(),
+                is_valid: self.0,
+                confidence: Some(0.8),
+                attempts: 2,
+                lang: None,
+                system_fingerprint: None,
+                fallback_from: None,
+                self_consistency: None,
+                rationale: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_what_was_recorded() {
+        let dir = std::env::temp_dir().join(format!("truth-fixtures-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let recorder = FixtureProvider::new(Box::new(Fixed(true)), &dir, FixtureMode::Record);
+        recorder.validate("The sky is blue", None).await.unwrap();
+
+        // The wrapped provider would now say "no"; replay must not ask it.
+        let replayer = FixtureProvider::new(Box::new(Fixed(false)), &dir, FixtureMode::Replay);
+        let vote = replayer.validate("The sky is blue", None).await.unwrap();
+        assert_eq!((vote.is_valid, vote.confidence, vote.attempts), (true, Some(0.8), 2));
+        assert!(matches!(
+            replayer.validate("The sky is blue", Some("round 1")).await,
+            Err(ProviderError::MissingFixture(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn fixture_names_are_filesystem_safe() {
+        let path = fixture_path(Path::new("f"), "openai/gpt-4o", "x", None);
+        let name = path.file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with("openai_gpt-4o-") && name.ends_with(".json"), "{}", name);
+    }
+}
@@ -0,0 +1,77 @@
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::Record;
+
+/// `--on-commit`: a shell command run after each record is committed, with the
+/// record's JSON on stdin.
+pub struct CommitHook {
+    command: String,
+    timeout: Duration,
+}
+
+impl CommitHook {
+    pub fn new(command: &str, timeout: Duration) -> Self {
+        Self {
+            command: command.to_string(),
+            timeout,
+        }
+    }
+
+    /// Fire-and-forget: the command runs on its own task, is killed after the
+    /// timeout, and failures are only logged.
+    pub fn notify(&self, record: &Record) {
+        let input = match serde_json::to_vec(record) {
+            Ok(input) => input,
+            Err(e) => {
+                tracing::warn!("on-commit hook skipped: {}", e);
+                return;
+            }
+        };
+        let command = self.command.clone();
+        let timeout = self.timeout;
+        let id = record.id.clone();
+        tokio::spawn(async move {
+            match tokio::time::timeout(timeout, run(&command, &input)).await {
+                Ok(Ok(status)) if !status.success() => {
+                    tracing::warn!("on-commit hook for {} exited with {}", id, status)
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => tracing::warn!("on-commit hook for {} failed: {}", id, e),
+                Err(_) => tracing::warn!("on-commit hook for {} timed out after {:?}", id, timeout),
+            }
+        });
+    }
+}
+
+async fn run(command: &str, input: &[u8]) -> std::io::Result<std::process::ExitStatus> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A command that never reads stdin closes the pipe early; that's not a failure.
+        if let Err(e) = stdin.write_all(input).await {
+            if e.kind() != std::io::ErrorKind::BrokenPipe {
+                return Err(e);
+            }
+        }
+    }
+    child.wait().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn passes_the_input_on_stdin_and_reports_the_exit_status() {
+        assert!(run("grep -q '\"id\"'", b"{\"id\":\"a\"}").await.unwrap().success());
+        assert!(!run("grep -q missing", b"{\"id\":\"a\"}").await.unwrap().success());
+        assert!(run("true", b"ignored").await.unwrap().success());
+    }
+}
@@ -0,0 +1,145 @@
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+/// Strips ANSI escape sequences and control characters from a submitted statement,
+/// or from model text before it is stored.
+///
+/// Both are echoed into rendered ledgers and terminals, so an embedded escape could
+/// repaint the display (e.g. forge a green "yes"). Line breaks and tabs become
+/// spaces; every other control character and escape sequence is dropped.
+pub fn sanitize_statement(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1B' => match chars.next() {
+                // CSI: parameters and intermediates up to a final byte in '@'..='~'.
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC: terminated by BEL or ST (ESC \).
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1B' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                // Two-character escapes (ESC c, ESC 7, ...).
+                _ => {}
+            },
+            '\n' | '\r' | '\t' => out.push(' '),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out.trim().to_string()
+}
+
+/// Longest console line accepted, in bytes. Anything past it is read and discarded
+/// rather than buffered, so a huge paste can't balloon memory.
+pub const MAX_LINE_BYTES: usize = 8 * 1024;
+
+/// One line read from the console.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Line {
+    Text(String),
+    /// Longer than `MAX_LINE_BYTES`; the whole line was skipped.
+    TooLong,
+    /// Not UTF-8, or contains NUL bytes.
+    NotText,
+}
+
+/// Reads console lines defensively: capped in length and rejecting binary input,
+/// where `AsyncBufReadExt::lines` would buffer without limit and fail on bad UTF-8.
+pub struct LineReader<R> {
+    reader: R,
+}
+
+impl<R: AsyncBufRead + Unpin> LineReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+
+    /// The next line without its terminator, or `None` at end of input.
+    pub async fn next_line(&mut self) -> std::io::Result<Option<Line>> {
+        let mut line = Vec::new();
+        let mut len = 0;
+        let mut saw_any = false;
+        loop {
+            let buf = self.reader.fill_buf().await?;
+            if buf.is_empty() {
+                if !saw_any {
+                    return Ok(None);
+                }
+                break;
+            }
+            saw_any = true;
+            let (chunk, done) = match buf.iter().position(|&b| b == b'\n') {
+                Some(end) => (&buf[..end], true),
+                None => (buf, false),
+            };
+            if len + chunk.len() <= MAX_LINE_BYTES {
+                line.extend_from_slice(chunk);
+            }
+            len += chunk.len();
+            let consumed = chunk.len() + usize::from(done);
+            self.reader.consume(consumed);
+            if done {
+                break;
+            }
+        }
+        if len > MAX_LINE_BYTES {
+            return Ok(Some(Line::TooLong));
+        }
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        Ok(Some(match String::from_utf8(line) {
+            Ok(text) if !text.contains('\0') => Line::Text(text),
+            _ => Line::NotText,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_escape_sequences() {
+        assert_eq!(sanitize_statement("\x1B[32myes\x1B[0m"), "yes");
+        assert_eq!(sanitize_statement("a\x1B]0;title\x07b"), "ab");
+        assert_eq!(sanitize_statement("a\x1B]8;;http://x\x1B\\b"), "ab");
+        assert_eq!(sanitize_statement("a\x1Bcb"), "ab");
+    }
+
+    #[test]
+    fn flattens_whitespace_and_drops_controls() {
+        assert_eq!(sanitize_statement("  one\ntwo\tthree\r "), "one two three");
+        assert_eq!(sanitize_statement("bell\x07\x00"), "bell");
+        assert_eq!(sanitize_statement("\x1B[2J"), "");
+    }
+
+    #[tokio::test]
+    async fn line_reader_caps_length_and_rejects_binary() {
+        let mut bytes = b"ok\r\n".to_vec();
+        bytes.extend(vec![b'x'; MAX_LINE_BYTES + 1]);
+        bytes.extend(b"\nbad\xff\nnul\0\nlast");
+        // A small buffer makes lines span several reads.
+        let mut reader = LineReader::new(tokio::io::BufReader::with_capacity(16, &bytes[..]));
+        assert_eq!(reader.next_line().await.unwrap(), Some(Line::Text("ok".to_string())));
+        assert_eq!(reader.next_line().await.unwrap(), Some(Line::TooLong));
+        assert_eq!(reader.next_line().await.unwrap(), Some(Line::NotText));
+        assert_eq!(reader.next_line().await.unwrap(), Some(Line::NotText));
+        assert_eq!(reader.next_line().await.unwrap(), Some(Line::Text("last".to_string())));
+        assert_eq!(reader.next_line().await.unwrap(), None);
+    }
+}
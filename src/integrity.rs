@@ -0,0 +1,96 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::{ledger, AppState, LEDGER};
+
+/// Outcome counters of `--verify-interval-mins` checks, reported by `/api/status`.
+#[derive(Debug, Default)]
+pub struct IntegrityChecks {
+    runs: AtomicU64,
+    failures: AtomicU64,
+    broken: AtomicBool,
+    last_failure: Mutex<Option<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IntegritySnapshot {
+    pub checks: u64,
+    pub failures: u64,
+    /// Whether the latest check failed.
+    pub broken: bool,
+    pub last_failure: Option<String>,
+}
+
+impl IntegrityChecks {
+    /// Counts one check; returns true when it is the first failure after a passing
+    /// check (or startup), so an alert fires once per breakage rather than every interval.
+    fn observe(&self, result: &Result<(), ledger::ChainError>) -> bool {
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        match result {
+            Ok(()) => {
+                self.broken.store(false, Ordering::Relaxed);
+                false
+            }
+            Err(e) => {
+                self.failures.fetch_add(1, Ordering::Relaxed);
+                *self.last_failure.lock().unwrap() = Some(e.to_string());
+                !self.broken.swap(true, Ordering::Relaxed)
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> IntegritySnapshot {
+        IntegritySnapshot {
+            checks: self.runs.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            broken: self.broken.load(Ordering::Relaxed),
+            last_failure: self.last_failure.lock().unwrap().clone(),
+        }
+    }
+}
+
+/// Re-verifies the whole chain every `interval` under the ledger's read lock, so
+/// commits are only held up while a check is walking the records.
+pub async fn run(state: Arc<AppState>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    // The chain was verified at startup; the first tick fires immediately.
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        let result = {
+            let ledger = LEDGER.read().await;
+            ledger::verify_chain(ledger.records())
+        };
+        let first_failure = state.integrity.observe(&result);
+        if let Err(e) = &result {
+            tracing::error!("LEDGER INTEGRITY CHECK FAILED: {}", e);
+            if first_failure && state.config.alert_on_corruption {
+                if let Some(alert) = &state.alert {
+                    alert.notify_corruption(&e.to_string());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::ChainError;
+
+    #[test]
+    fn alerts_once_per_breakage() {
+        let checks = IntegrityChecks::default();
+        let broken = || Err(ChainError::HashMismatch { index: 2, id: "r".to_string() });
+        assert!(!checks.observe(&Ok(())));
+        assert!(checks.observe(&broken()));
+        assert!(!checks.observe(&broken()));
+        assert!(!checks.observe(&Ok(())));
+        assert!(checks.observe(&broken()));
+        let snapshot = checks.snapshot();
+        assert_eq!((snapshot.checks, snapshot.failures, snapshot.broken), (5, 3, true));
+        assert!(snapshot.last_failure.unwrap().contains("record 2"));
+    }
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::ledger::RecordFilter;
+use crate::{run_round, status_report, verify_report, AppState, LedgerQuery, ValidateRequest, LEDGER};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// A round that recorded nothing; `data.status` carries the REST API's HTTP status.
+const ROUND_FAILED: i64 = -32000;
+/// `getBlock` for an index past the ledger.
+const NOT_FOUND: i64 = -32001;
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct RpcError {
+    code: i64,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Request {
+    jsonrpc: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    /// Absent for notifications, which get no response.
+    id: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockParams {
+    /// 1-based, as in `/api/ledger/{n}` routes.
+    index: usize,
+}
+
+/// `--jsonrpc`: answers a JSON-RPC 2.0 request body, single or batched, with the
+/// REST API's own functions behind each method. `None` when every call was a
+/// notification, so there is nothing to send back.
+pub async fn handle(state: &AppState, body: &[u8]) -> Option<Value> {
+    let body: Value = match serde_json::from_slice(body) {
+        Ok(body) => body,
+        Err(e) => return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, format!("parse error: {}", e)))),
+    };
+    match body {
+        Value::Array(calls) if calls.is_empty() => {
+            Some(error_response(Value::Null, RpcError::new(INVALID_REQUEST, "empty batch")))
+        }
+        Value::Array(calls) => {
+            let mut responses = Vec::new();
+            for call in calls {
+                responses.extend(handle_one(state, call).await);
+            }
+            (!responses.is_empty()).then_some(Value::Array(responses))
+        }
+        call => handle_one(state, call).await,
+    }
+}
+
+async fn handle_one(state: &AppState, value: Value) -> Option<Value> {
+    let request: Request = match serde_json::from_value(value) {
+        Ok(request) => request,
+        Err(e) => return Some(error_response(Value::Null, RpcError::new(INVALID_REQUEST, e.to_string()))),
+    };
+    let id = request.id.clone();
+    let result = match request.jsonrpc.as_str() {
+        "2.0" => call(state, &request.method, request.params).await,
+        other => Err(RpcError::new(INVALID_REQUEST, format!("unsupported jsonrpc version '{}'", other))),
+    };
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+        Err(error) => error_response(id, error),
+    })
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "error": error, "id": id })
+}
+
+/// Named params (an object) or none; positional params aren't accepted.
+fn params<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = match params {
+        Value::Null => json!({}),
+        Value::Array(_) => return Err(RpcError::new(INVALID_PARAMS, "params must be an object")),
+        params => params,
+    };
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn to_value(value: impl Serialize) -> Value {
+    serde_json::to_value(value).expect("API payloads serialize")
+}
+
+async fn call(state: &AppState, method: &str, raw: Value) -> Result<Value, RpcError> {
+    match method {
+        "submit" => {
+            let req: ValidateRequest = params(raw)?;
+            let (statement, claim) = req.parts().map_err(|e| RpcError::new(INVALID_PARAMS, e))?;
+            match run_round(state, statement, claim, None).await {
+                Ok(response) => Ok(to_value(response)),
+                Err(e) => Err(RpcError {
+                    code: ROUND_FAILED,
+                    message: e.message,
+                    data: Some(json!({ "status": e.status.as_u16() })),
+                }),
+            }
+        }
+        "getLedger" => {
+            let query: LedgerQuery = params(raw)?;
+            let ledger = LEDGER.read().await;
+            Ok(to_value(ledger.query(&RecordFilter { since: query.since })))
+        }
+        "getBlock" => {
+            let BlockParams { index } = params(raw)?;
+            let ledger = LEDGER.read().await;
+            match index.checked_sub(1).and_then(|i| ledger.records().get(i)) {
+                Some(record) => Ok(to_value(record)),
+                None => Err(RpcError::new(NOT_FOUND, format!("no record at index {}", index))),
+            }
+        }
+        "verifyChain" => Ok(to_value(verify_report(LEDGER.read().await.records()))),
+        "getStatus" => Ok(to_value(status_report(state).await)),
+        other => Err(RpcError::new(METHOD_NOT_FOUND, format!("unknown method '{}'", other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_must_be_named() {
+        let block: BlockParams = params(json!({ "index": 3 })).unwrap();
+        assert_eq!(block.index, 3);
+        assert_eq!(params::<BlockParams>(json!([3])).unwrap_err().code, INVALID_PARAMS);
+        assert_eq!(params::<BlockParams>(Value::Null).unwrap_err().code, INVALID_PARAMS);
+        let query: LedgerQuery = params(Value::Null).unwrap();
+        assert!(query.since.is_none());
+        let error = error_response(json!(7), RpcError::new(METHOD_NOT_FOUND, "unknown method 'x'"));
+        assert_eq!(
+            error,
+            json!({ "jsonrpc": "2.0", "error": { "code": -32601, "message": "unknown method 'x'" }, "id": 7 })
+        );
+    }
+}
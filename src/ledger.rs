@@ -0,0 +1,738 @@
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+
+use crate::Record;
+
+/// `prev_hash` of the first record in a chain.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ChainError {
+    #[error("record {index} ({id}) does not link to the hash of its predecessor")]
+    BrokenLink { index: usize, id: String },
+    #[error("record {index} ({id}) does not match its stored hash")]
+    HashMismatch { index: usize, id: String },
+    #[error("imported record {id} reuses an existing id with different content")]
+    IdCollision { id: String },
+}
+
+/// SHA-256 over the fields a record commits to: its id, transaction, consensus,
+/// truth score, details text, votes (with any rationales), abstentions, timestamp,
+/// Solana slot, the hash of the record before it, and its provenance, earlier debate
+/// rounds, rationale, claim and majority/dissenting agents when present.
+pub fn hash_record(record: &Record) -> String {
+    hash_fields(record, Some(&record.prev_hash))
+}
+
+/// Like `hash_record` but without the chain link, so the same record has the same
+/// content hash in any ledger it is imported into.
+pub fn content_hash(record: &Record) -> String {
+    hash_fields(record, None)
+}
+
+fn hash_fields(record: &Record, prev_hash: Option<&str>) -> String {
+    let votes: Vec<String> = record
+        .votes
+        .iter()
+        .map(|v| {
+            let vote = format!("{}:{}={}:{:?}", v.agent_name, v.model, v.is_valid, v.confidence);
+            // Single attempts stay implicit, so records from before attempts were
+            // counted keep their hashes.
+            let vote = match v.attempts {
+                1 => vote,
+                n => format!("{}#{}", vote, n),
+            };
+            // Likewise only non-English prompts, returned fingerprints, fallbacks,
+            // self-consistency samples and given rationales are marked.
+            let vote = match &v.lang {
+                Some(lang) => format!("{}@{}", vote, lang),
+                None => vote,
+            };
+            let vote = match &v.system_fingerprint {
+                Some(fingerprint) => format!("{}~{}", vote, fingerprint),
+                None => vote,
+            };
+            let vote = match &v.fallback_from {
+                Some(missed) => format!("{}<{}", vote, missed),
+                None => vote,
+            };
+            let vote = match &v.self_consistency {
+                Some(s) => format!("{}*{}/{}/{}", vote, s.samples, s.answered, s.agreeing),
+                None => vote,
+            };
+            match &v.rationale {
+                Some(rationale) => format!("{}|{:?}", vote, rationale),
+                None => vote,
+            }
+        })
+        .collect();
+    let abstentions: Vec<String> = record
+        .abstentions
+        .iter()
+        .map(|a| {
+            let abstention = format!("{}:{}={}", a.agent_name, a.model, a.reason);
+            // A refusal can decide the tally under `--error-policy no`; marked only
+            // when set, like the vote markers above.
+            match a.refusal {
+                true => format!("{}!refused", abstention),
+                false => abstention,
+            }
+        })
+        .collect();
+    let mut hasher = Sha256::new();
+    for field in [
+        record.id.as_str(),
+        record.transaction.id.as_str(),
+        record.transaction.content.as_str(),
+        if record.consensus { "true" } else { "false" },
+        &format!("{:.2}", record.truth_score),
+        record.details.as_str(),
+        &votes.join(","),
+        &abstentions.join(","),
+        &record.timestamp.to_rfc3339(),
+        &record.solana_slot.map(|slot| slot.to_string()).unwrap_or_default(),
+        prev_hash.unwrap_or_default(),
+    ] {
+        hasher.update(field.as_bytes());
+        hasher.update([0u8]);
+    }
+    // Optional fields are hashed only when present, so older records keep their hashes.
+    if let Some(provenance) = &record.provenance {
+        hasher.update(serde_json::to_vec(provenance).expect("provenance serializes"));
+        hasher.update([0u8]);
+    }
+    for round in &record.debate_rounds {
+        let votes: Vec<String> = round
+            .iter()
+            .map(|v| format!("{}:{}={}:{:?}", v.agent_name, v.model, v.is_valid, v.confidence))
+            .collect();
+        hasher.update(votes.join(",").as_bytes());
+        hasher.update([0u8]);
+    }
+    if let Some(rationale) = &record.consensus_rationale {
+        hasher.update(rationale.as_bytes());
+        hasher.update([0u8]);
+    }
+    if let Some(challenge) = &record.devils_advocate {
+        hasher.update(serde_json::to_vec(challenge).expect("challenges serialize"));
+        hasher.update([0u8]);
+    }
+    if let Some(claim) = &record.transaction.claim {
+        hasher.update(serde_json::to_vec(claim).expect("claims serialize"));
+        hasher.update([0u8]);
+    }
+    if !record.majority_agents.is_empty() || !record.dissenting_agents.is_empty() {
+        for side in [&record.majority_agents, &record.dissenting_agents] {
+            hasher.update(side.join(",").as_bytes());
+            hasher.update([0u8]);
+        }
+    }
+    if !record.vetoed_by.is_empty() {
+        hasher.update(record.vetoed_by.join(",").as_bytes());
+        hasher.update([0u8]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Walks the chain and reports the first record whose link or hash is broken.
+pub fn verify_chain(records: &[Record]) -> Result<(), ChainError> {
+    let mut prev_hash = GENESIS_HASH;
+    for (index, record) in records.iter().enumerate() {
+        if record.prev_hash != prev_hash {
+            return Err(ChainError::BrokenLink { index, id: record.id.clone() });
+        }
+        if record.hash != hash_record(record) {
+            return Err(ChainError::HashMismatch { index, id: record.id.clone() });
+        }
+        prev_hash = &record.hash;
+    }
+    Ok(())
+}
+
+/// A record committed earlier than the record before it, from clock skew or an import
+/// of older records. The hashes still verify, so this is reported, not refused.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct TimestampAnomaly {
+    pub index: usize,
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub previous: DateTime<Utc>,
+}
+
+impl std::fmt::Display for TimestampAnomaly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "record {} ({}) is timestamped {} but its predecessor {}",
+            self.index,
+            self.id,
+            self.timestamp.to_rfc3339(),
+            self.previous.to_rfc3339()
+        )
+    }
+}
+
+/// Every record whose timestamp precedes its predecessor's.
+pub fn timestamp_anomalies(records: &[Record]) -> Vec<TimestampAnomaly> {
+    records
+        .windows(2)
+        .enumerate()
+        .filter(|(_, pair)| pair[1].timestamp < pair[0].timestamp)
+        .map(|(i, pair)| TimestampAnomaly {
+            index: i + 1,
+            id: pair[1].id.clone(),
+            timestamp: pair[1].timestamp,
+            previous: pair[0].timestamp,
+        })
+        .collect()
+}
+
+/// Merkle root over the given record hashes.
+///
+/// Parents are the SHA-256 of the two child hex strings concatenated; an odd node
+/// at any level is paired with itself. An empty ledger has `GENESIS_HASH` as its root.
+pub fn merkle_root(hashes: &[String]) -> String {
+    if hashes.is_empty() {
+        return GENESIS_HASH.to_string();
+    }
+    let mut level = hashes.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+            .collect();
+    }
+    level.remove(0)
+}
+
+fn hash_pair(left: &str, right: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// One level of a Merkle proof: the sibling's hash, and whether it sits to the
+/// right of the running hash (the position bit).
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ProofStep {
+    pub sibling: String,
+    pub right: bool,
+}
+
+/// Proves the record at 1-based `index` is in the ledger whose Merkle root is `root`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct MerkleProof {
+    pub index: usize,
+    /// The record's hash.
+    pub leaf: String,
+    pub root: String,
+    /// Siblings from the leaf's level up to the root's children.
+    pub steps: Vec<ProofStep>,
+}
+
+impl MerkleProof {
+    /// Whether folding `leaf` with the steps arrives at `root`. Needs only the proof,
+    /// not the ledger.
+    pub fn verifies(&self) -> bool {
+        let computed = self.steps.iter().fold(self.leaf.clone(), |hash, step| {
+            if step.right {
+                hash_pair(&hash, &step.sibling)
+            } else {
+                hash_pair(&step.sibling, &hash)
+            }
+        });
+        computed == self.root
+    }
+}
+
+/// The Merkle proof for the hash at 0-based `position`, built the way
+/// `merkle_root` pairs nodes; `None` if there is no such hash.
+pub fn merkle_proof(hashes: &[String], position: usize) -> Option<MerkleProof> {
+    let leaf = hashes.get(position)?.clone();
+    let mut steps = Vec::new();
+    let mut level = hashes.to_vec();
+    let mut at = position;
+    while level.len() > 1 {
+        let right = at.is_multiple_of(2);
+        let sibling = if right { level.get(at + 1).unwrap_or(&level[at]) } else { &level[at - 1] };
+        steps.push(ProofStep {
+            sibling: sibling.clone(),
+            right,
+        });
+        level = level
+            .chunks(2)
+            .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+            .collect();
+        at /= 2;
+    }
+    Some(MerkleProof {
+        index: position + 1,
+        leaf,
+        root: level.remove(0),
+        steps,
+    })
+}
+
+/// Which records a query should return.
+#[derive(Debug, Default)]
+pub struct RecordFilter {
+    /// Only records committed strictly after this time.
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl RecordFilter {
+    pub fn matches(&self, record: &Record) -> bool {
+        self.since.is_none_or(|since| record.timestamp > since)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub imported: Vec<Record>,
+    pub skipped: usize,
+}
+
+/// Length and head hash of the last state that passed `verify_chain`.
+struct Checkpoint {
+    len: usize,
+    head_hash: String,
+}
+
+pub struct Ledger {
+    records: Vec<Record>,
+    checkpoint: Checkpoint,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Self {
+            records: Vec::new(),
+            checkpoint: Checkpoint {
+                len: 0,
+                head_hash: GENESIS_HASH.to_string(),
+            },
+        }
+    }
+
+    /// Rebuilds a ledger from stored records, refusing a chain that doesn't verify.
+    pub fn from_records(records: Vec<Record>) -> Result<Self, ChainError> {
+        verify_chain(&records)?;
+        let checkpoint = Checkpoint {
+            len: records.len(),
+            head_hash: records.last().map_or(GENESIS_HASH, |r| r.hash.as_str()).to_string(),
+        };
+        Ok(Self { records, checkpoint })
+    }
+
+    /// Re-links `records` in order from genesis, recomputing every `prev_hash` and
+    /// `hash`. For migrating ledgers written before records were hashed; it also
+    /// blesses any edits, so only run it on a ledger you trust.
+    pub fn rehash(records: Vec<Record>) -> Result<Self, ChainError> {
+        let mut ledger = Self::new();
+        for record in records {
+            ledger.link(record);
+        }
+        ledger.verify_or_roll_back()?;
+        Ok(ledger)
+    }
+
+    /// A copy of this ledger without its unpinned records from before `cutoff`,
+    /// re-linked from genesis, and how many records were dropped.
+    pub fn retained_since(&self, cutoff: DateTime<Utc>) -> Result<(Self, usize), ChainError> {
+        let kept: Vec<Record> = self
+            .records
+            .iter()
+            .filter(|r| r.pinned || r.timestamp >= cutoff)
+            .cloned()
+            .collect();
+        let dropped = self.records.len() - kept.len();
+        Ok((Self::rehash(kept)?, dropped))
+    }
+
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    pub fn query(&self, filter: &RecordFilter) -> Vec<&Record> {
+        self.records.iter().filter(|r| filter.matches(r)).collect()
+    }
+
+    pub fn head_hash(&self) -> &str {
+        self.records.last().map_or(GENESIS_HASH, |r| r.hash.as_str())
+    }
+
+    pub fn merkle_root(&self) -> String {
+        let hashes: Vec<String> = self.records.iter().map(|r| r.hash.clone()).collect();
+        merkle_root(&hashes)
+    }
+
+    /// The Merkle proof for the record at a 1-based index.
+    pub fn merkle_proof(&self, index: usize) -> Option<MerkleProof> {
+        let hashes: Vec<String> = self.records.iter().map(|r| r.hash.clone()).collect();
+        merkle_proof(&hashes, index.checked_sub(1)?)
+    }
+
+    /// Links `record` onto the head of the chain and appends it.
+    ///
+    /// The whole chain is re-verified after the write. If that fails, the ledger is
+    /// truncated back to the last verified checkpoint and the error is returned.
+    pub fn commit(&mut self, record: Record) -> Result<&Record, ChainError> {
+        self.link(record);
+        self.verify_or_roll_back()?;
+        Ok(self.records.last().expect("just pushed"))
+    }
+
+    fn link(&mut self, mut record: Record) {
+        record.prev_hash = self.head_hash().to_string();
+        record.hash = hash_record(&record);
+        self.records.push(record);
+    }
+
+    /// Checkpoints the current chain if it verifies, else truncates to the last checkpoint.
+    fn verify_or_roll_back(&mut self) -> Result<(), ChainError> {
+        if let Err(e) = verify_chain(&self.records) {
+            self.records.truncate(self.checkpoint.len);
+            tracing::error!(
+                "ledger verification failed ({}); rolled back to {} records at {}",
+                e,
+                self.checkpoint.len,
+                self.checkpoint.head_hash
+            );
+            return Err(e);
+        }
+        self.checkpoint = Checkpoint {
+            len: self.records.len(),
+            head_hash: self.head_hash().to_string(),
+        };
+        Ok(())
+    }
+
+    /// Sets the label on the record at a 1-based index. Labels sit outside the hash,
+    /// so this leaves the chain intact.
+    pub fn set_label(&mut self, index: usize, label: Option<bool>) -> Option<&Record> {
+        let record = self.records.get_mut(index.checked_sub(1)?)?;
+        record.label = label;
+        Some(record)
+    }
+
+    /// Appends every foreign record whose content isn't already in this ledger,
+    /// re-linking each onto the local head. The foreign chain must verify first, and
+    /// nothing is appended if a new record reuses an id already in use.
+    pub fn import(&mut self, foreign: Vec<Record>) -> Result<ImportSummary, ChainError> {
+        verify_chain(&foreign)?;
+        let mut known: HashSet<String> = self.records.iter().map(content_hash).collect();
+        let mut ids: HashSet<String> = self.records.iter().map(|r| r.id.clone()).collect();
+        let mut fresh = Vec::new();
+        let mut skipped = 0;
+        for record in foreign {
+            if !known.insert(content_hash(&record)) {
+                skipped += 1;
+                continue;
+            }
+            if !ids.insert(record.id.clone()) {
+                return Err(ChainError::IdCollision { id: record.id });
+            }
+            fresh.push(record);
+        }
+
+        let start = self.records.len();
+        for record in fresh {
+            self.link(record);
+        }
+        self.verify_or_roll_back()?;
+        Ok(ImportSummary {
+            imported: self.records[start..].to_vec(),
+            skipped,
+        })
+    }
+
+    /// Replaces the human notes on the record at a 1-based index; outside the hash.
+    pub fn set_notes(&mut self, index: usize, notes: Vec<crate::Note>) -> Option<&Record> {
+        let record = self.records.get_mut(index.checked_sub(1)?)?;
+        record.human_notes = notes;
+        Some(record)
+    }
+
+    /// Pins or unpins the record at a 1-based index; like labels, outside the hash.
+    pub fn set_pinned(&mut self, index: usize, pinned: bool) -> Option<&Record> {
+        let record = self.records.get_mut(index.checked_sub(1)?)?;
+        record.pinned = pinned;
+        Some(record)
+    }
+
+    /// Records the `--anchor` memo transaction of the record at a 1-based index. The
+    /// memo carries the record's hash, so the signature sits outside it.
+    pub fn set_solana_signature(&mut self, index: usize, signature: Option<String>) -> Option<&Record> {
+        let record = self.records.get_mut(index.checked_sub(1)?)?;
+        record.solana_signature = signature;
+        Some(record)
+    }
+
+    /// Drops the head record, e.g. when it could not be persisted after `commit`.
+    pub fn revert_head(&mut self) {
+        self.records.pop();
+        self.checkpoint = Checkpoint {
+            len: self.records.len(),
+            head_hash: self.head_hash().to_string(),
+        };
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{AIResponse, Transaction};
+
+    /// An unlinked record whose ids derive from `content`.
+    pub(crate) fn record(content: &str) -> Record {
+        Record {
+            id: format!("id-{}", content),
+            transaction: Transaction {
+                id: format!("tx-{}", content),
+                content: content.to_string(),
+                claim: None,
+            },
+            consensus: true,
+            truth_score: 100.0,
+            details: format!("{} details", content),
+            votes: vec![AIResponse {
+                agent_name: "agent".to_string(),
+                model: "openai/gpt-4o".to_string(),
+                is_valid: true,
+                confidence: Some(0.9),
+                attempts: 1,
+                lang: None,
+                system_fingerprint: None,
+                fallback_from: None,
+                self_consistency: None,
+                rationale: None,
+            }],
+            abstentions: Vec::new(),
+            debate_rounds: Vec::new(),
+            provenance: None,
+            consensus_rationale: None,
+            devils_advocate: None,
+            majority_agents: Vec::new(),
+            dissenting_agents: Vec::new(),
+            vetoed_by: Vec::new(),
+            timestamp: Utc::now(),
+            solana_slot: None,
+            solana_signature: None,
+            label: None,
+            pinned: false,
+            human_notes: Vec::new(),
+            prev_hash: String::new(),
+            hash: String::new(),
+        }
+    }
+
+    fn ledger_of(contents: &[&str]) -> Ledger {
+        let mut ledger = Ledger::new();
+        for content in contents {
+            ledger.commit(record(content)).unwrap();
+        }
+        ledger
+    }
+
+    #[test]
+    fn commit_links_records_into_a_verifiable_chain() {
+        let ledger = ledger_of(&["a", "b", "c"]);
+        let records = ledger.records();
+        assert_eq!(records[0].prev_hash, GENESIS_HASH);
+        assert_eq!(records[1].prev_hash, records[0].hash);
+        assert_eq!(ledger.head_hash(), records[2].hash);
+        verify_chain(records).unwrap();
+    }
+
+    #[test]
+    fn verify_chain_detects_edits_and_broken_links() {
+        let mut records = ledger_of(&["a", "b"]).records().to_vec();
+        records[1].details.push_str("tampered");
+        assert!(matches!(verify_chain(&records), Err(ChainError::HashMismatch { index: 1, .. })));
+
+        let mut records = ledger_of(&["a", "b"]).records().to_vec();
+        records[1].prev_hash = GENESIS_HASH.to_string();
+        assert!(matches!(verify_chain(&records), Err(ChainError::BrokenLink { index: 1, .. })));
+    }
+
+    #[test]
+    fn flipping_a_refusal_breaks_verification() {
+        let mut abstaining = record("a");
+        let abstention = |refusal| crate::Abstention {
+            agent_name: "x/grok".to_string(),
+            model: "x/grok".to_string(),
+            reason: "declined to answer".to_string(),
+            refusal,
+        };
+        abstaining.abstentions = vec![abstention(true), abstention(false)];
+        let mut ledger = Ledger::new();
+        ledger.commit(abstaining).unwrap();
+        for i in 0..2 {
+            let mut records = ledger.records().to_vec();
+            records[0].abstentions[i].refusal = !records[0].abstentions[i].refusal;
+            assert!(matches!(verify_chain(&records), Err(ChainError::HashMismatch { index: 0, .. })));
+        }
+    }
+
+    #[test]
+    fn timestamp_anomalies_flag_records_older_than_their_predecessor() {
+        let mut ledger = ledger_of(&["a", "b"]);
+        assert!(timestamp_anomalies(ledger.records()).is_empty());
+        let mut old = record("c");
+        old.timestamp = ledger.records()[0].timestamp - chrono::Duration::hours(1);
+        ledger.commit(old).unwrap();
+        let anomalies = timestamp_anomalies(ledger.records());
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!((anomalies[0].index, anomalies[0].id.as_str()), (2, "id-c"));
+    }
+
+    #[test]
+    fn rehash_links_unhashed_records() {
+        let legacy: Vec<Record> = ["a", "b"].iter().map(|c| record(c)).collect();
+        assert!(verify_chain(&legacy).is_err());
+        let ledger = Ledger::rehash(legacy).unwrap();
+        verify_chain(ledger.records()).unwrap();
+        assert_eq!(ledger.records()[1].prev_hash, ledger.records()[0].hash);
+    }
+
+    #[test]
+    fn retried_votes_change_the_hash() {
+        let once = record("a");
+        let mut retried = record("a");
+        retried.votes[0].attempts = 3;
+        assert_ne!(hash_record(&once), hash_record(&retried));
+        let mut fingerprinted = record("a");
+        fingerprinted.votes[0].system_fingerprint = Some("fp_1".to_string());
+        assert_ne!(hash_record(&once), hash_record(&fingerprinted));
+        let mut reasoned = record("a");
+        reasoned.votes[0].rationale = Some("Water is wet by definition.".to_string());
+        assert_ne!(hash_record(&once), hash_record(&reasoned));
+    }
+
+    #[test]
+    fn hiding_a_fallback_breaks_verification() {
+        let mut fallen_back = record("a");
+        fallen_back.votes[0].fallback_from = Some("anthropic/claude-3-haiku".to_string());
+        let mut ledger = Ledger::new();
+        ledger.commit(fallen_back).unwrap();
+        let mut records = ledger.records().to_vec();
+        records[0].votes[0].fallback_from = None;
+        assert!(matches!(verify_chain(&records), Err(ChainError::HashMismatch { index: 0, .. })));
+    }
+
+    #[test]
+    fn rewriting_self_consistency_samples_breaks_verification() {
+        let mut sampled = record("a");
+        sampled.votes[0].self_consistency =
+            Some(crate::consistency::SelfConsistency { samples: 5, answered: 5, agreeing: 3 });
+        let mut ledger = Ledger::new();
+        ledger.commit(sampled).unwrap();
+        let mut records = ledger.records().to_vec();
+        records[0].votes[0].self_consistency.as_mut().unwrap().agreeing = 5;
+        assert!(matches!(verify_chain(&records), Err(ChainError::HashMismatch { index: 0, .. })));
+        records[0].votes[0].self_consistency = None;
+        assert!(matches!(verify_chain(&records), Err(ChainError::HashMismatch { index: 0, .. })));
+    }
+
+    #[test]
+    fn retention_keeps_recent_and_pinned_records() {
+        let mut old = record("old");
+        old.timestamp -= chrono::Duration::days(30);
+        let mut pinned = record("pinned");
+        pinned.timestamp -= chrono::Duration::days(30);
+        pinned.pinned = true;
+        let mut ledger = Ledger::new();
+        for r in [old, pinned, record("new")] {
+            ledger.commit(r).unwrap();
+        }
+        let (retained, dropped) = ledger.retained_since(Utc::now() - chrono::Duration::days(7)).unwrap();
+        assert_eq!(dropped, 1);
+        let ids: Vec<&str> = retained.records().iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, ["id-pinned", "id-new"]);
+        verify_chain(retained.records()).unwrap();
+    }
+
+    #[test]
+    fn annotations_do_not_break_the_chain() {
+        let mut ledger = ledger_of(&["a"]);
+        assert!(ledger.set_label(1, Some(true)).is_some());
+        assert!(ledger.set_label(0, Some(true)).is_none());
+        assert!(ledger.set_label(2, Some(true)).is_none());
+        assert!(ledger.set_pinned(1, true).is_some());
+        let note = crate::Note { at: Utc::now(), text: "checked against the source".to_string() };
+        assert!(ledger.set_notes(1, vec![note]).is_some());
+        assert!(ledger.set_solana_signature(1, Some("5sig".to_string())).is_some());
+        verify_chain(ledger.records()).unwrap();
+    }
+
+    #[test]
+    fn failed_commit_rolls_back_to_checkpoint() {
+        let mut ledger = ledger_of(&["a", "b"]);
+        ledger.records[0].transaction.content = "tampered".to_string();
+        assert!(ledger.commit(record("c")).is_err());
+        assert_eq!(ledger.records().len(), 2);
+    }
+
+    #[test]
+    fn merkle_root_pairs_odd_nodes_with_themselves() {
+        assert_eq!(merkle_root(&[]), GENESIS_HASH);
+        let hashes: Vec<String> = ["a", "b", "c"].iter().map(|h| h.to_string()).collect();
+        assert_eq!(merkle_root(&hashes[..1]), "a");
+        let expected = hash_pair(&hash_pair("a", "b"), &hash_pair("c", "c"));
+        assert_eq!(merkle_root(&hashes), expected);
+    }
+
+    #[test]
+    fn merkle_proofs_verify_against_the_root() {
+        let hashes: Vec<String> = ["a", "b", "c", "d", "e"].iter().map(|h| h.to_string()).collect();
+        let root = merkle_root(&hashes);
+        for position in 0..hashes.len() {
+            let proof = merkle_proof(&hashes, position).unwrap();
+            assert_eq!(proof.root, root);
+            assert!(proof.verifies(), "proof for {} fails", position);
+        }
+        let mut forged = merkle_proof(&hashes, 2).unwrap();
+        forged.leaf = "x".to_string();
+        assert!(!forged.verifies());
+        let mut flipped = merkle_proof(&hashes, 2).unwrap();
+        flipped.steps[0].right = !flipped.steps[0].right;
+        assert!(!flipped.verifies());
+        assert!(merkle_proof(&hashes, 5).is_none());
+        assert!(merkle_proof(&hashes[..1], 0).unwrap().verifies());
+    }
+
+    #[test]
+    fn import_skips_known_content_and_relinks_the_rest() {
+        let mut local = ledger_of(&["a", "b"]);
+        let foreign = ledger_of(&["b", "c"]);
+        // Same content as local "b", committed at a different position.
+        let mut foreign_records = foreign.records().to_vec();
+        foreign_records[0] = local.records()[1].clone();
+        foreign_records[0].prev_hash = GENESIS_HASH.to_string();
+        foreign_records[0].hash = hash_record(&foreign_records[0]);
+        foreign_records[1].prev_hash = foreign_records[0].hash.clone();
+        foreign_records[1].hash = hash_record(&foreign_records[1]);
+
+        let summary = local.import(foreign_records).unwrap();
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.imported.len(), 1);
+        assert_eq!(local.records().len(), 3);
+        assert_eq!(local.records()[2].prev_hash, local.records()[1].hash);
+        verify_chain(local.records()).unwrap();
+    }
+
+    #[test]
+    fn import_rejects_reused_ids_before_appending() {
+        let mut local = ledger_of(&["a"]);
+        let mut clash = record("b");
+        clash.id = local.records()[0].id.clone();
+        let mut foreign = ledger_of(&["c"]);
+        foreign.commit(clash).unwrap();
+
+        let result = local.import(foreign.records().to_vec());
+        assert!(matches!(result, Err(ChainError::IdCollision { .. })));
+        assert_eq!(local.records().len(), 1);
+    }
+}
@@ -1,42 +1,345 @@
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use clap::{Parser, ValueEnum};
 use dotenv::dotenv;
 use futures::future::join_all;
 use lazy_static::lazy_static;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use schemars::JsonSchema;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::borrow::Cow;
 use std::env;
-use std::sync::{Arc, Mutex};
-use uuid::Uuid;
-use warp::Filter;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::RwLock;
+use warp::{Filter, Reply};
+
+mod advocate;
+mod alert;
+mod anthropic;
+mod anchor;
+mod analytics;
+mod breaker;
+mod checkpoint;
+mod claim;
+mod audit;
+mod budget;
+mod config;
+mod console;
+mod consensus;
+mod consistency;
+mod context;
+mod cost;
+mod explain;
+mod fixtures;
+mod guard;
+mod hook;
+mod ids;
+mod input;
+mod integrity;
+mod jsonrpc;
+mod lang;
+mod ledger;
+mod models;
+mod preflight;
+mod picker;
+mod pool;
+mod profile;
+mod progress;
+mod query;
+mod redact;
+mod refusal;
+mod remote;
+mod report;
+mod reputation;
+mod render;
+mod retention;
+mod retry;
+mod solana;
+mod sla;
+mod sse;
+mod store;
+mod strict;
+mod viewer;
+mod workspace;
+
+use alert::AlertWebhook;
+use fixtures::{FixtureMode, FixtureProvider};
+use advocate::DevilsAdvocate;
+use explain::Explainer;
+use hook::CommitHook;
+use ids::IdGenerator;
+use anchor::Anchor;
+use claim::Claim;
+use breaker::{BreakerProvider, BreakerState, CircuitBreaker};
+use config::{Command, Config, ConsensusStrategy, ErrorPolicy, StoreKind};
+use ledger::{Ledger, RecordFilter};
+use profile::{Profile, Stage};
+use render::RenderOptions;
+use anthropic::AnthropicProvider;
+use consistency::SelfConsistentProvider;
+use sla::SlaProvider;
+use solana::{Keypair, SolanaClient};
+use store::{JsonFileStore, LedgerStore, MemoryStore, SqliteStore, StoreError};
 
 // ======== Models / Data Structures ========
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 struct Transaction {
     id: String,
     content: String,
+    /// The structured fact `content` was rendered from, when one was submitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    claim: Option<Claim>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 struct Record {
     id: String,
     transaction: Transaction,
     consensus: bool,
+    /// 0–100 confidence-weighted estimate that the statement is true.
+    truth_score: f32,
     details: String,
-    timestamp: String,
+    /// Each agent's vote, kept structurally so the ledger can be re-rendered.
+    votes: Vec<AIResponse>,
+    /// Agents that were asked this round but returned no vote.
+    #[serde(default)]
+    abstentions: Vec<Abstention>,
+    /// Under `--rounds`, the votes of every debate round before the final one in
+    /// `votes`, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    debate_rounds: Vec<Vec<AIResponse>>,
+    /// How the verdict was produced; absent on records from before it was captured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    provenance: Option<Provenance>,
+    /// Under `--explain-consensus`, a summarizer's one-paragraph rationale for the verdict.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    consensus_rationale: Option<String>,
+    /// Under `--devils-advocate`, the case made against the verdict. Never part of
+    /// the tally.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    devils_advocate: Option<advocate::Challenge>,
+    /// Agents on the winning side, including failed ones counted as "no" under
+    /// `--error-policy no`; empty on records from before sides were captured.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    majority_agents: Vec<String>,
+    /// Agents on the losing side.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    dissenting_agents: Vec<String>,
+    /// Under `--consensus veto`, the trusted models that overturned a majority "yes".
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    vetoed_by: Vec<String>,
+    timestamp: DateTime<Utc>,
+    /// Solana slot observed when the record was committed; `None` if no RPC is
+    /// configured or it was unreachable.
+    solana_slot: Option<u64>,
+    /// Under `--anchor`, the memo transaction that wrote this record's hash to
+    /// Solana. Outside the hash, which the memo carries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    solana_signature: Option<String>,
+    /// Human-assigned ground truth for the statement. Not covered by the record hash,
+    /// since it is added after commit.
+    #[serde(default)]
+    label: Option<bool>,
+    /// Marked as important by a user. Like the label, outside the record hash.
+    #[serde(default)]
+    pinned: bool,
+    /// Reviewers' commentary, oldest first. Outside the hash, like the label.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    human_notes: Vec<Note>,
+    /// Hash of the preceding record, or `ledger::GENESIS_HASH` for the first one.
+    /// Empty in ledgers written before hashing; `--rehash` fills both in.
+    #[serde(default)]
+    prev_hash: String,
+    #[serde(default)]
+    hash: String,
+}
+
+/// A human note attached to a record after review.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+struct Note {
+    at: DateTime<Utc>,
+    text: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 struct AIResponse {
     agent_name: String,
+    /// Model that actually answered; differs from `agent_name` under `--model-override`.
+    model: String,
     is_valid: bool,
+    /// The agent's self-reported confidence in its verdict, 0.0–1.0, if it gave one.
+    confidence: Option<f32>,
+    /// Requests it took to get this vote, counting retries of truncated replies.
+    #[serde(default = "one_attempt")]
+    attempts: u32,
+    /// `--lang` the prompt was worded in; absent for the default English.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lang: Option<String>,
+    /// The backend's `system_fingerprint`, when it returned one; a change between
+    /// votes on the same model means its configuration changed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    system_fingerprint: Option<String>,
+    /// Under `--model-sla`, the slot's model that missed its SLA; `model` is then the
+    /// `--fallback-model` that answered in its place.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fallback_from: Option<String>,
+    /// Under `--self-consistency`, how many of the model's samples agreed with this vote.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    self_consistency: Option<consistency::SelfConsistency>,
+    /// The one-sentence justification the agent gave after its verdict, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rationale: Option<String>,
+}
+
+fn one_attempt() -> u32 {
+    1
+}
+
+/// The configuration a record's verdict was produced under, so a ledger read months
+/// later still says how each verdict came about.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+struct Provenance {
+    crate_version: String,
+    /// The `--consensus` strategy, e.g. `majority`.
+    consensus: String,
+    min_responses: usize,
+    /// Providers required to agree, under provider-quorum.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    quorum_providers: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    required_providers: Vec<String>,
+    /// Models whose "no" fails consensus, under veto.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    veto_models: Vec<String>,
+    /// Models that must vote "yes", under veto.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    required_yes_models: Vec<String>,
+    /// `--weights`: each model's vote weight, under majority or veto.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    trust_weights: Option<consensus::TrustWeights>,
+    /// `--quorum`: share of "yes" votes needed, under majority or veto.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    quorum: Option<f64>,
+    /// Weighted truth score needed, under truth-score.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_truth_score: Option<f32>,
+    /// Models asked this round, in slot order.
+    models: Vec<String>,
+    /// Temperature each model was sampled at, where one was configured.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    temperatures: Vec<(String, f32)>,
+    rounds: usize,
+    /// `--openai-seed`, when set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    /// SHA-256 of the `--context` document the agents judged against.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    context_sha256: Option<String>,
+    /// `--error-policy`, when not the default `abstain`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error_policy: Option<String>,
+    /// `--self-consistency`: samples each vote was the majority of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    self_consistency: Option<u32>,
+}
+
+impl Provenance {
+    fn capture(config: &Config, settings: &ProviderSettings, agents: &[&dyn AIProvider]) -> Self {
+        let models: Vec<String> = agents.iter().map(|a| a.model().to_string()).collect();
+        let quorum = config.consensus == ConsensusStrategy::ProviderQuorum;
+        let veto = config.consensus == ConsensusStrategy::Veto;
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            consensus: config
+                .consensus
+                .to_possible_value()
+                .expect("no skipped variants")
+                .get_name()
+                .to_string(),
+            min_responses: config.min_responses,
+            quorum_providers: quorum.then_some(config.quorum_providers),
+            required_providers: if quorum { config.required_providers.clone() } else { Vec::new() },
+            quorum: config.quorum.filter(|_| config.consensus == ConsensusStrategy::Majority || veto),
+            trust_weights: config.weights.clone().filter(|_| config.consensus == ConsensusStrategy::Majority || veto),
+            veto_models: if veto { config.veto_models.clone() } else { Vec::new() },
+            required_yes_models: if veto { config.required_yes_models.clone() } else { Vec::new() },
+            min_truth_score: (config.consensus == ConsensusStrategy::TruthScore).then_some(config.min_truth_score),
+            temperatures: models
+                .iter()
+                .filter_map(|m| settings.temperature(m).map(|t| (m.clone(), t)))
+                .collect(),
+            models,
+            rounds: config.rounds,
+            seed: settings.seed,
+            context_sha256: settings.budget.context.as_ref().map(|c| c.sha256.clone()),
+            error_policy: (config.error_policy != ErrorPolicy::Abstain).then(|| {
+                let value = config.error_policy.to_possible_value().expect("no skipped variants");
+                value.get_name().to_string()
+            }),
+            self_consistency: config.self_consistency,
+        }
+    }
+}
+
+impl Record {
+    /// The votes its verdict was formed from: the recorded votes, plus a "no" for
+    /// each failed agent when it was recorded under `--error-policy no`.
+    fn tally(&self) -> Cow<'_, [AIResponse]> {
+        match self.provenance.as_ref().and_then(|p| p.error_policy.as_deref()) {
+            Some("no") => Cow::Owned(consensus::with_failures_as_no(&self.votes, &self.abstentions)),
+            _ => Cow::Borrowed(&self.votes),
+        }
+    }
+}
+
+/// `--error-policy fail`: the round's first failed agent, as the reason to abort it.
+fn first_failure(config: &Config, abstentions: &[Abstention]) -> Option<RoundError> {
+    if config.error_policy != ErrorPolicy::Fail {
+        return None;
+    }
+    abstentions.iter().find(|a| !a.refusal).map(|a| {
+        RoundError::new(
+            warp::http::StatusCode::BAD_GATEWAY,
+            format!("{} failed ({}); --error-policy fail records nothing", a.agent_name, a.reason),
+        )
+    })
+}
+
+/// An agent that returned no vote, and why.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+struct Abstention {
+    agent_name: String,
+    model: String,
+    reason: String,
+    /// The model answered but declined to give a verdict.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    refusal: bool,
 }
 
 #[derive(Deserialize)]
+/// Either a free-text `statement` or a structured `claim`.
 struct ValidateRequest {
-    statement: String,
+    #[serde(default)]
+    statement: Option<String>,
+    #[serde(default)]
+    claim: Option<Claim>,
+}
+
+impl ValidateRequest {
+    /// The statement and claim `run_round` takes, or why the request is malformed.
+    fn parts(&self) -> Result<(&str, Option<&Claim>), String> {
+        if self.statement.is_some() == self.claim.is_some() {
+            return Err("send exactly one of statement or claim".to_string());
+        }
+        Ok((self.statement.as_deref().unwrap_or_default(), self.claim.as_ref()))
+    }
 }
 
 #[derive(Serialize)]
@@ -45,82 +348,736 @@ struct ValidateResponse {
     ai_responses: Vec<AIResponse>,
 }
 
-// In-memory ledger
+#[derive(Deserialize)]
+struct LedgerQuery {
+    /// Only return records committed strictly after this RFC3339 time.
+    since: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    q: String,
+    #[serde(default)]
+    mode: store::SearchMode,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct TrendQuery {
+    window: Option<usize>,
+    threshold: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct WhatIfQuery {
+    strategy: String,
+    quorum_providers: Option<usize>,
+    min_truth_score: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct RobustnessQuery {
+    resamples: Option<usize>,
+    /// Fixes the draws, so a report can be reproduced.
+    seed: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct PurgeRequest {
+    days: u64,
+}
+
+#[derive(Deserialize)]
+struct LabelRequest {
+    label: bool,
+}
+
+#[derive(Deserialize)]
+struct NoteRequest {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct WorkspaceRequest {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct RenderQuery {
+    color: Option<bool>,
+    verbose: Option<bool>,
+}
+
+/// Point-in-time copy of the ledger with a summary header, served by `/api/export`.
+#[derive(Serialize)]
+struct LedgerSnapshot<'a> {
+    exported_at: DateTime<Utc>,
+    record_count: usize,
+    consensus_count: usize,
+    head_hash: &'a str,
+    merkle_root: String,
+    records: &'a [Record],
+}
+
+/// The chain tip and Merkle root: a compact fingerprint of the whole ledger.
+#[derive(Serialize)]
+struct HeadReport<'a> {
+    records: usize,
+    head_hash: &'a str,
+    merkle_root: String,
+}
+
+// In-memory ledger. Reads (listing, queries) share the lock; only commits take it exclusively.
 lazy_static! {
-    static ref LEDGER: Mutex<Vec<Record>> = Mutex::new(Vec::new());
+    static ref LEDGER: RwLock<Ledger> = RwLock::new(Ledger::new());
 }
 
 // ======== Expanded LLM Models ========
 
-const AI_MODELS: [&str; 9] = [
-    "openai/gpt-4o",
-    "openai/gpt-3.5-turbo",
-    "anthropic/claude-3-haiku",
-    "google/gemini-pro",
-    "mistral/mistral-7b-instruct",
-    "meta/llama-3-70b-instruct",
-    "cohere/command-r-plus",
-    "deepseek/deepseek-coder",
-    "x/grok",
-];
+// The order is part of the ledger's meaning: agent slot N (and "agent-N" under
+// --model-override) is always the Nth entry, so keep this a fixed array.
+const AI_MODELS: [&str; 9] = models::api_names();
 
 // ======== AI Provider Trait ========
 
 #[async_trait]
 trait AIProvider: Send + Sync {
-    async fn validate(&self, text: &str) -> Result<AIResponse, Box<dyn std::error::Error + Send + Sync>>;
+    /// Name of the agent slot this provider votes as.
+    fn agent_name(&self) -> &str;
+    /// Model identifier as listed in `AI_MODELS`.
+    fn model(&self) -> &str;
+    /// State of the circuit breaker guarding this provider, if it has one.
+    fn breaker_state(&self) -> Option<BreakerState> {
+        None
+    }
+    /// Votes on `text`. In a debate round, `debate` describes the previous round's
+    /// votes for the agent to reconsider.
+    async fn validate(&self, text: &str, debate: Option<&str>) -> Result<AIResponse, ProviderError>;
+}
+
+// ======== Provider Errors ========
+
+#[derive(Debug, thiserror::Error)]
+enum ProviderError {
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// A 2xx response whose body was cut off mid-JSON.
+    #[error("truncated response body ({0} bytes)")]
+    Truncated(usize),
+    /// The connection dropped while the body was still being read.
+    #[error("response body interrupted: {0}")]
+    Interrupted(reqwest::Error),
+    /// A 2xx response that carries an error or no completion text.
+    #[error("unusable response: {0}")]
+    Malformed(String),
+    /// Under `--strict-json`, a 2xx body that departs from the chat-completions schema.
+    /// `raw` is the body as received, for `--check-providers` to show.
+    #[error("response breaks the chat-completions schema at {path}: {problem}")]
+    Schema { path: String, problem: String, raw: String },
+    /// The provider's API key isn't configured, so no request was sent.
+    #[error("{0} not set")]
+    MissingKey(&'static str),
+    /// The provider's circuit breaker is open, so no request was sent.
+    #[error("circuit breaker open")]
+    CircuitOpen,
+    /// `--replay` has no saved response for this model and statement.
+    #[error("no recorded response at {0}")]
+    MissingFixture(String),
+    /// The model declined to answer (`--detect-refusals`/`--refusal-pattern`).
+    #[error("refused to answer (matched '{0}')")]
+    Refused(String),
+    /// The prompt is over the model's `--max-prompt-tokens` limit, so it wasn't sent.
+    #[error("prompt is {tokens} tokens, over the limit of {max}")]
+    PromptTooLong { tokens: usize, max: usize },
+    /// The model missed its `--model-sla` and its `--fallback-model` failed too.
+    #[error("missed its {sla_ms} ms SLA; fallback {model} failed: {source}")]
+    FallbackFailed {
+        model: String,
+        sla_ms: u64,
+        source: Box<ProviderError>,
+    },
+    /// Under `--self-consistency`, the model's answered samples split evenly.
+    #[error("split {yes}–{no} across its own samples")]
+    SelfSplit { yes: u32, no: u32 },
+    /// A non-2xx response, with the provider's error message when it sent one and
+    /// how long its `Retry-After` asked callers to wait.
+    #[error("HTTP {status}: {message}")]
+    Status {
+        status: reqwest::StatusCode,
+        message: String,
+        retry_after: Option<std::time::Duration>,
+    },
+}
+
+impl ProviderError {
+    /// Transient failures worth another request: rate limits, server errors, network
+    /// errors and cut-off bodies. Other statuses (a bad key, a malformed request) fail fast.
+    fn is_retryable(&self) -> bool {
+        match self {
+            ProviderError::Truncated(_) | ProviderError::Interrupted(_) => true,
+            ProviderError::Status { status, .. } => {
+                *status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+            }
+            ProviderError::Http(e) => e.is_connect() || e.is_timeout(),
+            _ => false,
+        }
+    }
+
+    fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            ProviderError::Status { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// The model (or the fallback standing in for it) answered, but declined to.
+    fn is_refusal(&self) -> bool {
+        match self {
+            ProviderError::Refused(_) => true,
+            ProviderError::FallbackFailed { source, .. } => source.is_refusal(),
+            _ => false,
+        }
+    }
+}
+
+/// A provider's reply text and the `system_fingerprint` it came with, if any.
+struct ProviderReply {
+    text: String,
+    fingerprint: Option<String>,
+}
+
+/// Reads a chat-completions body and returns the first choice's content.
+///
+/// Non-2xx responses become `ProviderError::Status` carrying the status code. A
+/// successful status with an unparseable or interrupted body means the stream was
+/// truncated, which is reported separately from a genuine API error so it can be
+/// retried. A 2xx body that still reports an error, or has no completion text, is
+/// an error too rather than a vote. With `strict`, so is any other departure from
+/// the chat-completions schema.
+async fn read_reply(
+    response: reqwest::Response,
+    profile: Option<&Profile>,
+    strict: bool,
+) -> Result<ProviderReply, ProviderError> {
+    let status = response.status();
+    let retry_after = retry::retry_after(response.headers());
+    let started = Instant::now();
+    let bytes = response.bytes().await.map_err(ProviderError::Interrupted)?;
+    profile::record(profile, Stage::AgentHttp, started);
+    let started = Instant::now();
+    let parsed = serde_json::from_slice::<serde_json::Value>(&bytes);
+    profile::record(profile, Stage::JsonParse, started);
+
+    if !status.is_success() {
+        let message = match &parsed {
+            Ok(body) => body["error"]["message"].as_str().map(str::to_string),
+            Err(_) => None,
+        }
+        .unwrap_or_else(|| String::from_utf8_lossy(&bytes).chars().take(200).collect());
+        return Err(ProviderError::Status { status, message, retry_after });
+    }
+
+    let body = match parsed {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::debug!(raw = %String::from_utf8_lossy(&bytes), "partial JSON from provider: {}", e);
+            return Err(ProviderError::Truncated(bytes.len()));
+        }
+    };
+
+    if let Some(error) = body.get("error") {
+        let message = error["message"].as_str().map_or_else(|| error.to_string(), str::to_string);
+        return Err(ProviderError::Malformed(message));
+    }
+    if strict {
+        if let Err(deviation) = strict::check_completion(&body) {
+            let raw = String::from_utf8_lossy(&bytes).into_owned();
+            tracing::error!(raw = %raw, "schema deviation at {}", deviation.path);
+            return Err(ProviderError::Schema {
+                path: deviation.path,
+                problem: deviation.problem,
+                raw,
+            });
+        }
+    }
+    match body["choices"][0]["message"]["content"].as_str() {
+        Some(content) => Ok(ProviderReply {
+            text: content.to_string(),
+            fingerprint: body["system_fingerprint"].as_str().map(str::to_string),
+        }),
+        None => Err(ProviderError::Malformed("no completion in response".to_string())),
+    }
+}
+
+/// Reads a `stream: true` chat-completions response, logging content as it arrives,
+/// and returns the reassembled message.
+///
+/// A stream that ends before `[DONE]` is reported as truncated so it can be retried.
+async fn read_stream(
+    mut response: reqwest::Response,
+    model: &str,
+    profile: Option<&Profile>,
+) -> Result<ProviderReply, ProviderError> {
+    if !response.status().is_success() {
+        // `--strict-json` can't be combined with `--stream`.
+        return read_reply(response, profile, false).await;
+    }
+    let mut sse = sse::SseAssembler::default();
+    loop {
+        let started = Instant::now();
+        let chunk = response.chunk().await.map_err(ProviderError::Interrupted)?;
+        profile::record(profile, Stage::AgentHttp, started);
+        let Some(chunk) = chunk else { break };
+        let started = Instant::now();
+        let deltas = sse.push(&chunk).map_err(ProviderError::Malformed)?;
+        profile::record(profile, Stage::JsonParse, started);
+        for delta in deltas {
+            tracing::debug!("{} streaming: {}", model, delta);
+        }
+    }
+    if !sse.is_done() {
+        return Err(ProviderError::Truncated(sse.content().len()));
+    }
+    if sse.content().is_empty() {
+        return Err(ProviderError::Malformed("no completion in stream".to_string()));
+    }
+    Ok(ProviderReply {
+        text: sse.content().to_string(),
+        fingerprint: sse.fingerprint().map(str::to_string),
+    })
+}
+
+/// Request options shared by every provider, taken from `Config`.
+#[derive(Debug, Default)]
+struct ProviderSettings {
+    /// Ask for `stream: true` responses and log the reply as it arrives.
+    stream: bool,
+    budget: budget::PromptBudget,
+    proxy: Option<reqwest::Proxy>,
+    /// `--temperature`, used for models without a `--model-temperature`.
+    temperature: Option<f32>,
+    model_temperatures: Vec<(String, f32)>,
+    /// `--extra-header` entries, as (backend, name, value).
+    extra_headers: Vec<(String, String, String)>,
+    /// Set with `--profile`; stage timings for the whole session, providers included.
+    profile: Option<Profile>,
+    /// `--openai-seed`, sent as `seed` so backends sample reproducibly.
+    seed: Option<u64>,
+    /// Reply phrases that make a vote a refusal; empty unless refusal detection is on.
+    refusal_patterns: Vec<String>,
+    /// `--strict-json`: reject replies that depart from the chat-completions schema.
+    strict_json: bool,
+    retry: retry::RetryPolicy,
+    /// `--anthropic-direct`: anthropic/* models go to Anthropic's own API.
+    anthropic_direct: bool,
+    /// The endpoint of a non-OpenAI `--openai-base-url` server, which then serves
+    /// every agent routed to the OpenAI backend.
+    openai_server: Option<String>,
+}
+
+impl ProviderSettings {
+    /// Sampling temperature for `model`, if one is configured; otherwise the
+    /// provider's own default applies.
+    fn temperature(&self, model: &str) -> Option<f32> {
+        self.model_temperatures
+            .iter()
+            .find(|(m, _)| m == model)
+            .map(|(_, t)| *t)
+            .or(self.temperature)
+    }
+
+    /// A reply that declines to answer is an abstention, not a "no".
+    fn check_refusal(&self, reply: &str) -> Result<(), ProviderError> {
+        match refusal::refusal(reply, &self.refusal_patterns) {
+            Some(pattern) => Err(ProviderError::Refused(pattern.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Adds the configured extra headers for `backend` (openai, openrouter, grok or anthropic).
+    fn with_extra_headers(&self, backend: &str, mut request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        for (_, name, value) in self.extra_headers.iter().filter(|(b, _, _)| b == backend) {
+            request = request.header(name, value);
+        }
+        request
+    }
+}
+
+/// A client builder for outbound calls. `proxy` (from `--proxy`) takes precedence;
+/// without it reqwest follows HTTPS_PROXY, HTTP_PROXY, ALL_PROXY and NO_PROXY.
+fn client_builder(proxy: Option<&reqwest::Proxy>) -> reqwest::ClientBuilder {
+    let builder = Client::builder();
+    match proxy {
+        Some(proxy) => builder.proxy(proxy.clone()),
+        None => builder,
+    }
+}
+
+/// A provider's reply and the number of requests it took.
+struct Completion {
+    text: String,
+    fingerprint: Option<String>,
+    attempts: u32,
+}
+
+/// The request and reply format a backend speaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Api {
+    /// OpenAI-style chat completions: OpenAI, OpenRouter, Grok and compatible servers.
+    ChatCompletions,
+    /// Anthropic's Messages API, which takes no seed and temperatures only up to 1.
+    Messages,
+}
+
+/// Sends a chat-completions request, retrying transient failures as `settings.retry` allows.
+async fn complete(
+    request: reqwest::RequestBuilder,
+    body: serde_json::Value,
+    model: &str,
+    settings: &ProviderSettings,
+) -> Result<Completion, ProviderError> {
+    complete_as(Api::ChatCompletions, request, body, model, settings).await
+}
+
+/// `complete` for a backend speaking `api`. Streaming and `--strict-json` apply only
+/// to chat completions; main rejects them for the other routes.
+async fn complete_as(
+    api: Api,
+    request: reqwest::RequestBuilder,
+    mut body: serde_json::Value,
+    model: &str,
+    settings: &ProviderSettings,
+) -> Result<Completion, ProviderError> {
+    let chat = api == Api::ChatCompletions;
+    if settings.stream && chat {
+        body["stream"] = json!(true);
+    }
+    if let Some(temperature) = settings.temperature(model) {
+        // Anthropic's range is 0–1; the flags allow up to 2.
+        body["temperature"] = json!(if chat { temperature } else { temperature.min(1.0) });
+    }
+    if let Some(seed) = settings.seed.filter(|_| chat) {
+        body["seed"] = json!(seed);
+    }
+    let request = request.json(&body);
+    let mut attempt = 1;
+    loop {
+        let started = Instant::now();
+        let sent = request.try_clone().expect("JSON request bodies are cloneable").send().await;
+        let profile = settings.profile.as_ref();
+        profile::record(profile, Stage::AgentHttp, started);
+        let reply = match sent {
+            Ok(response) if !chat => anthropic::read_response(response, profile).await,
+            Ok(response) if settings.stream => read_stream(response, model, profile).await,
+            Ok(response) => read_reply(response, profile, settings.strict_json).await,
+            Err(e) => Err(ProviderError::Http(e)),
+        };
+        match reply {
+            Err(e) if e.is_retryable() && attempt < settings.retry.max_attempts => {
+                backoff(model, &e, attempt, settings.retry).await;
+                attempt += 1;
+            }
+            result => {
+                return result.map(|reply| Completion {
+                    text: reply.text,
+                    fingerprint: reply.fingerprint,
+                    attempts: attempt,
+                })
+            }
+        }
+    }
+}
+
+/// Logs a retryable failure and waits out its backoff before attempt `attempt + 1`.
+async fn backoff(model: &str, error: &ProviderError, attempt: u32, policy: retry::RetryPolicy) {
+    let delay = policy.delay(attempt, rand::random(), error.retry_after());
+    tracing::warn!(
+        "{}: {}, retrying in {} ms (attempt {}/{})",
+        model,
+        error,
+        delay.as_millis(),
+        attempt + 1,
+        policy.max_attempts
+    );
+    tokio::time::sleep(delay).await;
+}
+
+/// The yes/no question every agent is asked about a statement, worded in `lang`,
+/// followed in debate rounds by the previous round's votes.
+fn validation_prompt(lang: config::Lang, text: &str, debate: Option<&str>) -> String {
+    let framing = lang::framing(lang);
+    let mut prompt = format!("{}'{}'", framing.question, text);
+    if let Some(debate) = debate {
+        prompt.push_str(&format!("\n\n{}\n{}", debate, framing.reconsider));
+    }
+    prompt
+}
+
+/// What agents are shown before a debate round: the previous round's tally and
+/// every vote, with agent and model names withheld.
+fn debate_summary(round: usize, votes: &[AIResponse]) -> String {
+    let yes = votes.iter().filter(|v| v.is_valid).count();
+    let mut out = format!(
+        "In round {} the panel voted {} yes and {} no:",
+        round,
+        yes,
+        votes.len() - yes
+    );
+    for (i, vote) in votes.iter().enumerate() {
+        let verdict = if vote.is_valid { "yes" } else { "no" };
+        let confidence = vote.confidence.map(|c| format!(" {:.2}", c)).unwrap_or_default();
+        out.push_str(&format!("\nAgent {}: {}{}", i + 1, verdict, confidence));
+    }
+    out
+}
+
+/// Splits a reply into its verdict, its confidence and the justification after
+/// them. The verdict is the first standalone "yes" or "no", so a rationale such
+/// as "not yes-worthy" can't flip it; the confidence is the first number in 0..=1
+/// before the justification starts. A reply without a verdict is unusable, and the
+/// agent abstains rather than being counted as a "no".
+fn parse_vote(reply: &str) -> Result<(bool, Option<f32>, Option<String>), ProviderError> {
+    let reply = input::sanitize_statement(reply);
+    let Some((is_valid, end)) = verdict_word(&reply) else {
+        let excerpt: String = reply.chars().take(80).collect();
+        return Err(ProviderError::Malformed(format!("no yes/no verdict in reply: {:?}", excerpt)));
+    };
+    let rest = &reply[end..];
+    let (head, rationale) = rest.split_at(rest.find(char::is_alphabetic).unwrap_or(rest.len()));
+    let confidence = head
+        .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .filter_map(|token| token.trim_end_matches('.').parse::<f32>().ok())
+        .find(|c| (0.0..=1.0).contains(c));
+    let rationale = rationale.trim();
+    Ok((is_valid, confidence, (!rationale.is_empty()).then(|| rationale.to_string())))
+}
+
+/// The first word of `reply` that is "yes" or "no", in any case, and the byte
+/// offset just past it.
+fn verdict_word(reply: &str) -> Option<(bool, usize)> {
+    let mut start = None;
+    for (i, c) in reply.char_indices().chain([(reply.len(), ' ')]) {
+        match (start, c.is_alphanumeric()) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                let word = &reply[s..i];
+                if word.eq_ignore_ascii_case("yes") || word.eq_ignore_ascii_case("no") {
+                    return Some((word.eq_ignore_ascii_case("yes"), i));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// ======== OpenAI Provider ========
+
+const OPENAI_CHAT_URL: &str = "https://api.openai.com/v1/chat/completions";
+
+/// OpenAI keys handed out round-robin, so parallel agents spread across per-key rate limits.
+struct KeyRing {
+    keys: Vec<String>,
+    next: AtomicUsize,
+}
+
+impl KeyRing {
+    /// Reads `OPENAI_API_KEYS` (comma-separated), falling back to `OPENAI_API_KEY`.
+    fn from_env() -> Option<Self> {
+        let keys: Vec<String> = env::var("OPENAI_API_KEYS")
+            .or_else(|_| env::var("OPENAI_API_KEY"))
+            .ok()?
+            .split(',')
+            .map(|k| k.trim().to_string())
+            .filter(|k| !k.is_empty())
+            .collect();
+        if keys.is_empty() {
+            return None;
+        }
+        Some(Self {
+            keys,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// No keys, for an `--openai-base-url` server that takes requests without auth.
+    fn empty() -> Self {
+        Self {
+            keys: Vec::new(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the next key and a short label ("#2 ...abcd") safe to log, or `None`
+    /// for an empty ring.
+    fn next(&self) -> Option<(&str, String)> {
+        if self.keys.is_empty() {
+            return None;
+        }
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.keys.len();
+        let key = &self.keys[index];
+        let tail = key.get(key.len().saturating_sub(4)..).unwrap_or_default();
+        Some((key, format!("#{} ...{}", index + 1, tail)))
+    }
+}
+
+/// Calls the OpenAI API directly. Used for `openai/*` models under `--openai-direct`,
+/// so usage is billed (and attributed) to that account rather than OpenRouter, and
+/// for every model when `--openai-base-url` names another OpenAI-compatible server.
+struct OpenAIProvider {
+    client: Client,
+    agent_name: String,
+    model: String,
+    keys: Arc<KeyRing>,
+    organization: Option<String>,
+    project: Option<String>,
+    settings: Arc<ProviderSettings>,
+}
+
+impl OpenAIProvider {
+    fn new(agent_name: &str, model: &str, keys: Arc<KeyRing>, settings: Arc<ProviderSettings>) -> Self {
+        Self {
+            client: client_builder(settings.proxy.as_ref())
+                .build()
+                .expect("static client configuration"),
+            agent_name: agent_name.to_string(),
+            model: model.to_string(),
+            keys,
+            settings,
+            organization: env::var("OPENAI_ORG_ID").ok(),
+            project: env::var("OPENAI_PROJECT_ID").ok(),
+        }
+    }
+}
+
+#[async_trait]
+impl AIProvider for OpenAIProvider {
+    fn agent_name(&self) -> &str {
+        &self.agent_name
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn validate(&self, text: &str, debate: Option<&str>) -> Result<AIResponse, ProviderError> {
+        let prompt = self.settings.budget.fit(&self.model, text, debate)?;
+        let request_body = json!({
+            "model": server_model(&self.model).unwrap_or(&self.model),
+            "messages": [
+                {
+                    "role": "user",
+                    "content": prompt
+                }
+            ]
+        });
+
+        let endpoint = self.settings.openai_server.as_deref().unwrap_or(OPENAI_CHAT_URL);
+        let mut request = self.client.post(endpoint);
+        let key_label = match self.keys.next() {
+            Some((api_key, label)) => {
+                request = request.header("Authorization", format!("Bearer {}", api_key));
+                label
+            }
+            None => "(none)".to_string(),
+        };
+        tracing::debug!("{} using {} with OpenAI key {}", self.model, endpoint, key_label);
+        if let Some(org) = &self.organization {
+            request = request.header("OpenAI-Organization", org);
+        }
+        if let Some(project) = &self.project {
+            request = request.header("OpenAI-Project", project);
+        }
+        let request = self.settings.with_extra_headers("openai", request);
+        let completion = complete(request, request_body, &self.model, &self.settings)
+            .await
+            .inspect_err(|e| tracing::warn!("{} failed with OpenAI key {}: {}", self.model, key_label, e))?;
+
+        self.settings.check_refusal(&completion.text)?;
+        let (is_valid, confidence, rationale) = parse_vote(&completion.text)?;
+        Ok(AIResponse {
+            agent_name: self.agent_name.clone(),
+            model: self.model.clone(),
+            is_valid,
+            confidence,
+            attempts: completion.attempts,
+            lang: self.settings.budget.vote_lang(),
+            system_fingerprint: completion.fingerprint,
+            fallback_from: None,
+            self_consistency: None,
+            rationale,
+        })
+    }
 }
 
 // ======== OpenRouter Provider ========
 
 struct OpenRouterProvider {
     client: Client,
+    agent_name: String,
     model: String,
+    settings: Arc<ProviderSettings>,
 }
 
 impl OpenRouterProvider {
-    fn new(model: &str) -> Self {
+    fn new(agent_name: &str, model: &str, settings: Arc<ProviderSettings>) -> Self {
         Self {
-            client: Client::new(),
+            client: client_builder(settings.proxy.as_ref())
+                .build()
+                .expect("static client configuration"),
+            agent_name: agent_name.to_string(),
             model: model.to_string(),
+            settings,
         }
     }
 }
 
 #[async_trait]
 impl AIProvider for OpenRouterProvider {
-    async fn validate(&self, text: &str) -> Result<AIResponse, Box<dyn std::error::Error + Send + Sync>> {
+    fn agent_name(&self) -> &str {
+        &self.agent_name
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn validate(&self, text: &str, debate: Option<&str>) -> Result<AIResponse, ProviderError> {
         // Use the OPENROUTER_API_KEY from your environment
         let api_key = env::var("OPENROUTER_API_KEY")
-            .expect("OPENROUTER_API_KEY not set");
-        
+            .map_err(|_| ProviderError::MissingKey("OPENROUTER_API_KEY"))?;
+
+        let prompt = self.settings.budget.fit(&self.model, text, debate)?;
         let request_body = json!({
             "model": self.model,
             "messages": [
                 {
                     "role": "user",
-                    "content": format!("Is the following statement valid? Respond ONLY with 'yes' or 'no': '{}'", text)
+                    "content": prompt
                 }
             ]
         });
 
-        let response = self.client
+        let request = self.client
             .post("https://openrouter.ai/api/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", api_key))
-            .json(&request_body)
-            .send()
-            .await?;
-        
-        let response_body: serde_json::Value = response.json().await?;
-        let reply = response_body["choices"][0]["message"]["content"]
-            .as_str()
-            .unwrap_or("no response")
-            .to_lowercase();
-
-        let is_valid = reply.contains("yes");
+            .header("Authorization", format!("Bearer {}", api_key));
+        let request = self.settings.with_extra_headers("openrouter", request);
+        let completion = complete(request, request_body, &self.model, &self.settings).await?;
+
+        self.settings.check_refusal(&completion.text)?;
+        let (is_valid, confidence, rationale) = parse_vote(&completion.text)?;
         Ok(AIResponse {
-            agent_name: self.model.clone(),
+            agent_name: self.agent_name.clone(),
+            model: self.model.clone(),
             is_valid,
+            confidence,
+            attempts: completion.attempts,
+            lang: self.settings.budget.vote_lang(),
+            system_fingerprint: completion.fingerprint,
+            fallback_from: None,
+            self_consistency: None,
+            rationale,
         })
     }
 }
@@ -129,178 +1086,2408 @@ impl AIProvider for OpenRouterProvider {
 
 struct GrokProvider {
     client: Client,
+    agent_name: String,
+    settings: Arc<ProviderSettings>,
 }
 
 impl GrokProvider {
-    fn new() -> Self {
+    fn new(agent_name: &str, settings: Arc<ProviderSettings>) -> Self {
         // Note: For testing purposes, we accept invalid certificates.
         Self {
-            client: Client::builder()
+            agent_name: agent_name.to_string(),
+            client: client_builder(settings.proxy.as_ref())
                 .timeout(std::time::Duration::from_secs(10))
                 .danger_accept_invalid_certs(true)
                 .build()
                 .unwrap(),
+            settings,
         }
     }
 }
 
 #[async_trait]
 impl AIProvider for GrokProvider {
-    async fn validate(&self, text: &str) -> Result<AIResponse, Box<dyn std::error::Error + Send + Sync>> {
-        let api_key = env::var("X_API_KEY").unwrap_or_else(|_| {
-            eprintln!("Grok API key missing or invalid. Using fallback.");
-            "dummy_key".to_string()
-        });
+    fn agent_name(&self) -> &str {
+        &self.agent_name
+    }
+
+    fn model(&self) -> &str {
+        "x/grok"
+    }
 
+    async fn validate(&self, text: &str, debate: Option<&str>) -> Result<AIResponse, ProviderError> {
+        let api_key = env::var("X_API_KEY").map_err(|_| ProviderError::MissingKey("X_API_KEY"))?;
+
+        let prompt = self.settings.budget.fit("x/grok", text, debate)?;
         let request_body = json!({
             "model": "grok-2-latest",
             "messages": [
                 {
                     "role": "user",
-                    "content": format!("Is the following statement valid? Respond ONLY with 'yes' or 'no': '{}'", text)
+                    "content": prompt
                 }
             ],
             "temperature": 0.1
         });
 
-        let response = self.client
+        let request = self.client
             .post("https://api.x.ai/v1/chat/completions")
             .header("Authorization", format!("Bearer {}", api_key))
-            .header("X-API-Version", "2023-11-22")
-            .json(&request_body)
-            .send()
-            .await;
-
-        // If there is an error, log it and mark the response as invalid.
-        let response = match response {
-            Ok(resp) => resp,
-            Err(e) => {
-                eprintln!("Grok API error: {}", e);
-                return Ok(AIResponse {
-                    agent_name: "x/grok".to_string(),
-                    is_valid: false,
-                });
-            }
-        };
+            .header("X-API-Version", "2023-11-22");
+        let request = self.settings.with_extra_headers("grok", request);
 
-        let response_body: serde_json::Value = response.json().await?;
-        let reply = response_body["choices"][0]["message"]["content"]
-            .as_str()
-            .unwrap_or("no response")
-            .to_lowercase();
+        // A failed request is an abstention, not a "no" vote.
+        let completion = complete(request, request_body, "x/grok", &self.settings).await?;
 
-        let is_valid = reply.contains("yes");
+        self.settings.check_refusal(&completion.text)?;
+        let (is_valid, confidence, rationale) = parse_vote(&completion.text)?;
         Ok(AIResponse {
-            agent_name: "x/grok".to_string(),
+            agent_name: self.agent_name.clone(),
+            model: "x/grok".to_string(),
             is_valid,
+            confidence,
+            attempts: completion.attempts,
+            lang: self.settings.budget.vote_lang(),
+            system_fingerprint: completion.fingerprint,
+            fallback_from: None,
+            self_consistency: None,
+            rationale,
         })
     }
 }
 
-// ======== Handler Logic ========
-
-async fn handle_validate(
-    req: ValidateRequest,
-    providers: Arc<Vec<Box<dyn AIProvider>>>,
-) -> Result<impl warp::Reply, warp::Rejection> {
-    // 1. Create a transaction
-    let transaction = Transaction {
-        id: Uuid::new_v4().to_string(),
-        content: req.statement.clone(),
-    };
+/// The name an OpenAI-compatible server is asked for `model` by: an OpenAI pool id
+/// without its prefix, or a bare name such as Ollama's `llama3`. `None` for another
+/// vendor's id (`google/gemini-pro`), which only OpenRouter serves.
+fn server_model(model: &str) -> Option<&str> {
+    let name = model.trim_start_matches("openai/");
+    (!name.contains('/')).then_some(name)
+}
 
-    // 2. Query all AI providers in parallel
-    let futures = providers.iter().map(|p| p.validate(&transaction.content));
-    let responses: Vec<AIResponse> = join_all(futures)
-        .await
-        .into_iter()
-        .filter_map(Result::ok)
-        .collect();
+/// The backend a model's requests go to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Route {
+    Grok,
+    OpenAI,
+    Anthropic,
+    OpenRouter,
+}
 
-    // 3. Build details string from responses
-    let mut details = String::new();
-    for r in &responses {
-        let vote_str = if r.is_valid { "yes" } else { "no" };
-        details.push_str(&format!("{} voted: {}\n", r.agent_name, vote_str));
+impl Route {
+    /// The route for `model` under the configured direct-API flags.
+    fn of(model: &str, openai_keys: &Option<Arc<KeyRing>>, settings: &ProviderSettings) -> Self {
+        if models::lookup(model).is_some_and(|m| m.provider == models::Provider::XAi) {
+            Route::Grok
+        } else if (model.starts_with("openai/") || settings.openai_server.is_some()) && openai_keys.is_some() {
+            Route::OpenAI
+        } else if model.starts_with("anthropic/") && settings.anthropic_direct {
+            Route::Anthropic
+        } else {
+            Route::OpenRouter
+        }
     }
-    details.push_str(&format!("\nRecorded at {}\n", Utc::now().to_rfc3339()));
 
-    // 4. Create a record and add it to our ledger
-    let record = Record {
-        id: Uuid::new_v4().to_string(),
-        transaction,
-        consensus: true,
-        details,
-        timestamp: Utc::now().to_rfc3339(),
-    };
-    {
-        let mut ledger = LEDGER.lock().unwrap();
-        ledger.push(record.clone());
+    /// The env var this route reads its key from on every call. The OpenAI route is
+    /// only taken once its key ring is loaded.
+    fn key_var(self) -> Option<&'static str> {
+        match self {
+            Route::Grok => Some("X_API_KEY"),
+            Route::Anthropic => Some("ANTHROPIC_API_KEY"),
+            Route::OpenRouter => Some("OPENROUTER_API_KEY"),
+            Route::OpenAI => None,
+        }
     }
-
-    // 5. Return the response as JSON
-    let response = ValidateResponse {
-        record,
-        ai_responses: responses,
-    };
-    Ok(warp::reply::json(&response))
 }
 
-fn with_providers(
-    providers: Arc<Vec<Box<dyn AIProvider>>>,
-) -> impl Filter<Extract = (Arc<Vec<Box<dyn AIProvider>>>,), Error = std::convert::Infallible> + Clone {
-    warp::any().map(move || providers.clone())
+/// Builds the provider for one agent slot, routed by the model it should query.
+///
+/// `Route::of` picks the backend: Grok for "x/grok", OpenAI for "openai/*" models
+/// (every model, for a custom `--openai-base-url`) when `--openai-direct` supplied a
+/// key ring, Anthropic for "anthropic/*" models under `--anthropic-direct`, else
+/// OpenRouter.
+fn build_provider(
+    agent_name: &str,
+    model: &str,
+    openai_keys: &Option<Arc<KeyRing>>,
+    settings: &Arc<ProviderSettings>,
+) -> Box<dyn AIProvider> {
+    match (Route::of(model, openai_keys, settings), openai_keys) {
+        (Route::Grok, _) => Box::new(GrokProvider::new(agent_name, settings.clone())),
+        (Route::OpenAI, Some(keys)) => Box::new(OpenAIProvider::new(agent_name, model, keys.clone(), settings.clone())),
+        (Route::Anthropic, _) => Box::new(AnthropicProvider::new(agent_name, model, settings.clone())),
+        _ => Box::new(OpenRouterProvider::new(agent_name, model, settings.clone())),
+    }
 }
 
-// A simple GET route at `/` for basic info
-fn root_handler() -> impl warp::Reply {
-    warp::reply::html("<h1>Truth Terminal MVP</h1><p>POST to /api/validate with {\"statement\":\"...\"}</p>")
+/// What to tell someone who started without a key for any agent, or for an opt-in
+/// extra call: each missing env var with what needs it, and how to set one.
+fn credential_help(missing: &[(String, &'static str)]) -> String {
+    let mut vars: Vec<(&str, Vec<&str>)> = Vec::new();
+    for (model, var) in missing {
+        match vars.iter_mut().find(|(v, _)| v == var) {
+            Some((_, models)) => models.push(model),
+            None => vars.push((var, vec![model])),
+        }
+    }
+    let mut out = String::from("Can't start: these need an API key that is not set.\n\n");
+    for (var, models) in &vars {
+        out.push_str(&format!("  {:<20} {}\n", var, models.join(", ")));
+    }
+    let first = vars.first().map_or("OPENROUTER_API_KEY", |(var, _)| var);
+    out.push_str(&format!(
+        "\nSet one before starting, e.g. `export {}=...`, or add `{}=...` to a .env file here.\n\
+         --openai-direct and --anthropic-direct route models to their own APIs instead.\n",
+        first, first
+    ));
+    out
 }
 
-// ======== MAIN SERVER ========
+// ======== Shared State ========
+
+/// Everything a handler needs besides the ledger, built once in `main`.
+struct AppState {
+    providers: Vec<Box<dyn AIProvider>>,
+    config: Config,
+    /// Set only with `--solana-url`; without it no record is anchored.
+    solana: Option<SolanaClient>,
+    store: Arc<dyn LedgerStore>,
+    /// Shared by every provider; kept here to describe records' provenance.
+    settings: Arc<ProviderSettings>,
+    /// Serializes commits and label updates end to end, including their store writes,
+    /// so the ledger lock itself is never held across disk I/O.
+    commit_lock: tokio::sync::Mutex<()>,
+    /// Batched Solana anchors written so far, oldest first.
+    anchors: RwLock<Vec<Anchor>>,
+    alert: Option<AlertWebhook>,
+    on_commit: Option<CommitHook>,
+    explainer: Option<Explainer>,
+    advocate: Option<DevilsAdvocate>,
+    spend_cap: Option<cost::SpendCap>,
+    /// Per-model reputation, replayed from the ledger at startup and updated on commit.
+    reputation: std::sync::Mutex<reputation::ReputationBook>,
+    /// Transaction and record ids: random, or derived from `--seed`.
+    ids: Box<dyn IdGenerator>,
+    /// Draws `--agents` samples; seeded by `--seed` for reproducible rounds.
+    rng: std::sync::Mutex<StdRng>,
+    /// Set with `--worker-threads`; without it rounds run unbounded and commit as they finish.
+    pool: Option<pool::WorkerPool>,
+    /// Results of the `--verify-interval-mins` checks.
+    integrity: integrity::IntegrityChecks,
+    /// Set with `--audit-log`.
+    audit: Option<audit::AuditLog>,
+    /// When this run started; `--report` covers the records committed since.
+    started: DateTime<Utc>,
+}
+
+impl AppState {
+    /// Appends an `--audit-log` event; `event` is only built when the log is on.
+    fn audit(&self, event: impl FnOnce() -> String) {
+        if let Some(log) = &self.audit {
+            log.record(&event());
+        }
+    }
+
+    fn audit_agents(&self, id: &str, round: usize, votes: &[AIResponse], abstentions: &[Abstention]) {
+        if let Some(log) = &self.audit {
+            for event in audit::agent_results(id, round, votes, abstentions) {
+                log.record(&event);
+            }
+        }
+    }
+}
+
+/// Runs a blocking store call off the async runtime.
+async fn persist<F>(store: &Arc<dyn LedgerStore>, write: F) -> Result<(), StoreError>
+where
+    F: FnOnce(&dyn LedgerStore) -> Result<(), StoreError> + Send + 'static,
+{
+    let store = store.clone();
+    tokio::task::spawn_blocking(move || write(store.as_ref()))
+        .await
+        .expect("store task panicked")
+}
+
+/// Asks every agent in parallel, splitting the results into votes and abstentions.
+async fn poll_agents(
+    agents: &[&dyn AIProvider],
+    text: &str,
+    debate: Option<&str>,
+    progress: Option<&progress::Progress>,
+) -> (Vec<AIResponse>, Vec<Abstention>) {
+    let futures = agents.iter().map(|agent| async {
+        if let Some(progress) = progress {
+            progress.querying(agent.agent_name(), agent.model());
+        }
+        // Whatever a backend or a replayed fixture returned, no terminal escape in it
+        // reaches the record.
+        let result = match agent.validate(text, debate).await {
+            Ok(vote) => Ok(AIResponse {
+                system_fingerprint: vote.system_fingerprint.as_deref().map(input::sanitize_statement),
+                rationale: vote.rationale.as_deref().map(input::sanitize_statement),
+                ..vote
+            }),
+            Err(e) => {
+                tracing::warn!("{} did not vote: {}", agent.model(), e);
+                Err(Abstention {
+                    agent_name: agent.agent_name().to_string(),
+                    model: agent.model().to_string(),
+                    reason: input::sanitize_statement(&e.to_string()),
+                    refusal: e.is_refusal(),
+                })
+            }
+        };
+        if let Some(progress) = progress {
+            match &result {
+                Ok(vote) => progress.voted(vote),
+                Err(abstention) => progress.abstained(abstention),
+            }
+        }
+        result
+    });
+    let mut responses = Vec::new();
+    let mut abstentions = Vec::new();
+    for result in join_all(futures).await {
+        match result {
+            Ok(response) => responses.push(response),
+            Err(abstention) => abstentions.push(abstention),
+        }
+    }
+    (responses, abstentions)
+}
+
+// ======== Agent Assignment ========
+
+/// Picks the providers that vote this round.
+///
+/// With `--agents N` below the pool size, N distinct models are drawn at random,
+/// weighted by `--sample-weight`; otherwise every model votes. The selection is
+/// made once per round and kept in pool order, and each vote carries its model name.
+fn assign_agents<'a>(
+    providers: &'a [Box<dyn AIProvider>],
+    config: &Config,
+    rng: &mut impl rand::Rng,
+) -> Vec<&'a dyn AIProvider> {
+    let count = config.agents.unwrap_or(providers.len());
+    if count >= providers.len() {
+        return providers.iter().map(|p| p.as_ref()).collect();
+    }
+
+    let pool: Vec<usize> = (0..providers.len()).collect();
+    let mut indices: Vec<usize> = pool
+        .choose_multiple_weighted(rng, count, |&i| {
+            config.sample_weight(providers[i].model())
+        })
+        .expect("sample weights are validated as positive")
+        .copied()
+        .collect();
+    indices.sort_unstable();
+    indices.into_iter().map(|i| providers[i].as_ref()).collect()
+}
+
+// ======== Handler Logic ========
+
+/// JSON `{"error": ...}` body with the given status.
+fn error_reply(status: warp::http::StatusCode, message: String) -> warp::reply::Response {
+    warp::reply::with_status(warp::reply::json(&json!({ "error": message })), status).into_response()
+}
+
+/// Why a round recorded nothing, with the HTTP status it maps to.
+#[derive(Debug)]
+struct RoundError {
+    status: warp::http::StatusCode,
+    message: String,
+}
+
+impl RoundError {
+    fn new(status: warp::http::StatusCode, message: String) -> Self {
+        Self { status, message }
+    }
+}
+
+/// Validates one statement end to end: polls this round's agents, forms consensus,
+/// and commits and persists the record. Shared by the HTTP API and the console,
+/// which passes `progress` to print each agent's result as it arrives. With a
+/// `claim`, agents are asked about its rendered statement instead of `statement`.
+async fn run_round(
+    state: &AppState,
+    statement: &str,
+    claim: Option<&Claim>,
+    progress: Option<&progress::Progress>,
+) -> Result<ValidateResponse, RoundError> {
+    let result = play_round(state, statement, claim, progress).await;
+    if let Err(e) = &result {
+        let submitted = claim.map_or_else(|| statement.to_string(), Claim::triple);
+        state.audit(|| format!("rejected {}: {}", audit::quote(&submitted), e.message));
+    }
+    result
+}
+
+async fn play_round(
+    state: &AppState,
+    statement: &str,
+    claim: Option<&Claim>,
+    progress: Option<&progress::Progress>,
+) -> Result<ValidateResponse, RoundError> {
+    let config = &state.config;
+    let profile = state.settings.profile.as_ref();
+    let round_started = Instant::now();
+    // 1. Create a transaction from the sanitized statement or claim
+    let claim = match claim.map(Claim::sanitized) {
+        Some(None) => {
+            return Err(RoundError::new(
+                warp::http::StatusCode::BAD_REQUEST,
+                "claim needs a non-empty subject, predicate and object".to_string(),
+            ))
+        }
+        Some(claim) => claim,
+        None => None,
+    };
+    let content = match &claim {
+        Some(claim) => claim.statement(),
+        None => input::sanitize_statement(statement),
+    };
+    if content.is_empty() {
+        return Err(RoundError::new(
+            warp::http::StatusCode::BAD_REQUEST,
+            "statement is empty".to_string(),
+        ));
+    }
+    let transaction = Transaction {
+        id: state.ids.next_id().to_string(),
+        content,
+        claim,
+    };
+    state.audit(|| format!("submit {} {}", transaction.id, audit::quote(&transaction.content)));
+    if let Some(cap) = &state.spend_cap {
+        cap.reserve(cost::repeat_cost(&transaction.content, config))
+            .map_err(|e| RoundError::new(warp::http::StatusCode::PAYMENT_REQUIRED, e.to_string()))?;
+    }
+    let injection = config
+        .flag_injection
+        .then(|| guard::injection_match(&transaction.content))
+        .flatten();
+    if let Some(pattern) = injection {
+        tracing::warn!("statement {} looks like a prompt injection ('{}')", transaction.id, pattern);
+    }
+
+    // Under `--worker-threads`, wait for a worker; the slot also fixes commit order.
+    let slot = match &state.pool {
+        Some(pool) => Some(pool.enter().await),
+        None => None,
+    };
+
+    // 2. Query this round's agents in parallel
+    let agents = assign_agents(&state.providers, config, &mut *state.rng.lock().unwrap());
+    let (mut responses, mut abstentions) = poll_agents(&agents, &transaction.content, None, progress).await;
+    state.audit_agents(&transaction.id, 1, &responses, &abstentions);
+    if let Some(e) = first_failure(config, &abstentions) {
+        return Err(e);
+    }
+
+    // 2b. Debate rounds: whoever voted sees the anonymized votes and votes again.
+    let mut debate_rounds = Vec::new();
+    for round in 2..=config.rounds {
+        if responses.is_empty() {
+            break;
+        }
+        let summary = debate_summary(round - 1, &responses);
+        let voters: Vec<&dyn AIProvider> = agents
+            .iter()
+            .copied()
+            .filter(|a| responses.iter().any(|r| r.agent_name == a.agent_name()))
+            .collect();
+        let (next, dropped) = poll_agents(&voters, &transaction.content, Some(&summary), progress).await;
+        state.audit_agents(&transaction.id, round, &next, &dropped);
+        if let Some(e) = first_failure(config, &dropped) {
+            return Err(e);
+        }
+        debate_rounds.push(std::mem::replace(&mut responses, next));
+        abstentions.extend(dropped);
+    }
+
+    // A verdict from a handful of survivors of an outage isn't a consensus.
+    if responses.len() < config.min_responses {
+        return Err(RoundError::new(
+            warp::http::StatusCode::SERVICE_UNAVAILABLE,
+            format!(
+                "only {} of {} agents responded (minimum {}); nothing recorded",
+                responses.len(),
+                agents.len(),
+                config.min_responses
+            ),
+        ));
+    }
+
+    // 3. Tally the votes and build details string from responses
+    let started = Instant::now();
+    let result = {
+        let reputation = state.reputation.lock().unwrap();
+        let rules = consensus::Rules {
+            min_providers: config.quorum_providers,
+            required: &config.required_providers,
+            quorum: config.quorum,
+            veto: &config.veto_models,
+            required_yes: &config.required_yes_models,
+            trust: config.weights.as_ref(),
+            min_truth_score: config.min_truth_score,
+            weight: &|model| reputation.weight(model),
+        };
+        let tally = match config.error_policy {
+            ErrorPolicy::No => Cow::Owned(consensus::with_failures_as_no(&responses, &abstentions)),
+            ErrorPolicy::Abstain | ErrorPolicy::Fail => Cow::Borrowed(responses.as_slice()),
+        };
+        consensus::apply(config.consensus, &tally, &rules)
+    };
+    profile::record(profile, Stage::Consensus, started);
+    let mut details = String::new();
+    if let Some(pattern) = injection {
+        details.push_str(&format!("Flagged: matches prompt-injection pattern '{}'\n", pattern));
+    }
+    for (i, round) in debate_rounds.iter().enumerate() {
+        let yes = round.iter().filter(|v| v.is_valid).count();
+        details.push_str(&format!("Round {}: {} yes, {} no\n", i + 1, yes, round.len() - yes));
+    }
+    for r in &responses {
+        let vote_str = if r.is_valid { "yes" } else { "no" };
+        match &r.rationale {
+            Some(rationale) => details.push_str(&format!("{} voted: {} ({})\n", r.agent_name, vote_str, rationale)),
+            None => details.push_str(&format!("{} voted: {}\n", r.agent_name, vote_str)),
+        }
+    }
+    for a in &abstentions {
+        details.push_str(&format!("{} abstained: {}\n", a.agent_name, a.reason));
+    }
+    details.push_str(&format!("\nConsensus: {} ({})\n", result.consensus, result.details));
+    details.push_str(&format!("\nRecorded at {}\n", Utc::now().to_rfc3339()));
+
+    // An unreachable cluster leaves the record unanchored rather than claiming slot 0.
+    let started = Instant::now();
+    let solana_slot = match &state.solana {
+        Some(solana) => solana
+            .get_slot()
+            .await
+            .inspect_err(|e| tracing::warn!("{}", e))
+            .ok(),
+        None => None,
+    };
+    if state.solana.is_some() {
+        profile::record(profile, Stage::SolanaRpc, started);
+    }
+    match solana_slot {
+        Some(slot) => details.push_str(&format!("Solana: observed at slot {}\n", slot)),
+        None => details.push_str("Solana: not anchored\n"),
+    }
+
+    // A rationale that can't be produced leaves the record without one.
+    let consensus_rationale = match &state.explainer {
+        Some(explainer) => explainer
+            .explain(&transaction.content, result.consensus, &responses)
+            .await
+            .inspect_err(|e| tracing::warn!("no consensus rationale: {}", e))
+            .ok(),
+        None => None,
+    };
+
+    // Like the rationale, a challenge that fails leaves the record without one.
+    let devils_advocate = match &state.advocate {
+        Some(advocate) => advocate
+            .challenge(&transaction.content, result.consensus)
+            .await
+            .inspect_err(|e| tracing::warn!("no devil's advocate challenge: {}", e))
+            .ok(),
+        None => None,
+    };
+
+    // 4. Create a record and add it to our ledger
+    let record = Record {
+        id: state.ids.next_id().to_string(),
+        transaction,
+        consensus: result.consensus,
+        truth_score: result.truth_score,
+        details,
+        votes: responses.clone(),
+        abstentions,
+        debate_rounds,
+        provenance: Some(Provenance::capture(config, &state.settings, &agents)),
+        consensus_rationale,
+        devils_advocate,
+        majority_agents: result.majority_agents,
+        dissenting_agents: result.dissenting_agents,
+        vetoed_by: result.vetoed_by,
+        timestamp: Utc::now(),
+        solana_slot,
+        solana_signature: None,
+        label: None,
+        pinned: false,
+        human_notes: Vec::new(),
+        prev_hash: String::new(),
+        hash: String::new(),
+    };
+    if let Some(slot) = &slot {
+        slot.turn().await;
+    }
+    let commit_guard = state.commit_lock.lock().await;
+    let started = Instant::now();
+    let record = match LEDGER.write().await.commit(record) {
+        Ok(record) => record.clone(),
+        Err(e) => {
+            return Err(RoundError::new(
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("ledger rolled back: {}", e),
+            ))
+        }
+    };
+    // Still the head: every other commit waits on `commit_lock`.
+    let stored = record.clone();
+    if let Err(e) = persist(&state.store, move |store| store.append(&stored)).await {
+        LEDGER.write().await.revert_head();
+        return Err(RoundError::new(
+            warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("record not persisted: {}", e),
+        ));
+    }
+    profile::record(profile, Stage::Persist, started);
+    let record = match state.solana.as_ref().filter(|_| state.config.anchor) {
+        Some(solana) => anchor_head(state, solana, record).await,
+        None => record,
+    };
+    state.reputation.lock().unwrap().observe(&record);
+    state.audit(|| {
+        let verdict = if record.consensus { "yes" } else { "no" };
+        let slot = match (&record.solana_signature, record.solana_slot) {
+            (Some(signature), _) => format!("anchored in solana transaction {}", signature),
+            (None, Some(slot)) => format!("observed at solana slot {}", slot),
+            (None, None) => "not anchored".to_string(),
+        };
+        format!(
+            "consensus {}: {} (truth score {:.0}); record {} committed, hash {}, {}",
+            record.transaction.id, verdict, record.truth_score, record.id, record.hash, slot
+        )
+    });
+    drop(commit_guard);
+    drop(slot);
+
+    if let Some(hook) = &state.on_commit {
+        hook.notify(&record);
+    }
+    if !record.consensus {
+        if let Some(alert) = &state.alert {
+            alert.notify(&record);
+        }
+    }
+
+    profile::record(profile, Stage::Round, round_started);
+    Ok(ValidateResponse {
+        record,
+        ai_responses: responses,
+    })
+}
+
+/// `--anchor`: writes the just-committed head `record` to Solana and stores the
+/// transaction's signature on it. The record is already committed, so a memo that
+/// fails (an unfunded payer, an unreachable cluster) leaves it unanchored.
+async fn anchor_head(state: &AppState, solana: &SolanaClient, record: Record) -> Record {
+    let started = Instant::now();
+    let sent = anchor::anchor_record(solana, &record).await;
+    profile::record(state.settings.profile.as_ref(), Stage::SolanaRpc, started);
+    let signature = match sent {
+        Ok(signature) => signature,
+        Err(e) => {
+            tracing::warn!("record {} not anchored: {}", record.id, e);
+            return record;
+        }
+    };
+    // Still the head: the caller holds `commit_lock`.
+    let index = LEDGER.read().await.records().len();
+    let anchored = LEDGER
+        .write()
+        .await
+        .set_solana_signature(index, Some(signature.clone()))
+        .expect("the head exists")
+        .clone();
+    let stored = anchored.clone();
+    if let Err(e) = persist(&state.store, move |store| store.update(&stored)).await {
+        tracing::warn!("anchor {} of record {} not persisted: {}", signature, record.id, e);
+        LEDGER.write().await.set_solana_signature(index, None);
+        return record;
+    }
+    tracing::info!("anchored record {} in {}", record.id, signature);
+    anchored
+}
+
+async fn handle_validate(
+    req: ValidateRequest,
+    state: Arc<AppState>,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    let (statement, claim) = match req.parts() {
+        Ok(parts) => parts,
+        Err(message) => return Ok(error_reply(warp::http::StatusCode::BAD_REQUEST, message)),
+    };
+    match run_round(&state, statement, claim, None).await {
+        Ok(response) => Ok(warp::reply::json(&response).into_response()),
+        Err(e) => Ok(error_reply(e.status, e.message)),
+    }
+}
+
+async fn handle_ledger(query: LedgerQuery) -> Result<impl warp::Reply, warp::Rejection> {
+    let filter = RecordFilter { since: query.since };
+    let ledger = LEDGER.read().await;
+    Ok(warp::reply::json(&ledger.query(&filter)))
+}
+
+/// The most recently committed record, or 404 on an empty ledger.
+async fn handle_last() -> Result<warp::reply::Response, warp::Rejection> {
+    let ledger = LEDGER.read().await;
+    match ledger.records().last() {
+        Some(record) => Ok(warp::reply::json(record).into_response()),
+        None => Ok(error_reply(warp::http::StatusCode::NOT_FOUND, "ledger is empty".to_string())),
+    }
+}
+
+/// Updates an unhashed annotation (`get`/`set`) on the record at a 1-based ledger
+/// index with `update`, applied to its current value, and persists it, restoring the
+/// previous value if the store write fails.
+async fn annotate<V: Clone>(
+    state: &AppState,
+    index: usize,
+    update: impl FnOnce(V) -> V,
+    get: fn(&Record) -> V,
+    set: fn(&mut Ledger, usize, V) -> Option<&Record>,
+    action: &str,
+) -> warp::reply::Response {
+    let _commit = state.commit_lock.lock().await;
+    let (record, previous) = {
+        let mut ledger = LEDGER.write().await;
+        let Some(previous) = index.checked_sub(1).and_then(|i| ledger.records().get(i)).map(get) else {
+            return error_reply(
+                warp::http::StatusCode::NOT_FOUND,
+                format!("no record at index {}", index),
+            );
+        };
+        let record = set(&mut ledger, index, update(previous.clone())).expect("index checked above").clone();
+        (record, previous)
+    };
+    let stored = record.clone();
+    if let Err(e) = persist(&state.store, move |store| store.update(&stored)).await {
+        // Keep memory in step with what the store still holds.
+        set(&mut *LEDGER.write().await, index, previous);
+        return error_reply(warp::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+    }
+    state.audit(|| format!("record {} ({}) {}", index, record.id, action));
+    warp::reply::json(&record).into_response()
+}
+
+/// Sets the ground-truth label of the record at a 1-based ledger index.
+async fn handle_label(
+    index: usize,
+    req: LabelRequest,
+    state: Arc<AppState>,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    let action = format!("labeled {}", req.label);
+    Ok(annotate(&state, index, |_| Some(req.label), |r| r.label, Ledger::set_label, &action).await)
+}
+
+/// Appends a human note to the record at a 1-based ledger index.
+async fn handle_note(
+    index: usize,
+    req: NoteRequest,
+    state: Arc<AppState>,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    let text = input::sanitize_statement(&req.text);
+    if text.is_empty() {
+        return Ok(error_reply(warp::http::StatusCode::BAD_REQUEST, "note is empty".to_string()));
+    }
+    let action = format!("noted {}", audit::quote(&text));
+    let note = Note { at: Utc::now(), text };
+    let append = |mut notes: Vec<Note>| {
+        notes.push(note);
+        notes
+    };
+    Ok(annotate(&state, index, append, |r| r.human_notes.clone(), Ledger::set_notes, &action).await)
+}
+
+/// The workspaces under `--workspaces-dir` and the one this server is running.
+async fn handle_workspaces(state: Arc<AppState>) -> Result<warp::reply::Response, warp::Rejection> {
+    match workspace::list(&state.config.workspaces_dir) {
+        Ok(names) => Ok(warp::reply::json(&json!({
+            "current": state.config.workspace,
+            "workspaces": names,
+        }))
+        .into_response()),
+        Err(e) => Ok(error_reply(
+            warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("cannot list workspaces: {}", e),
+        )),
+    }
+}
+
+/// `POST /rpc`, only with `--jsonrpc`. Notifications alone get 204.
+async fn handle_jsonrpc(body: warp::hyper::body::Bytes, state: Arc<AppState>) -> Result<warp::reply::Response, warp::Rejection> {
+    if !state.config.jsonrpc {
+        return Err(warp::reject::not_found());
+    }
+    match jsonrpc::handle(&state, &body).await {
+        Some(response) => Ok(warp::reply::json(&response).into_response()),
+        None => Ok(warp::http::StatusCode::NO_CONTENT.into_response()),
+    }
+}
+
+/// Creates an empty workspace; the server keeps running the current one, and a
+/// restart with `--workspace NAME` switches to it.
+async fn handle_new_workspace(
+    req: WorkspaceRequest,
+    state: Arc<AppState>,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    let name = match workspace::parse_name(req.name.trim()) {
+        Ok(name) => name,
+        Err(e) => return Ok(error_reply(warp::http::StatusCode::BAD_REQUEST, e)),
+    };
+    let dir = &state.config.workspaces_dir;
+    match workspace::create(dir, &name) {
+        Ok(()) => {
+            let store_path = workspace::store_path(dir, &name, state.config.store);
+            let created = json!({ "name": name, "store_path": store_path });
+            Ok(warp::reply::with_status(warp::reply::json(&created), warp::http::StatusCode::CREATED).into_response())
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(error_reply(
+            warp::http::StatusCode::CONFLICT,
+            format!("workspace '{}' already exists", name),
+        )),
+        Err(e) => Ok(error_reply(
+            warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("cannot create workspace '{}': {}", name, e),
+        )),
+    }
+}
+
+/// Pins (PUT) or unpins (DELETE) the record at a 1-based ledger index.
+async fn handle_pin(
+    index: usize,
+    method: warp::http::Method,
+    state: Arc<AppState>,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    let pinned = method == warp::http::Method::PUT;
+    let action = if pinned { "pinned" } else { "unpinned" };
+    Ok(annotate(&state, index, |_| pinned, |r| r.pinned, Ledger::set_pinned, action).await)
+}
+
+/// One statement rerun by `/api/retry-failed`.
+#[derive(Serialize)]
+struct Retry {
+    /// 1-based position of the failed record.
+    index: usize,
+    statement: String,
+    /// Position and verdict of the new record; absent when the round recorded nothing.
+    new_index: Option<usize>,
+    consensus: Option<bool>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RetryReport {
+    retried: usize,
+    /// Retries that now reach consensus.
+    changed: usize,
+    /// Failed statements left unasked because `--max-cost-usd` ran out.
+    skipped: usize,
+    results: Vec<Retry>,
+}
+
+/// Reruns each statement whose latest record failed consensus through the current
+/// pool and settings, appending a new record for each; the old ones stay as they are.
+async fn handle_retry_failed(state: Arc<AppState>) -> Result<impl warp::Reply, warp::Rejection> {
+    let pending: Vec<(usize, Transaction)> = {
+        let ledger = LEDGER.read().await;
+        let records = ledger.records();
+        analytics::latest_failed(records)
+            .into_iter()
+            .map(|i| (i + 1, records[i].transaction.clone()))
+            .collect()
+    };
+    let mut results = Vec::new();
+    for (index, transaction) in &pending {
+        if state.spend_cap.as_ref().is_some_and(|cap| cap.halted()) {
+            break;
+        }
+        let outcome = run_round(&state, &transaction.content, transaction.claim.as_ref(), None).await;
+        let (new_index, consensus, error) = match outcome {
+            Ok(response) => {
+                let position = LEDGER.read().await.records().iter().position(|r| r.id == response.record.id);
+                (position.map(|i| i + 1), Some(response.record.consensus), None)
+            }
+            Err(e) => (None, None, Some(e.message)),
+        };
+        results.push(Retry {
+            index: *index,
+            statement: transaction.content.clone(),
+            new_index,
+            consensus,
+            error,
+        });
+    }
+    Ok(warp::reply::json(&RetryReport {
+        retried: results.len(),
+        changed: results.iter().filter(|r| r.consensus == Some(true)).count(),
+        skipped: pending.len() - results.len(),
+        results,
+    }))
+}
+
+/// Removes unpinned records older than `days` days and re-links the rest.
+async fn handle_purge(
+    req: PurgeRequest,
+    state: Arc<AppState>,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    match retention::purge(&state, req.days).await {
+        Ok(summary) => Ok(warp::reply::json(&summary).into_response()),
+        Err(e @ retention::PurgeError::AppendOnly) => Ok(error_reply(warp::http::StatusCode::FORBIDDEN, e.to_string())),
+        Err(e) => Ok(error_reply(warp::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+async fn handle_pinned() -> Result<impl warp::Reply, warp::Rejection> {
+    let ledger = LEDGER.read().await;
+    let pinned: Vec<&Record> = ledger.records().iter().filter(|r| r.pinned).collect();
+    Ok(warp::reply::json(&pinned))
+}
+
+/// High-agreement rate over the last `window` records (default 20) against the
+/// window before; `threshold` (default 1.0, unanimous) is the majority share needed.
+async fn handle_trend(query: TrendQuery, state: Arc<AppState>) -> Result<warp::reply::Response, warp::Rejection> {
+    let window = query.window.unwrap_or(20);
+    let threshold = query.threshold.unwrap_or(1.0);
+    if window == 0 || !(0.5..=1.0).contains(&threshold) {
+        return Ok(error_reply(
+            warp::http::StatusCode::BAD_REQUEST,
+            "window must be positive and threshold within 0.5–1.0".to_string(),
+        ));
+    }
+    let ledger = LEDGER.read().await;
+    let trend = analytics::trend(ledger.records(), window, threshold, state.config.half_life_days);
+    Ok(warp::reply::json(&trend).into_response())
+}
+
+/// Reruns `strategy` over every record's stored votes, without querying any model,
+/// and lists the records whose verdict would change. Quorum and truth-score settings
+/// default to the server's own.
+async fn handle_whatif(query: WhatIfQuery, state: Arc<AppState>) -> Result<warp::reply::Response, warp::Rejection> {
+    let Ok(strategy) = ConsensusStrategy::from_str(&query.strategy, true) else {
+        return Ok(error_reply(
+            warp::http::StatusCode::BAD_REQUEST,
+            format!("unknown strategy '{}' (expected majority, provider-quorum, weighted-majority, truth-score or veto)", query.strategy),
+        ));
+    };
+    let ledger = LEDGER.read().await;
+    let reputation = state.reputation.lock().unwrap();
+    let rules = consensus::Rules {
+        min_providers: query.quorum_providers.unwrap_or(state.config.quorum_providers).max(1),
+        required: &state.config.required_providers,
+        quorum: state.config.quorum,
+        veto: &state.config.veto_models,
+        required_yes: &state.config.required_yes_models,
+        trust: state.config.weights.as_ref(),
+        min_truth_score: query.min_truth_score.unwrap_or(state.config.min_truth_score),
+        weight: &|model| reputation.weight(model),
+    };
+    let report = analytics::what_if(ledger.records(), &query.strategy, |record| {
+        consensus::apply(strategy, &record.tally(), &rules).consensus
+    });
+    Ok(warp::reply::json(&report).into_response())
+}
+
+/// Resamples per robustness report, unless the request asks for another count.
+const DEFAULT_RESAMPLES: usize = 1000;
+const MAX_RESAMPLES: usize = 100_000;
+
+/// Bootstraps `record`'s votes under the rule it was decided by: its provenance's
+/// strategy and thresholds, or the current `--consensus` settings for records that
+/// predate provenance. Shared by `/api/robustness` and the viewer.
+fn robustness_report(
+    index: usize,
+    record: &Record,
+    config: &Config,
+    weight: &dyn Fn(&str) -> f64,
+    resamples: usize,
+    seed: Option<u64>,
+) -> analytics::Robustness {
+    let provenance = record.provenance.as_ref();
+    let strategy = provenance
+        .and_then(|p| ConsensusStrategy::from_str(&p.consensus, true).ok())
+        .unwrap_or(config.consensus);
+    let rules = consensus::Rules {
+        min_providers: provenance
+            .and_then(|p| p.quorum_providers)
+            .unwrap_or(config.quorum_providers)
+            .max(1),
+        required: provenance.map_or(&config.required_providers, |p| &p.required_providers),
+        quorum: provenance.map_or(config.quorum, |p| p.quorum),
+        veto: provenance.map_or(&config.veto_models, |p| &p.veto_models),
+        required_yes: provenance.map_or(&config.required_yes_models, |p| &p.required_yes_models),
+        trust: provenance.map_or(config.weights.as_ref(), |p| p.trust_weights.as_ref()),
+        min_truth_score: provenance.and_then(|p| p.min_truth_score).unwrap_or(config.min_truth_score),
+        weight,
+    };
+    let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+    analytics::robustness(index, record, resamples, &mut rng, |votes| {
+        consensus::apply(strategy, votes, &rules).consensus
+    })
+}
+
+async fn handle_robustness(
+    index: usize,
+    query: RobustnessQuery,
+    state: Arc<AppState>,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    let resamples = query.resamples.unwrap_or(DEFAULT_RESAMPLES);
+    if !(1..=MAX_RESAMPLES).contains(&resamples) {
+        return Ok(error_reply(
+            warp::http::StatusCode::BAD_REQUEST,
+            format!("resamples must be between 1 and {}", MAX_RESAMPLES),
+        ));
+    }
+    let ledger = LEDGER.read().await;
+    let Some(record) = index.checked_sub(1).and_then(|i| ledger.records().get(i)) else {
+        return Ok(error_reply(
+            warp::http::StatusCode::NOT_FOUND,
+            format!("no record at index {}", index),
+        ));
+    };
+    let reputation = state.reputation.lock().unwrap();
+    let weight = |model: &str| reputation.weight(model);
+    let report = robustness_report(index, record, &state.config, &weight, resamples, query.seed);
+    Ok(warp::reply::json(&report).into_response())
+}
+
+/// JSON Schema for each type in the ledger format, keyed by type name.
+fn ledger_schema() -> serde_json::Value {
+    json!({
+        "Record": schemars::schema_for!(Record),
+        "Transaction": schemars::schema_for!(Transaction),
+        "AIResponse": schemars::schema_for!(AIResponse),
+        "ConsensusResult": schemars::schema_for!(consensus::ConsensusResult),
+    })
+}
+
+async fn handle_schema() -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(&ledger_schema()))
+}
+
+async fn handle_reputation(state: Arc<AppState>) -> Result<impl warp::Reply, warp::Rejection> {
+    let ranked = state.reputation.lock().unwrap().ranked();
+    Ok(warp::reply::json(&ranked))
+}
+
+async fn handle_calibration() -> Result<impl warp::Reply, warp::Rejection> {
+    let ledger = LEDGER.read().await;
+    Ok(warp::reply::json(&analytics::calibration(ledger.records())))
+}
+
+async fn handle_accuracy() -> Result<impl warp::Reply, warp::Rejection> {
+    let ledger = LEDGER.read().await;
+    Ok(warp::reply::json(&analytics::accuracy(ledger.records())))
+}
+
+/// Records whose statement matches `q`, best first when the store has a full-text
+/// index (SQLite), otherwise in ledger order.
+async fn handle_search(
+    query: SearchQuery,
+    state: Arc<AppState>,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    let limit = query.limit.unwrap_or(50);
+    let store = state.store.clone();
+    let (q, mode) = (query.q.clone(), query.mode);
+    let ranked = tokio::task::spawn_blocking(move || store.search(&q, mode, limit))
+        .await
+        .expect("store task panicked");
+    let ledger = LEDGER.read().await;
+    let records: Vec<&Record> = match ranked {
+        Ok(Some(ids)) => ids
+            .iter()
+            .filter_map(|id| ledger.records().iter().find(|r| &r.id == id))
+            .collect(),
+        Ok(None) => ledger
+            .records()
+            .iter()
+            .filter(|r| store::scan_matches(&r.transaction.content, &query.q, query.mode))
+            .take(limit)
+            .collect(),
+        Err(e) => return Ok(error_reply(warp::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    };
+    Ok(warp::reply::json(&records).into_response())
+}
+
+/// Distinct models that have voted or abstained in the ledger, with counts.
+async fn handle_models() -> Result<impl warp::Reply, warp::Rejection> {
+    let ledger = LEDGER.read().await;
+    Ok(warp::reply::json(&analytics::model_usage(ledger.records())))
+}
+
+/// Re-renders the stored ledger as text, so display settings can change after the fact.
+async fn handle_render(query: RenderQuery) -> Result<impl warp::Reply, warp::Rejection> {
+    let defaults = RenderOptions::default();
+    let opts = RenderOptions {
+        color: query.color.unwrap_or(defaults.color),
+        verbose: query.verbose.unwrap_or(defaults.verbose),
+    };
+    let ledger = LEDGER.read().await;
+    Ok(render::render_ledger(ledger.records(), &opts))
+}
+
+/// Two records side by side, by 1-based ledger index.
+async fn handle_compare(left: usize, right: usize, query: RenderQuery) -> Result<warp::reply::Response, warp::Rejection> {
+    let opts = RenderOptions {
+        color: query.color.unwrap_or(RenderOptions::default().color),
+        verbose: false,
+    };
+    let ledger = LEDGER.read().await;
+    let records = ledger.records();
+    let get = |n: usize| n.checked_sub(1).and_then(|i| records.get(i)).map(|record| (n, record));
+    match (get(left), get(right)) {
+        (Some(left), Some(right)) => Ok(render::render_compare(left, right, &opts).into_response()),
+        _ => Ok(error_reply(
+            warp::http::StatusCode::NOT_FOUND,
+            format!("no record at index {}", if get(left).is_none() { left } else { right }),
+        )),
+    }
+}
+
+/// How the record at a 1-based ledger index reached its verdict, as text.
+async fn handle_why(index: usize, query: RenderQuery) -> Result<warp::reply::Response, warp::Rejection> {
+    let opts = RenderOptions {
+        color: query.color.unwrap_or(RenderOptions::default().color),
+        verbose: false,
+    };
+    let ledger = LEDGER.read().await;
+    match index.checked_sub(1).and_then(|i| ledger.records().get(i)) {
+        Some(record) => Ok(render::render_why(index, record, &opts).into_response()),
+        None => Ok(error_reply(
+            warp::http::StatusCode::NOT_FOUND,
+            format!("no record at index {}", index),
+        )),
+    }
+}
+
+/// The tally behind `/chart` in the viewer, as JSON for a frontend to draw.
+async fn handle_chart(index: usize) -> Result<warp::reply::Response, warp::Rejection> {
+    let ledger = LEDGER.read().await;
+    match index.checked_sub(1).and_then(|i| ledger.records().get(i)) {
+        Some(record) => Ok(warp::reply::json(&render::vote_chart(record)).into_response()),
+        None => Ok(error_reply(
+            warp::http::StatusCode::NOT_FOUND,
+            format!("no record at index {}", index),
+        )),
+    }
+}
+
+/// Every batched Solana anchor, oldest first.
+async fn handle_anchors(state: Arc<AppState>) -> Result<impl warp::Reply, warp::Rejection> {
+    let anchors = state.anchors.read().await;
+    Ok(warp::reply::json(&*anchors))
+}
+
+#[derive(Deserialize)]
+struct AnchorStatusQuery {
+    limit: Option<usize>,
+}
+
+/// The most recent anchors (default 10) with their signature, confirmation and
+/// slot, polling the cluster for any not yet finalized.
+async fn handle_anchor_status(
+    query: AnchorStatusQuery,
+    state: Arc<AppState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let statuses = anchor::status(&state, query.limit.unwrap_or(10)).await;
+    Ok(warp::reply::json(&statuses))
+}
+
+#[derive(Serialize)]
+struct VerifyReport {
+    valid: bool,
+    records: usize,
+    error: Option<String>,
+    anomalies: Vec<ledger::TimestampAnomaly>,
+}
+
+/// Re-verifies the hash chain and lists timestamp anomalies.
+fn verify_report(records: &[Record]) -> VerifyReport {
+    let error = ledger::verify_chain(records).err().map(|e| e.to_string());
+    VerifyReport {
+        valid: error.is_none(),
+        records: records.len(),
+        error,
+        anomalies: ledger::timestamp_anomalies(records),
+    }
+}
+
+async fn handle_verify() -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(&verify_report(LEDGER.read().await.records())))
+}
+
+#[derive(Serialize)]
+struct ProviderStatus {
+    agent_name: String,
+    model: String,
+    breaker: Option<BreakerState>,
+}
+
+#[derive(Serialize)]
+struct SolanaStatus {
+    reachable: bool,
+    slot: Option<u64>,
+    error: Option<String>,
+}
+
+/// Operational state served by `/api/status` and `/health`.
+#[derive(Serialize)]
+struct StatusReport {
+    ledger_records: usize,
+    chain_verified: bool,
+    last_record_at: Option<DateTime<Utc>>,
+    providers: Vec<ProviderStatus>,
+    /// `None` when no Solana RPC is configured.
+    solana: Option<SolanaStatus>,
+    /// Periodic `--verify-interval-mins` checks so far.
+    integrity: integrity::IntegritySnapshot,
+}
+
+async fn status_report(state: &AppState) -> StatusReport {
+    let (ledger_records, chain_verified, last_record_at) = {
+        let chain = LEDGER.read().await;
+        let records = chain.records();
+        (
+            records.len(),
+            ledger::verify_chain(records).is_ok(),
+            records.last().map(|r| r.timestamp),
+        )
+    };
+    let providers = state
+        .providers
+        .iter()
+        .map(|p| ProviderStatus {
+            agent_name: p.agent_name().to_string(),
+            model: p.model().to_string(),
+            breaker: p.breaker_state(),
+        })
+        .collect();
+    let solana = match &state.solana {
+        Some(solana) => Some(match solana.get_slot().await {
+            Ok(slot) => SolanaStatus { reachable: true, slot: Some(slot), error: None },
+            Err(e) => SolanaStatus { reachable: false, slot: None, error: Some(e.to_string()) },
+        }),
+        None => None,
+    };
+    StatusReport {
+        ledger_records,
+        chain_verified,
+        last_record_at,
+        providers,
+        solana,
+        integrity: state.integrity.snapshot(),
+    }
+}
+
+async fn handle_status(state: Arc<AppState>) -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(&status_report(&state).await))
+}
+
+/// Like `/api/status`, but 503 when the chain fails to verify, for health checks.
+async fn handle_health(state: Arc<AppState>) -> Result<impl warp::Reply, warp::Rejection> {
+    let report = status_report(&state).await;
+    let status = if report.chain_verified {
+        warp::http::StatusCode::OK
+    } else {
+        warp::http::StatusCode::SERVICE_UNAVAILABLE
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&report), status))
+}
+
+async fn handle_head() -> Result<impl warp::Reply, warp::Rejection> {
+    let ledger = LEDGER.read().await;
+    Ok(warp::reply::json(&HeadReport {
+        records: ledger.records().len(),
+        head_hash: ledger.head_hash(),
+        merkle_root: ledger.merkle_root(),
+    }))
+}
+
+/// The Merkle proof for the record at a 1-based index, or 404.
+async fn handle_prove(index: usize) -> Result<warp::reply::Response, warp::Rejection> {
+    match LEDGER.read().await.merkle_proof(index) {
+        Some(proof) => Ok(warp::reply::json(&proof).into_response()),
+        None => Ok(error_reply(
+            warp::http::StatusCode::NOT_FOUND,
+            format!("no record at index {}", index),
+        )),
+    }
+}
+
+/// Checks a proof against its own claimed root and leaf; the ledger isn't consulted.
+async fn handle_check_proof(proof: ledger::MerkleProof) -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(&json!({ "valid": proof.verifies() })))
+}
+
+async fn handle_export() -> Result<impl warp::Reply, warp::Rejection> {
+    let ledger = LEDGER.read().await;
+    let exported_at = Utc::now();
+    let snapshot = LedgerSnapshot {
+        exported_at,
+        record_count: ledger.records().len(),
+        consensus_count: ledger.records().iter().filter(|r| r.consensus).count(),
+        head_hash: ledger.head_hash(),
+        merkle_root: ledger.merkle_root(),
+        records: ledger.records(),
+    };
+    let disposition = format!(
+        "attachment; filename=\"ledger-{}.json\"",
+        exported_at.format("%Y%m%dT%H%M%SZ")
+    );
+    Ok(warp::reply::with_header(
+        warp::reply::json(&snapshot),
+        "content-disposition",
+        disposition,
+    ))
+}
+
+fn with_state(
+    state: Arc<AppState>,
+) -> impl Filter<Extract = (Arc<AppState>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || state.clone())
+}
+
+// A simple GET route at `/` for basic info
+fn root_handler() -> impl warp::Reply {
+    warp::reply::html(
+        "<h1>Truth Terminal MVP</h1>\
+         <p>POST to /api/validate with {\"statement\":\"...\"} or {\"claim\":{\"subject\":..,\"predicate\":..,\"object\":..}}</p>\
+         <p>GET /api/ledger[?since=RFC3339] to list recorded statements</p>\
+         <p>GET /api/ledger/last for the most recent record</p>\
+         <p>GET /api/ledger/render[?verbose=true&color=false] for a text view</p>\
+         <p>GET /api/compare/{a}/{b}[?color=false] to show two records side by side</p>\
+         <p>PUT /api/ledger/{n}/label with {\"label\":true} to record ground truth</p>\
+         <p>POST /api/ledger/{n}/notes with {\"text\":\"...\"} to attach a reviewer's note</p>\
+         <p>PUT or DELETE /api/ledger/{n}/pin to pin or unpin a record; GET /api/pinned to list pinned records</p>\
+         <p>POST /api/purge with {\"days\":N} to drop unpinned records older than N days</p>\
+         <p>POST /api/retry-failed to rerun statements whose latest record failed consensus</p>\
+         <p>GET /api/calibration for confidence vs. accuracy over labeled records</p>\
+         <p>GET /api/accuracy for how often each model's votes matched the labels</p>\
+         <p>GET /api/trend?window=K[&threshold=T] for agreement over the last K records vs. the K before</p>\
+         <p>GET /api/reputation for each model's earned vote weight</p>\
+         <p>GET /api/whatif?strategy=majority|provider-quorum|weighted-majority|truth-score|veto[&quorum_providers=N&min_truth_score=S] for verdicts that would flip under another strategy</p>\
+         <p>GET /api/search?q=...[&mode=all|any|phrase&limit=N] to search statements</p>\
+         <p>GET /schema for the JSON Schema of Record, Transaction, AIResponse and ConsensusResult</p>\
+         <p>GET /api/status (or /health, 503 on a broken chain) for ledger, provider and Solana state</p>\
+         <p>GET /api/head for the head hash and Merkle root of the ledger</p>\
+         <p>GET /api/why/{index} for how a record reached its verdict</p>\
+         <p>GET /api/chart/{index} for a record's yes/no/abstain counts and confidence-weighted totals</p>\
+         <p>GET /api/robustness/{index}[?resamples=N&seed=S] for how often its verdict survives resampling its votes</p>\
+         <p>GET /api/prove/{index} for a record's Merkle proof; POST one to /api/check-proof to verify it</p>\
+         <p>GET /api/verify to check the chain and record timestamps</p>\
+         <p>GET /api/models for every model that has voted in the ledger</p>\
+         <p>GET /api/workspaces to list workspaces; POST /api/workspaces with {\"name\":\"...\"} to create one</p>\
+         <p>POST /rpc (with --jsonrpc) for JSON-RPC 2.0: submit, getLedger, getBlock, verifyChain, getStatus</p>\
+         <p>GET /api/anchors for the Solana transactions anchoring the ledger</p>\
+         <p>GET /api/anchors/status[?limit=N] for recent anchors' confirmation and slot</p>\
+         <p>GET /api/export to download a snapshot of the ledger</p>",
+    )
+}
+
+// ======== MAIN SERVER ========
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().ok();
+    let config = Config::parse();
+    let logs = tracing_subscriber::fmt().with_env_filter(tracing_subscriber::EnvFilter::from_default_env());
+    // As a filter, stdout carries only records.
+    if config.stdin_jsonl {
+        logs.with_writer(std::io::stderr).init();
+    } else {
+        logs.init();
+    }
+    redact::install(config.redact.clone());
+    let per_round = config.agents.unwrap_or(AI_MODELS.len()).min(AI_MODELS.len());
+    if let Some(agents) = config.agents.filter(|&n| n > AI_MODELS.len()) {
+        tracing::warn!("--agents {} exceeds the pool of {} models; every model votes once", agents, AI_MODELS.len());
+    }
+    if config.min_responses > per_round {
+        return Err(format!(
+            "--min-responses {} exceeds the {} agents per round",
+            config.min_responses,
+            per_round
+        )
+        .into());
+    }
 
-    // Build AI providers based on our new list.
-    // Use GrokProvider if the model is "x/grok", else use OpenRouterProvider.
+    if let Some(path) = &config.estimate {
+        cost::print_estimate(path, &config)?;
+        return Ok(());
+    }
+    if let Some(path) = &config.readonly {
+        return viewer::run(path, &config).await;
+    }
+
+    if config.anthropic_direct {
+        if env::var_os("ANTHROPIC_API_KEY").is_none() {
+            return Err("--anthropic-direct needs ANTHROPIC_API_KEY".into());
+        }
+        // The Messages API has its own stream and response formats.
+        if config.stream || config.strict_json {
+            return Err("--stream and --strict-json apply to chat completions, not --anthropic-direct".into());
+        }
+    }
+
+    // Build one agent per entry in our model list. Under --model-override every
+    // agent queries the same model but keeps its own slot name.
+    let openai_server = config.openai_server();
+    if let (Some(server), false) = (&openai_server, config.openai_direct) {
+        return Err(format!("--openai-base-url (OPENAI_BASE_URL) {} needs --openai-direct", server).into());
+    }
+    let openai_keys = if config.openai_direct {
+        // Local OpenAI-compatible servers usually take requests without a key.
+        let keys = match (KeyRing::from_env(), &openai_server) {
+            (Some(keys), _) => keys,
+            (None, Some(_)) => KeyRing::empty(),
+            (None, None) => return Err("--openai-direct needs OPENAI_API_KEYS or OPENAI_API_KEY".into()),
+        };
+        Some(Arc::new(keys))
+    } else if config.pick_models {
+        // The picker offers direct routing only when there are keys to route with.
+        KeyRing::from_env().map(Arc::new)
+    } else {
+        None
+    };
+    let proxy = match &config.proxy {
+        Some(url) => Some(reqwest::Proxy::all(url.as_str()).map_err(|e| format!("invalid --proxy {}: {}", url, e))?),
+        None => None,
+    };
+    let settings = Arc::new(ProviderSettings {
+        proxy: proxy.clone(),
+        temperature: config.temperature,
+        model_temperatures: config.model_temperatures.clone(),
+        extra_headers: config.extra_headers.clone(),
+        profile: config.profile.then(Profile::default),
+        seed: config.openai_seed,
+        refusal_patterns: config.refusal_patterns(),
+        strict_json: config.strict_json || config.check_providers,
+        retry: retry::RetryPolicy {
+            max_attempts: config.max_attempts,
+            base: std::time::Duration::from_millis(config.backoff_ms),
+        },
+        anthropic_direct: config.anthropic_direct,
+        openai_server,
+        stream: config.stream,
+        budget: budget::PromptBudget {
+            tokenizer: config.tokenizer,
+            limits: config.max_prompt_tokens.clone(),
+            overflow: config.prompt_overflow,
+            guard: config.prompt_guard,
+            lang: config.lang.unwrap_or_default(),
+            context: config.context.clone(),
+        },
+    });
+    let fixtures = match (&config.record_dir, &config.replay_dir) {
+        (Some(dir), _) => {
+            std::fs::create_dir_all(dir).map_err(|e| format!("--record {}: {}", dir.display(), e))?;
+            Some((dir, FixtureMode::Record))
+        }
+        (None, Some(dir)) if !dir.is_dir() => return Err(format!("--replay {}: not a directory", dir.display()).into()),
+        (None, Some(dir)) => Some((dir, FixtureMode::Replay)),
+        (None, None) => None,
+    };
+    let roster = if config.pick_models {
+        picker::pick(&AI_MODELS, openai_keys.is_some(), config.openai_direct)?
+    } else {
+        AI_MODELS
+            .iter()
+            .map(|&model| picker::Pick { model, direct: config.openai_direct })
+            .collect()
+    };
     let mut ai_providers: Vec<Box<dyn AIProvider>> = Vec::new();
-    for model in AI_MODELS.iter() {
-        if *model == "x/grok" {
-            ai_providers.push(Box::new(GrokProvider::new()));
-        } else {
-            ai_providers.push(Box::new(OpenRouterProvider::new(model)));
+    // Agents whose backend has no key, with the env var it reads; replayed fixtures
+    // make no calls, so need none.
+    let mut missing_keys: Vec<(String, &'static str)> = Vec::new();
+    for (i, pick) in roster.iter().enumerate() {
+        let keys = if pick.direct { &openai_keys } else { &None };
+        let model = config.model_override.as_deref().unwrap_or(pick.model);
+        let route = Route::of(model, keys, &settings);
+        if let (Route::OpenAI, Some(server), None) = (route, &settings.openai_server, server_model(model)) {
+            return Err(format!(
+                "{} is another vendor's model id, which {} won't serve; pick one of its models with --model-override",
+                model, server
+            )
+            .into());
+        }
+        if let Some(var) = route.key_var().filter(|var| env::var_os(var).is_none()) {
+            if config.replay_dir.is_none() {
+                missing_keys.push((model.to_string(), var));
+            }
+        }
+        let mut provider = match &config.model_override {
+            Some(forced) => build_provider(&format!("agent-{}", i + 1), forced, keys, &settings),
+            None => build_provider(pick.model, pick.model, keys, &settings),
+        };
+        if let Some((dir, mode)) = fixtures {
+            provider = Box::new(FixtureProvider::new(provider, dir, mode));
         }
+        if let Some((sla, backup_model)) = config.sla(provider.model()) {
+            let mut backup = build_provider(provider.agent_name(), backup_model, keys, &settings);
+            if let Some((dir, mode)) = fixtures {
+                backup = Box::new(FixtureProvider::new(backup, dir, mode));
+            }
+            provider = Box::new(SlaProvider::new(provider, backup, sla));
+        }
+        let breaker = CircuitBreaker::new(
+            config.breaker_threshold,
+            std::time::Duration::from_secs(config.breaker_cooldown_secs),
+        );
+        provider = Box::new(BreakerProvider::new(provider, breaker));
+        if let Some(samples) = config.self_consistency {
+            if settings.temperature(provider.model()) == Some(0.0) {
+                let model = provider.model();
+                return Err(format!("--self-consistency needs a nonzero temperature, but {} is sampled at 0", model).into());
+            }
+            provider = Box::new(SelfConsistentProvider::new(provider, samples));
+        }
+        ai_providers.push(provider);
+    }
+
+    // Check credentials before any probe, write or statement, so a first run without
+    // a key stops here instead of abstaining through its first round. --rehash and the
+    // query subcommands call no model.
+    let calls_models = !config.rehash && !config.command.as_ref().is_some_and(Command::is_query);
+    let mut missing_extras = Vec::new();
+    for (on, flag, model) in [
+        (config.explain_consensus, "--explain-consensus", &config.explain_model),
+        (config.devils_advocate, "--devils-advocate", &config.devils_advocate_model),
+    ] {
+        if on && env::var_os("OPENROUTER_API_KEY").is_none() {
+            missing_extras.push((format!("{} ({})", flag, model), "OPENROUTER_API_KEY"));
+        }
+    }
+    let no_agent = !missing_keys.is_empty() && missing_keys.len() == roster.len();
+    if calls_models && (no_agent || !missing_extras.is_empty()) {
+        missing_keys.extend(missing_extras);
+        eprint!("{}", credential_help(&missing_keys));
+        std::process::exit(2);
+    }
+    for (model, var) in missing_keys.iter().filter(|_| calls_models) {
+        tracing::warn!("{} will abstain: {} is not set", model, var);
+    }
+
+    for (model, _) in &config.model_slas {
+        if !config.fallback_models.iter().any(|(m, _)| m == model) {
+            return Err(format!("--model-sla {} needs a --fallback-model {}=BACKUP", model, model).into());
+        }
+    }
+    for (model, _) in &config.fallback_models {
+        if !config.model_slas.iter().any(|(m, _)| m == model) {
+            return Err(format!("--fallback-model {} needs a --model-sla {}=MS", model, model).into());
+        }
+    }
+    if config.consensus == ConsensusStrategy::Veto && config.veto_models.is_empty() && config.required_yes_models.is_empty() {
+        return Err("--consensus veto needs a --veto-model or --require-yes-model".into());
+    }
+    if config.weights.is_some() && !matches!(config.consensus, ConsensusStrategy::Majority | ConsensusStrategy::Veto) {
+        return Err("--weights applies to --consensus majority or veto; weighted-majority and truth-score weigh by earned reputation".into());
+    }
+    if config.confirm_cost && !config.interactive {
+        return Err("--confirm-cost needs --interactive; the API has no one to ask".into());
+    }
+    if config.max_session_blocks.is_some() && !config.interactive {
+        return Err("--max-session-blocks needs --interactive".into());
+    }
+    let batch = config.input.is_some() || config.input_url.is_some();
+    let ask = matches!(config.command, Some(Command::Ask { .. }));
+    if config.output.is_some() && !config.interactive && !ask && !batch {
+        return Err("--output needs --interactive, --input, --input-url or ask".into());
+    }
+    if (config.interactive || batch) && config.command.is_some() {
+        return Err("--interactive, --input and --input-url cannot be combined with a subcommand".into());
+    }
+    if config.fail_on_no_consensus && !batch {
+        return Err("--fail-on-no-consensus needs --input or --input-url".into());
+    }
+    if config.delay_ms > 0 && !batch {
+        return Err("--delay-ms needs --input or --input-url".into());
+    }
+    if config.append_only && config.retention_days.is_some() {
+        return Err("--retention-days purges records, which --append-only forbids".into());
+    }
+    if config.append_only && config.rehash {
+        return Err("--rehash rewrites the ledger, which --append-only forbids".into());
+    }
+    if config.compress && config.store != StoreKind::Json {
+        return Err("--compress needs --store json".into());
+    }
+    if config.resume && config.store == StoreKind::Memory {
+        return Err("--resume needs --store json or sqlite; a memory ledger doesn't survive the restart".into());
+    }
+    if config.workspace.is_some() && config.store == StoreKind::Memory {
+        return Err("--workspace needs --store json or sqlite".into());
+    }
+    if config.store != StoreKind::Memory {
+        let path = config.ledger_path();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| format!("--store {}: {}", path.display(), e))?;
+        }
+    }
+    // Open the configured store and rebuild the in-memory chain from it.
+    let store: Arc<dyn LedgerStore> = match config.store {
+        StoreKind::Memory => Arc::new(MemoryStore),
+        StoreKind::Json => {
+            let mut path = config.ledger_path();
+            if config.compress && path.extension().is_none_or(|ext| ext != "gz") {
+                path.as_mut_os_string().push(".gz");
+            }
+            Arc::new(JsonFileStore::new(&path))
+        }
+        StoreKind::Sqlite => Arc::new(SqliteStore::open(&config.ledger_path())?),
+    };
+    // --no-solana wins over SOLANA_RPC_URL / SOLANA_KEYPAIR_PATH picked up from the environment.
+    let mut solana = match config.no_solana {
+        true => None,
+        false => config
+            .solana_url
+            .as_deref()
+            .map(|url| SolanaClient::new(url, proxy.as_ref()).with_commitment(config.commitment)),
+    };
+    let keypair = config.keypair_path();
+    if let Some(path) = keypair.as_ref().filter(|_| !config.no_solana) {
+        let client = solana.take().ok_or("--solana-keypair needs --solana-url")?;
+        solana = Some(client.with_signer(Keypair::read(path)?));
+    }
+    if config.anchor_interval_secs.is_some() && (config.no_solana || keypair.is_none()) {
+        return Err("--anchor-interval-secs needs --solana-url and --solana-keypair".into());
+    }
+    if config.anchor {
+        if config.no_solana || keypair.is_none() {
+            return Err("--anchor needs --solana-url and --solana-keypair".into());
+        }
+        // Fail now rather than on every record when the payer can't cover a memo; an
+        // unreachable cluster may recover, so that only warns.
+        match solana.as_ref().expect("checked above").balance().await {
+            Ok(0) => {
+                let payer = Keypair::read(keypair.as_ref().expect("checked above"))?;
+                return Err(solana::SolanaError::InsufficientFunds(payer.address()).to_string().into());
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("could not check the anchor payer's balance: {}", e),
+        }
+    }
+    if config.validate_only {
+        if !preflight::run(&ai_providers, solana.as_ref(), &store).await {
+            return Err("preflight failed".into());
+        }
+        return Ok(());
+    }
+    if config.check_providers {
+        if !preflight::check_providers(&ai_providers).await {
+            return Err("provider check failed".into());
+        }
+        return Ok(());
+    }
+    if config.live_banner {
+        preflight::print_banner(&ai_providers).await;
+    }
+
+    if config.rehash {
+        let records = store.load()?;
+        let before: Vec<String> = records.iter().map(|r| r.hash.clone()).collect();
+        let ledger = Ledger::rehash(records)?;
+        let changed = ledger.records().iter().zip(&before).filter(|(r, old)| r.hash != **old).count();
+        store.replace_all(ledger.records())?;
+        println!(
+            "Rehashed {} records ({} hashes changed); head {}",
+            ledger.records().len(),
+            changed,
+            ledger.head_hash()
+        );
+        return Ok(());
+    }
+
+    let records = store.load()?;
+    // Only a ledger written before hashing may be rehashed; a broken hash on a hashed
+    // record means the stored copy changed after commit, which rehashing would hide.
+    let predates_hashing = records.iter().any(|r| r.hash.is_empty());
+    let mut ledger = Ledger::from_records(records).map_err(|e| match predates_hashing {
+        true => format!("{}; if this ledger predates hashing, migrate it with --rehash", e),
+        false => format!("{}; the stored ledger was edited or corrupted after it was committed", e),
+    })?;
+
+    if let Some(command) = config.command.as_ref().filter(|c| c.is_query()) {
+        let opts = RenderOptions::for_stdout(config.no_color);
+        print!("{}", query::run(command, ledger.records(), &opts)?);
+        return Ok(());
+    }
+
+    // The merged chain is stored whole before it's served, so a failed write leaves
+    // neither the store nor the in-memory ledger half-imported.
+    if let Some(path) = &config.import {
+        let foreign = store::read_ledger_file(path)?;
+        let summary = ledger.import(foreign)?;
+        store.replace_all(ledger.records())?;
+        println!(
+            "Imported {} records from {} ({} duplicates skipped)",
+            summary.imported.len(),
+            path.display(),
+            summary.skipped
+        );
+    }
+
+    for anomaly in ledger::timestamp_anomalies(ledger.records()) {
+        tracing::warn!("{}", anomaly);
+    }
+    *LEDGER.write().await = ledger;
+
+    let anchors = store.load_anchors()?;
+    let audit = config.audit_log.as_deref().map(audit::AuditLog::open).transpose()?;
+    let explainer = config
+        .explain_consensus
+        .then(|| Explainer::new(&config.explain_model, settings.clone()));
+    let advocate = config
+        .devils_advocate
+        .then(|| DevilsAdvocate::new(&config.devils_advocate_model, settings.clone()));
+    let state = Arc::new(AppState {
+        providers: ai_providers,
+        solana,
+        store,
+        settings,
+        commit_lock: tokio::sync::Mutex::new(()),
+        anchors: RwLock::new(anchors),
+        alert: config.alert_webhook.as_deref().map(|url| AlertWebhook::new(url, proxy.as_ref())),
+        explainer,
+        advocate,
+        pool: config.worker_threads.map(pool::WorkerPool::new),
+        integrity: integrity::IntegrityChecks::default(),
+        audit,
+        started: Utc::now(),
+        ids: match config.seed {
+            Some(seed) => Box::new(ids::SeededIds::new(seed)),
+            None => Box::new(ids::RandomIds),
+        },
+        rng: std::sync::Mutex::new(match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        }),
+        spend_cap: config.max_cost_usd.map(cost::SpendCap::new),
+        reputation: std::sync::Mutex::new(reputation::ReputationBook::from_records(
+            LEDGER.read().await.records(),
+            config.half_life_days,
+        )),
+        on_commit: config
+            .on_commit
+            .as_deref()
+            .map(|command| CommitHook::new(command, std::time::Duration::from_secs(config.on_commit_timeout_secs))),
+        config,
+    });
+
+    if state.audit.is_some() {
+        let ledger = LEDGER.read().await;
+        state.audit(|| {
+            format!(
+                "start: version {}, {} records, head {}",
+                env!("CARGO_PKG_VERSION"),
+                ledger.records().len(),
+                ledger.head_hash()
+            )
+        });
+    }
+    if let Some(days) = state.config.retention_days {
+        tokio::spawn(retention::run(state.clone(), days));
+    }
+    if let Some(secs) = state.config.anchor_interval_secs {
+        tokio::spawn(anchor::run(state.clone(), std::time::Duration::from_secs(secs)));
+    }
+    if let Some(mins) = state.config.verify_interval_mins {
+        tokio::spawn(integrity::run(state.clone(), std::time::Duration::from_secs(mins * 60)));
+    }
+
+    if let Some(Command::Ask { statement }) = &state.config.command {
+        let recorded = console::ask(&state, statement).await?;
+        shutdown(&state).await;
+        return if recorded { Ok(()) } else { Err("nothing recorded".into()) };
+    }
+    let questions = match (&state.config.input, &state.config.input_url) {
+        (Some(path), _) => Some(cost::read_questions(path)?),
+        (None, Some(url)) => {
+            let client = client_builder(state.settings.proxy.as_ref()).build()?;
+            let questions = remote::fetch_questions(&client, url)
+                .await
+                .map_err(|e| format!("cannot fetch --input-url {}: {}", url, e))?;
+            println!("Fetched {} questions from {}", questions.len(), url);
+            Some(questions)
+        }
+        (None, None) => None,
+    };
+    if let Some(questions) = questions {
+        let checkpoint = state
+            .config
+            .input
+            .as_deref()
+            .filter(|_| state.config.resume)
+            .map(checkpoint::path_for);
+        let outcome = console::batch(&state, &questions, checkpoint.as_deref()).await?;
+        shutdown(&state).await;
+        if outcome.answered == 0 {
+            return Err("nothing recorded".into());
+        }
+        if state.config.fail_on_no_consensus && outcome.without_consensus > 0 {
+            return Err(format!(
+                "{} of {} questions did not reach consensus",
+                outcome.without_consensus, outcome.answered
+            )
+            .into());
+        }
+        return Ok(());
+    }
+    if state.config.stdin_jsonl {
+        let committed = console::jsonl(&state).await?;
+        shutdown(&state).await;
+        return if committed > 0 { Ok(()) } else { Err("nothing recorded".into()) };
+    }
+    if state.config.interactive {
+        console::run(&state).await?;
+        shutdown(&state).await;
+        return Ok(());
     }
-    let providers = Arc::new(ai_providers);
 
     // Define routes
     let root = warp::path::end().map(root_handler);
     let validate_route = warp::path!("api" / "validate")
         .and(warp::post())
         .and(warp::body::json())
-        .and(with_providers(providers.clone()))
+        .and(with_state(state.clone()))
         .and_then(handle_validate);
+    let ledger_route = warp::path!("api" / "ledger")
+        .and(warp::get())
+        .and(warp::query::<LedgerQuery>())
+        .and_then(handle_ledger);
+    let last_route = warp::path!("api" / "ledger" / "last")
+        .and(warp::get())
+        .and_then(handle_last);
+    let render_route = warp::path!("api" / "ledger" / "render")
+        .and(warp::get())
+        .and(warp::query::<RenderQuery>())
+        .and_then(handle_render);
+    let head_route = warp::path!("api" / "head")
+        .and(warp::get())
+        .and_then(handle_head);
+    let why_route = warp::path!("api" / "why" / usize)
+        .and(warp::get())
+        .and(warp::query::<RenderQuery>())
+        .and_then(handle_why);
+    let chart_route = warp::path!("api" / "chart" / usize)
+        .and(warp::get())
+        .and_then(handle_chart);
+    let robustness_route = warp::path!("api" / "robustness" / usize)
+        .and(warp::get())
+        .and(warp::query::<RobustnessQuery>())
+        .and(with_state(state.clone()))
+        .and_then(handle_robustness);
+    let prove_route = warp::path!("api" / "prove" / usize)
+        .and(warp::get())
+        .and_then(handle_prove);
+    let check_proof_route = warp::path!("api" / "check-proof")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and_then(handle_check_proof);
+    let compare_route = warp::path!("api" / "compare" / usize / usize)
+        .and(warp::get())
+        .and(warp::query::<RenderQuery>())
+        .and_then(handle_compare);
+    let label_route = warp::path!("api" / "ledger" / usize / "label")
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_label);
+    let note_route = warp::path!("api" / "ledger" / usize / "notes")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_note);
+    let pin_route = warp::path!("api" / "ledger" / usize / "pin")
+        .and(warp::put().or(warp::delete()).unify())
+        .and(warp::method())
+        .and(with_state(state.clone()))
+        .and_then(handle_pin);
+    let workspaces_route = warp::path!("api" / "workspaces")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_workspaces);
+    let new_workspace_route = warp::path!("api" / "workspaces")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_new_workspace);
+    let jsonrpc_route = warp::path!("rpc")
+        .and(warp::post())
+        .and(warp::body::bytes())
+        .and(with_state(state.clone()))
+        .and_then(handle_jsonrpc);
+    let purge_route = warp::path!("api" / "purge")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_purge);
+    let retry_failed_route = warp::path!("api" / "retry-failed")
+        .and(warp::post())
+        .and(with_state(state.clone()))
+        .and_then(handle_retry_failed);
+    let pinned_route = warp::path!("api" / "pinned")
+        .and(warp::get())
+        .and_then(handle_pinned);
+    let trend_route = warp::path!("api" / "trend")
+        .and(warp::get())
+        .and(warp::query::<TrendQuery>())
+        .and(with_state(state.clone()))
+        .and_then(handle_trend);
+    let whatif_route = warp::path!("api" / "whatif")
+        .and(warp::get())
+        .and(warp::query::<WhatIfQuery>())
+        .and(with_state(state.clone()))
+        .and_then(handle_whatif);
+    let schema_route = warp::path!("schema")
+        .and(warp::get())
+        .and_then(handle_schema);
+    let reputation_route = warp::path!("api" / "reputation")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_reputation);
+    let calibration_route = warp::path!("api" / "calibration")
+        .and(warp::get())
+        .and_then(handle_calibration);
+    let accuracy_route = warp::path!("api" / "accuracy")
+        .and(warp::get())
+        .and_then(handle_accuracy);
+    let search_route = warp::path!("api" / "search")
+        .and(warp::get())
+        .and(warp::query::<SearchQuery>())
+        .and(with_state(state.clone()))
+        .and_then(handle_search);
+    let status_route = warp::path!("api" / "status")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_status);
+    let health_route = warp::path!("health")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_health);
+    let verify_route = warp::path!("api" / "verify")
+        .and(warp::get())
+        .and_then(handle_verify);
+    let models_route = warp::path!("api" / "models")
+        .and(warp::get())
+        .and_then(handle_models);
+    let anchors_route = warp::path!("api" / "anchors")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_anchors);
+    let anchor_status_route = warp::path!("api" / "anchors" / "status")
+        .and(warp::get())
+        .and(warp::query::<AnchorStatusQuery>())
+        .and(with_state(state.clone()))
+        .and_then(handle_anchor_status);
+    let export_route = warp::path!("api" / "export")
+        .and(warp::get())
+        .and_then(handle_export);
 
     // Combine routes and add CORS support
+    // Grouped so the combined filter type stays shallow enough for the compiler.
+    let record_routes = ledger_route
+        .or(last_route)
+        .or(render_route)
+        .or(compare_route)
+        .or(head_route)
+        .or(why_route)
+        .or(robustness_route)
+        .or(chart_route)
+        .or(prove_route)
+        .or(check_proof_route);
     let routes = root
         .or(validate_route)
-        .with(
-            warp::cors()
-                .allow_any_origin()
-                .allow_header("content-type")
-                .allow_methods(vec![warp::http::Method::POST, warp::http::Method::GET])
-        );
+        .or(record_routes)
+        .or(label_route)
+        .or(note_route)
+        .or(pin_route)
+        .or(pinned_route)
+        .or(purge_route)
+        .or(retry_failed_route)
+        .or(calibration_route)
+        .or(accuracy_route)
+        .or(trend_route)
+        .or(whatif_route)
+        .or(reputation_route)
+        .or(schema_route)
+        .or(search_route)
+        .or(verify_route)
+        .or(status_route)
+        .or(health_route)
+        .or(models_route)
+        .or(anchors_route)
+        .or(anchor_status_route)
+        .or(export_route)
+        .or(workspaces_route)
+        .or(new_workspace_route)
+        .or(jsonrpc_route)
+        .with(cors());
 
-    println!("Server running on 0.0.0.0:3030");
-    warp::serve(routes).run(([0, 0, 0, 0], 3030)).await;
+    let (addr, server) = warp::serve(routes).bind_with_graceful_shutdown(([0, 0, 0, 0], 3030), async {
+        tokio::signal::ctrl_c().await.ok();
+    });
+    println!("Server running on {}", addr);
+    server.await;
+    shutdown(&state).await;
     Ok(())
 }
 
+/// Lets browser frontends on other origins call every route, including the viewer's
+/// `DELETE /api/ledger/{n}/pin`.
+fn cors() -> warp::cors::Builder {
+    warp::cors()
+        .allow_any_origin()
+        .allow_header("content-type")
+        .allow_methods(vec![
+            warp::http::Method::POST,
+            warp::http::Method::GET,
+            warp::http::Method::PUT,
+            warp::http::Method::DELETE,
+        ])
+}
+
+/// Runs once the console or server has stopped taking statements.
+async fn shutdown(state: &AppState) {
+    if let Some(secs) = state.config.wait_confirmations {
+        anchor::await_confirmations(state, std::time::Duration::from_secs(secs)).await;
+    }
+    if state.audit.is_some() {
+        let ledger = LEDGER.read().await;
+        state.audit(|| format!("exit: {} records, head {}", ledger.records().len(), ledger.head_hash()));
+    }
+    if let Some(path) = &state.config.report {
+        let ledger = LEDGER.read().await;
+        let session: Vec<&Record> = ledger.records().iter().filter(|r| r.timestamp >= state.started).collect();
+        let summary = report::summarize(&session, state.started, ledger.head_hash().to_string(), |record| {
+            cost::repeat_cost(&record.transaction.content, &state.config)
+        });
+        match report::write(path, &summary) {
+            Ok(()) => eprintln!("Session report written to {}", path.display()),
+            Err(e) => tracing::error!("cannot write --report {}: {}", path.display(), e),
+        }
+    }
+    if let Some(profile) = &state.settings.profile {
+        // stderr, so the summary stays out of `--format json` output.
+        eprint!("{}", profile.report());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeProvider(&'static str);
+
+    #[async_trait]
+    impl AIProvider for FakeProvider {
+        fn agent_name(&self) -> &str {
+            self.0
+        }
+
+        fn model(&self) -> &str {
+            self.0
+        }
+
+        async fn validate(&self, _text: &str, _debate: Option<&str>) -> Result<AIResponse, ProviderError> {
+            Err(ProviderError::CircuitOpen)
+        }
+    }
+
+    fn settings_for(config: &Config) -> ProviderSettings {
+        ProviderSettings {
+            stream: false,
+            budget: budget::PromptBudget {
+                tokenizer: config.tokenizer,
+                limits: Vec::new(),
+                overflow: config.prompt_overflow,
+                guard: config.prompt_guard,
+                lang: config.lang.unwrap_or_default(),
+                context: config.context.clone(),
+            },
+            proxy: None,
+            temperature: config.temperature,
+            model_temperatures: config.model_temperatures.clone(),
+            extra_headers: config.extra_headers.clone(),
+            profile: None,
+            seed: config.openai_seed,
+            refusal_patterns: config.refusal_patterns(),
+            strict_json: config.strict_json,
+            retry: retry::RetryPolicy::default(),
+            anthropic_direct: config.anthropic_direct,
+            openai_server: config.openai_server(),
+        }
+    }
+
+    /// Votes yes after `delay`, or fails (as agent "down") without voting.
+    struct Delayed(&'static str, u64);
+
+    #[async_trait]
+    impl AIProvider for Delayed {
+        fn agent_name(&self) -> &str {
+            self.0
+        }
+
+        fn model(&self) -> &str {
+            self.0
+        }
+
+        async fn validate(&self, _text: &str, _debate: Option<&str>) -> Result<AIResponse, ProviderError> {
+            tokio::time::sleep(std::time::Duration::from_millis(self.1)).await;
+            if self.0 == "down" {
+                return Err(ProviderError::Malformed("empty".to_string()));
+            }
+            Ok(AIResponse {
+                agent_name: self.0.to_string(),
+                model: self.0.to_string(),
+                is_valid: true,
+                confidence: None,
+                attempts: 1,
+                lang: None,
+                system_fingerprint: None,
+                fallback_from: None,
+                self_consistency: None,
+                rationale: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn agents_are_polled_concurrently_in_pool_order() {
+        let agents = [Delayed("a", 300), Delayed("down", 200), Delayed("b", 100), Delayed("c", 0)];
+        let agents: Vec<&dyn AIProvider> = agents.iter().map(|a| a as &dyn AIProvider).collect();
+        let started = Instant::now();
+        let (votes, abstentions) = poll_agents(&agents, "s", None, None).await;
+        // One round trip's worth, not the sum of all four.
+        assert!(started.elapsed() < std::time::Duration::from_millis(550), "{:?}", started.elapsed());
+        let order: Vec<&str> = votes.iter().map(|v| v.agent_name.as_str()).collect();
+        assert_eq!(order, ["a", "b", "c"]);
+        assert_eq!(abstentions.len(), 1);
+        assert_eq!(abstentions[0].reason, "unusable response: empty");
+    }
+
+    fn pool() -> Vec<Box<dyn AIProvider>> {
+        AI_MODELS.iter().map(|m| Box::new(FakeProvider(m)) as Box<dyn AIProvider>).collect()
+    }
+
+    #[tokio::test]
+    async fn cors_preflight_allows_unpinning() {
+        let route = warp::path!("api" / "ledger" / usize / "pin")
+            .and(warp::delete())
+            .map(|_| warp::reply())
+            .with(cors());
+        let preflight = warp::test::request()
+            .method("OPTIONS")
+            .path("/api/ledger/1/pin")
+            .header("origin", "http://viewer.example")
+            .header("access-control-request-method", "DELETE")
+            .reply(&route)
+            .await;
+        assert_eq!(preflight.status(), warp::http::StatusCode::OK);
+        let allowed = preflight.headers()["access-control-allow-methods"].to_str().unwrap();
+        assert!(allowed.contains("DELETE"), "{}", allowed);
+    }
+
+    #[test]
+    fn full_rounds_keep_pool_order() {
+        let providers = pool();
+        let config = Config::parse_from(["truth"]);
+        for _ in 0..20 {
+            let models: Vec<&str> = assign_agents(&providers, &config, &mut rand::thread_rng()).iter().map(|a| a.model()).collect();
+            assert_eq!(models, AI_MODELS);
+        }
+    }
+
+    #[test]
+    fn sampled_rounds_are_listed_in_pool_order() {
+        let providers = pool();
+        let config = Config::parse_from(["truth", "--agents", "4"]);
+        for _ in 0..50 {
+            let positions: Vec<usize> = assign_agents(&providers, &config, &mut rand::thread_rng())
+                .iter()
+                .map(|a| AI_MODELS.iter().position(|m| *m == a.model()).unwrap())
+                .collect();
+            assert_eq!(positions.len(), 4);
+            assert!(positions.windows(2).all(|w| w[0] < w[1]), "{:?}", positions);
+        }
+    }
+
+    #[test]
+    fn seeded_sampling_repeats() {
+        let providers = pool();
+        let config = Config::parse_from(["truth", "--agents", "3"]);
+        let draw = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let rounds: Vec<Vec<&str>> = (0..5)
+                .map(|_| assign_agents(&providers, &config, &mut rng).iter().map(|a| a.model()).collect())
+                .collect();
+            rounds
+        };
+        assert_eq!(draw(42), draw(42));
+    }
+
+    #[test]
+    fn model_temperature_overrides_the_global_one() {
+        let config = Config::parse_from(["truth", "--temperature", "0.7", "--model-temperature", "x/grok=0"]);
+        let settings = settings_for(&config);
+        assert_eq!(settings.temperature("x/grok"), Some(0.0));
+        assert_eq!(settings.temperature("openai/gpt-4o"), Some(0.7));
+        assert!(Config::try_parse_from(["truth", "--temperature", "3"]).is_err());
+    }
+
+    #[test]
+    fn anthropic_models_go_direct_only_when_asked() {
+        let haiku = |args: &[&str]| Route::of("anthropic/claude-3-haiku", &None, &settings_for(&Config::parse_from(args)));
+        assert_eq!(haiku(&["truth"]), Route::OpenRouter);
+        assert_eq!(haiku(&["truth", "--anthropic-direct"]), Route::Anthropic);
+    }
+
+    #[test]
+    fn extra_headers_apply_only_to_their_backend() {
+        let config = Config::parse_from(["truth", "--extra-header", "openrouter:X-Title=truth terminal"]);
+        let settings = settings_for(&config);
+        let client = reqwest::Client::new();
+        let build = |backend| settings.with_extra_headers(backend, client.get("http://localhost/")).build().unwrap();
+        assert_eq!(build("openrouter").headers()["X-Title"], "truth terminal");
+        assert!(build("grok").headers().get("X-Title").is_none());
+        assert!(build("anthropic").headers().get("X-Title").is_none());
+        assert!(Config::try_parse_from(["truth", "--extra-header", "mistral:X-Title=t"]).is_err());
+        assert!(Config::try_parse_from(["truth", "--extra-header", "grok:Bad Name=t"]).is_err());
+    }
+
+    #[test]
+    fn schema_describes_every_record_field() {
+        let schema = ledger_schema();
+        let properties = schema["Record"]["properties"].as_object().unwrap();
+        let record = serde_json::to_value(ledger::tests::record("a")).unwrap();
+        for field in record.as_object().unwrap().keys() {
+            assert!(properties.contains_key(field), "{} missing from schema", field);
+        }
+        assert!(schema["AIResponse"]["properties"]["confidence"].is_object());
+    }
+
+    #[test]
+    fn ask_takes_the_statement_after_the_flags() {
+        let config = Config::parse_from(["truth", "--repeat", "2", "ask", "The earth is round"]);
+        assert!(matches!(config.command, Some(Command::Ask { statement }) if statement == "The earth is round"));
+        assert_eq!(config.repeat, 2);
+    }
+
+    #[test]
+    fn provenance_captures_the_round_configuration() {
+        let config = Config::parse_from([
+            "truth", "--consensus", "provider-quorum", "--model-temperature", "x/grok=0.3", "--rounds", "2",
+        ]);
+        let settings = settings_for(&config);
+        let providers = pool();
+        let agents = assign_agents(&providers, &config, &mut rand::thread_rng());
+        let provenance = Provenance::capture(&config, &settings, &agents);
+        assert_eq!(provenance.consensus, "provider-quorum");
+        assert_eq!(provenance.quorum_providers, Some(2));
+        assert_eq!(provenance.models, AI_MODELS);
+        assert_eq!(provenance.temperatures, [("x/grok".to_string(), 0.3)]);
+        assert_eq!(provenance.rounds, 2);
+        assert_eq!(provenance.crate_version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn lang_localizes_the_framing_but_not_the_statement() {
+        let prompt = validation_prompt(config::Lang::Es, "The earth is flat", Some("In round 1 ..."));
+        assert!(prompt.starts_with("¿Es válida") && prompt.contains("'The earth is flat'"));
+        assert!(prompt.ends_with("responde en el mismo formato."));
+        let config = Config::parse_from(["truth", "--lang", "ja"]);
+        assert_eq!(settings_for(&config).budget.vote_lang().as_deref(), Some("ja"));
+        assert_eq!(settings_for(&Config::parse_from(["truth"])).budget.vote_lang(), None);
+    }
+
+    #[test]
+    fn debate_prompt_shows_anonymized_votes() {
+        let vote = |model: &str, is_valid, confidence| AIResponse {
+            agent_name: model.to_string(),
+            model: model.to_string(),
+            is_valid,
+            confidence,
+            attempts: 1,
+            lang: None,
+            system_fingerprint: None,
+            fallback_from: None,
+            self_consistency: None,
+            rationale: None,
+        };
+        let summary = debate_summary(1, &[vote("openai/gpt-4o", true, Some(0.9)), vote("x/grok", false, None)]);
+        assert_eq!(summary, "In round 1 the panel voted 1 yes and 1 no:\nAgent 1: yes 0.90\nAgent 2: no");
+        let prompt = validation_prompt(config::Lang::En, "The sky is green", Some(&summary));
+        assert!(prompt.contains("'The sky is green'") && prompt.contains("Agent 2: no"));
+        assert!(!prompt.contains("grok"));
+        assert!(!validation_prompt(config::Lang::En, "x", None).contains("Reconsider"));
+    }
+
+    #[test]
+    fn parse_vote_reads_verdict_and_confidence() {
+        assert_eq!(parse_vote("yes 0.9").unwrap(), (true, Some(0.9), None));
+        assert_eq!(parse_vote("no, 0.75").unwrap(), (false, Some(0.75), None));
+        assert_eq!(parse_vote("Yes").unwrap(), (true, None, None));
+        // Numbers outside 0..=1 are not confidences.
+        assert_eq!(parse_vote("no. 42 0.6").unwrap(), (false, Some(0.6), None));
+        // Sentence punctuation after the number.
+        assert_eq!(parse_vote("yes 0.9.").unwrap(), (true, Some(0.9), None));
+        assert_eq!(parse_vote("yes (.8)").unwrap(), (true, Some(0.8), None));
+    }
 
+    #[test]
+    fn a_custom_openai_base_url_names_its_server() {
+        let local = Config::parse_from(["truth", "--openai-base-url", "http://localhost:11434/v1/"]);
+        assert_eq!(local.openai_server().as_deref(), Some("http://localhost:11434/v1/chat/completions"));
+        let openai = Config::parse_from(["truth", "--openai-direct", "--openai-base-url", "https://api.openai.com/v1"]);
+        assert_eq!(openai.openai_server(), None);
+        assert_eq!(server_model("openai/gpt-4o"), Some("gpt-4o"));
+        assert_eq!(server_model("llama3"), Some("llama3"));
+        assert_eq!(server_model("google/gemini-pro"), None);
+    }
 
+    #[test]
+    fn the_ledger_defaults_to_a_json_file_under_the_data_dir() {
+        let config = Config::parse_from(["truth"]);
+        assert_eq!(config.store, StoreKind::Json);
+        assert_eq!(config.ledger_path(), std::path::Path::new("data").join("ledger.jsonl"));
+        let config = Config::parse_from(["truth", "--store", "sqlite", "--data-dir", "/var/lib/truth"]);
+        assert_eq!(config.ledger_path(), std::path::PathBuf::from("/var/lib/truth/ledger.db"));
+        let config = Config::parse_from(["truth", "--workspace", "alpha"]);
+        assert_eq!(config.ledger_path(), std::path::PathBuf::from("workspaces/alpha/ledger.jsonl"));
+    }
 
+    #[test]
+    fn missing_credentials_are_explained_per_env_var() {
+        assert_eq!(Route::Grok.key_var(), Some("X_API_KEY"));
+        assert_eq!(Route::OpenAI.key_var(), None);
+        let missing = vec![
+            ("openai/gpt-4o".to_string(), "OPENROUTER_API_KEY"),
+            ("x/grok".to_string(), "X_API_KEY"),
+            ("google/gemini-pro".to_string(), "OPENROUTER_API_KEY"),
+            ("--explain-consensus (openai/gpt-4o-mini)".to_string(), "OPENROUTER_API_KEY"),
+        ];
+        let help = credential_help(&missing);
+        assert!(help.starts_with("Can't start: these need an API key that is not set.\n\n"));
+        assert!(
+            help.contains("  OPENROUTER_API_KEY   openai/gpt-4o, google/gemini-pro, --explain-consensus (openai/gpt-4o-mini)\n"),
+            "{}",
+            help
+        );
+        assert!(help.contains("  X_API_KEY            x/grok\n"));
+        assert!(help.contains("`export OPENROUTER_API_KEY=...`"));
+    }
 
+    #[test]
+    fn only_transient_failures_are_retried() {
+        let status = |code: u16| ProviderError::Status {
+            status: reqwest::StatusCode::from_u16(code).unwrap(),
+            message: "x".to_string(),
+            retry_after: None,
+        };
+        assert!(status(429).is_retryable() && status(503).is_retryable());
+        assert!(!status(400).is_retryable() && !status(401).is_retryable());
+        assert!(ProviderError::Truncated(10).is_retryable());
+        assert!(!ProviderError::MissingKey("OPENAI_API_KEY").is_retryable());
+    }
 
+    #[test]
+    fn parse_vote_keeps_the_rationale_out_of_the_verdict() {
+        let reason = |s: &str| Some(s.to_string());
+        assert_eq!(
+            parse_vote("no, this is not yes-worthy").unwrap(),
+            (false, None, reason("this is not yes-worthy"))
+        );
+        assert_eq!(
+            parse_vote("No 0.8 Only 1 in 3 surveys agree, so yes is too strong.").unwrap(),
+            (false, Some(0.8), reason("Only 1 in 3 surveys agree, so yes is too strong."))
+        );
+        assert_eq!(parse_vote("Yes 0.9 水は濡れている。").unwrap(), (true, Some(0.9), reason("水は濡れている。")));
+        // A "yes" inside another word is no verdict.
+        assert!(matches!(parse_vote("My eyes deceive me"), Err(ProviderError::Malformed(_))));
+    }
+
+    /// State for a round over `providers` with default flags and no persistence.
+    fn test_state(providers: Vec<Box<dyn AIProvider>>) -> AppState {
+        let config = Config::parse_from(["truth"]);
+        AppState {
+            providers,
+            solana: None,
+            store: Arc::new(MemoryStore),
+            settings: Arc::new(ProviderSettings::default()),
+            commit_lock: tokio::sync::Mutex::new(()),
+            anchors: RwLock::new(Vec::new()),
+            alert: None,
+            on_commit: None,
+            explainer: None,
+            advocate: None,
+            spend_cap: None,
+            reputation: std::sync::Mutex::new(reputation::ReputationBook::from_records(&[], None)),
+            ids: Box::new(ids::RandomIds),
+            rng: std::sync::Mutex::new(StdRng::seed_from_u64(1)),
+            pool: None,
+            integrity: integrity::IntegrityChecks::default(),
+            audit: None,
+            started: Utc::now(),
+            config,
+        }
+    }
 
+    /// Answers nothing itself; replayed fixtures stand in for it.
+    struct Offline(&'static str);
+
+    #[async_trait]
+    impl AIProvider for Offline {
+        fn agent_name(&self) -> &str {
+            self.0
+        }
+
+        fn model(&self) -> &str {
+            self.0
+        }
+
+        async fn validate(&self, _text: &str, _debate: Option<&str>) -> Result<AIResponse, ProviderError> {
+            Err(ProviderError::Malformed("offline".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn stored_records_carry_no_terminal_escapes() {
+        let dir = std::env::temp_dir().join(format!("truth-escapes-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let statement = "Water is wet";
+        let fixture = serde_json::json!({
+            "model": "openai/gpt-4o",
+            "statement": statement,
+            "is_valid": true,
+            "confidence": 0.9,
+            "attempts": 1,
+            "system_fingerprint": "fp_1\u{1b}[2J",
+            "rationale": "\u{1b}[31mIt is\u{1b}[0m \u{1b}]8;;https://evil.example\u{7}wet\u{1b}]8;;\u{1b}\\ by\ndefinition.",
+        });
+        let path = fixtures::fixture_path(&dir, "openai/gpt-4o", statement, None);
+        std::fs::write(path, fixture.to_string()).unwrap();
+        let replaying = |model| -> Box<dyn AIProvider> {
+            Box::new(FixtureProvider::new(Box::new(Offline(model)), &dir, FixtureMode::Replay))
+        };
+        let state = test_state(vec![replaying("openai/gpt-4o"), replaying("x/grok")]);
+
+        let record = run_round(&state, statement, None, None).await.unwrap().record;
+        std::fs::remove_dir_all(&dir).unwrap();
+        let vote = &record.votes[0];
+        assert_eq!(vote.rationale.as_deref(), Some("It is wet by definition."));
+        assert_eq!(vote.system_fingerprint.as_deref(), Some("fp_1"));
+        let mut fields = vec![record.details.as_str(), record.transaction.content.as_str()];
+        fields.extend(record.votes.iter().filter_map(|v| v.rationale.as_deref()));
+        fields.extend(record.abstentions.iter().map(|a| a.reason.as_str()));
+        for field in fields {
+            assert!(!field.contains(['\u{1b}', '\u{7}']) && !field.contains("8;;"), "{:?}", field);
+        }
+    }
+}
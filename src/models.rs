@@ -0,0 +1,116 @@
+/// Who makes a model, whichever backend serves it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+    OpenAI,
+    Anthropic,
+    Google,
+    Mistral,
+    Meta,
+    Cohere,
+    DeepSeek,
+    XAi,
+}
+
+impl Provider {
+    pub fn name(self) -> &'static str {
+        match self {
+            Provider::OpenAI => "OpenAI",
+            Provider::Anthropic => "Anthropic",
+            Provider::Google => "Google",
+            Provider::Mistral => "Mistral",
+            Provider::Meta => "Meta",
+            Provider::Cohere => "Cohere",
+            Provider::DeepSeek => "DeepSeek",
+            Provider::XAi => "xAI",
+        }
+    }
+}
+
+/// A pool model: the friendly name people read, and the identifier requests and
+/// the ledger use.
+#[derive(Debug)]
+pub struct ModelSpec {
+    pub display: &'static str,
+    pub api_name: &'static str,
+    pub provider: Provider,
+    /// Short names flags accept besides the api name, its part after the `/` and the
+    /// display name.
+    pub aliases: &'static [&'static str],
+}
+
+const fn spec(display: &'static str, api_name: &'static str, provider: Provider, aliases: &'static [&'static str]) -> ModelSpec {
+    ModelSpec { display, api_name, provider, aliases }
+}
+
+/// The default agent pool, in slot order.
+pub const MODELS: [ModelSpec; 9] = [
+    spec("GPT-4o", "openai/gpt-4o", Provider::OpenAI, &["gpt4o"]),
+    spec("GPT-3.5 Turbo", "openai/gpt-3.5-turbo", Provider::OpenAI, &["gpt-3.5", "gpt35"]),
+    spec("Claude 3 Haiku", "anthropic/claude-3-haiku", Provider::Anthropic, &["haiku"]),
+    spec("Gemini Pro", "google/gemini-pro", Provider::Google, &["gemini"]),
+    spec("Mistral 7B Instruct", "mistral/mistral-7b-instruct", Provider::Mistral, &["mistral", "mistral-7b"]),
+    spec("Llama 3 70B Instruct", "meta/llama-3-70b-instruct", Provider::Meta, &["llama", "llama-3-70b"]),
+    spec("Command R+", "cohere/command-r-plus", Provider::Cohere, &["command-r"]),
+    spec("DeepSeek Coder", "deepseek/deepseek-coder", Provider::DeepSeek, &["deepseek"]),
+    spec("Grok", "x/grok", Provider::XAi, &[]),
+];
+
+/// The api names of `MODELS`, for code that only routes requests.
+pub const fn api_names() -> [&'static str; MODELS.len()] {
+    let mut names = [""; MODELS.len()];
+    let mut i = 0;
+    while i < MODELS.len() {
+        names[i] = MODELS[i].api_name;
+        i += 1;
+    }
+    names
+}
+
+pub fn lookup(api_name: &str) -> Option<&'static ModelSpec> {
+    MODELS.iter().find(|m| m.api_name == api_name)
+}
+
+/// The friendly name of a pool model; any other model goes by its api name.
+pub fn display_name(api_name: &str) -> &str {
+    lookup(api_name).map_or(api_name, |m| m.display)
+}
+
+/// The api name `name` refers to, ignoring case and surrounding space. Names that
+/// match no pool model pass through unchanged, so any OpenRouter model still works.
+pub fn resolve(name: &str) -> String {
+    let name = name.trim();
+    let matches = |m: &ModelSpec| {
+        let short = m.api_name.split_once('/').map_or(m.api_name, |(_, short)| short);
+        [m.api_name, short, m.display]
+            .iter()
+            .chain(m.aliases)
+            .any(|candidate| candidate.eq_ignore_ascii_case(name))
+    };
+    MODELS.iter().find(|m| matches(m)).map_or_else(|| name.to_string(), |m| m.api_name.to_string())
+}
+
+/// A model flag's value, as a clap value parser.
+pub fn parse_model(s: &str) -> Result<String, String> {
+    match resolve(s) {
+        model if model.is_empty() => Err("model name is empty".to_string()),
+        model => Ok(model),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_names_resolve_to_api_names() {
+        assert_eq!(resolve("haiku"), "anthropic/claude-3-haiku");
+        assert_eq!(resolve(" GPT-4o "), "openai/gpt-4o");
+        assert_eq!(resolve("claude-3-haiku"), "anthropic/claude-3-haiku");
+        assert_eq!(resolve("Command R+"), "cohere/command-r-plus");
+        assert_eq!(resolve("openai/gpt-4-turbo"), "openai/gpt-4-turbo");
+        assert!(parse_model("  ").is_err());
+        assert_eq!(display_name("x/grok"), "Grok");
+        assert_eq!(display_name("openai/gpt-4-turbo"), "openai/gpt-4-turbo");
+        assert_eq!(api_names()[2], "anthropic/claude-3-haiku");
+    }
+}
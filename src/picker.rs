@@ -0,0 +1,81 @@
+use dialoguer::{MultiSelect, Select};
+use std::io::IsTerminal;
+
+/// A model chosen in the `--pick-models` menu and where its requests go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pick {
+    pub model: &'static str,
+    /// Send an openai/* model straight to the OpenAI API rather than OpenRouter.
+    pub direct: bool,
+}
+
+/// The backend a model's requests go to, as shown in the menu.
+pub fn backend_label(model: &str, direct: bool) -> &'static str {
+    if model == "x/grok" {
+        "xAI"
+    } else if direct && model.starts_with("openai/") {
+        "OpenAI API"
+    } else {
+        "OpenRouter"
+    }
+}
+
+/// `--pick-models`: a checkbox menu over `models` (all checked to start), then, when
+/// OpenAI keys are available, a choice of backend for each selected openai/* model.
+/// `openai_direct` is the default for that choice.
+pub fn pick(models: &[&'static str], openai_keys: bool, openai_direct: bool) -> Result<Vec<Pick>, String> {
+    if !std::io::stdin().is_terminal() {
+        return Err("--pick-models needs a terminal".to_string());
+    }
+    let items: Vec<String> = models
+        .iter()
+        .map(|m| {
+            let name = match crate::models::lookup(m) {
+                Some(spec) => format!("{} by {}", spec.display, spec.provider.name()),
+                None => m.to_string(),
+            };
+            format!("{} (via {})", name, backend_label(m, openai_direct && openai_keys))
+        })
+        .collect();
+    let chosen = MultiSelect::new()
+        .with_prompt("Agents for this session (space toggles, enter confirms)")
+        .items(&items)
+        .defaults(&vec![true; items.len()])
+        .interact()
+        .map_err(|e| e.to_string())?;
+    if chosen.is_empty() {
+        return Err("no models selected".to_string());
+    }
+
+    let mut picks = Vec::new();
+    for i in chosen {
+        let model = models[i];
+        let direct = if openai_keys && model.starts_with("openai/") {
+            let backends = [backend_label(model, false), backend_label(model, true)];
+            Select::new()
+                .with_prompt(format!("Route {} through", model))
+                .items(&backends)
+                .default(usize::from(openai_direct))
+                .interact()
+                .map_err(|e| e.to_string())?
+                == 1
+        } else {
+            false
+        };
+        picks.push(Pick { model, direct });
+    }
+    Ok(picks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_openai_models_can_go_direct() {
+        assert_eq!(backend_label("openai/gpt-4o", true), "OpenAI API");
+        assert_eq!(backend_label("openai/gpt-4o", false), "OpenRouter");
+        assert_eq!(backend_label("google/gemini-pro", true), "OpenRouter");
+        assert_eq!(backend_label("x/grok", false), "xAI");
+    }
+}
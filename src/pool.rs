@@ -0,0 +1,108 @@
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+use tokio::sync::{Notify, Semaphore, SemaphorePermit};
+
+/// `--worker-threads N`: at most N rounds poll their agents at once, and records are
+/// committed in the order their rounds started, whichever finishes polling first.
+pub struct WorkerPool {
+    workers: Semaphore,
+    order: Mutex<Order>,
+    turn_changed: Notify,
+}
+
+#[derive(Default)]
+struct Order {
+    issued: u64,
+    /// The ticket whose round commits next.
+    next: u64,
+    /// Tickets done out of turn, waiting for `next` to catch up.
+    finished: BTreeSet<u64>,
+}
+
+/// A round's place in the pool: its worker slot and its position in commit order.
+/// Dropping it, committed or not, lets the next round commit.
+pub struct Slot<'a> {
+    pool: &'a WorkerPool,
+    ticket: u64,
+    _worker: SemaphorePermit<'a>,
+}
+
+impl WorkerPool {
+    pub fn new(workers: usize) -> Self {
+        Self {
+            workers: Semaphore::new(workers),
+            order: Mutex::new(Order::default()),
+            turn_changed: Notify::new(),
+        }
+    }
+
+    /// Waits for a free worker. Tickets are issued only once a worker is held, so a
+    /// round waiting to commit never waits on a round that hasn't started; the
+    /// semaphore is fair, so rounds start in arrival order.
+    pub async fn enter(&self) -> Slot<'_> {
+        let worker = self.workers.acquire().await.expect("the pool is never closed");
+        let mut order = self.order.lock().unwrap();
+        let ticket = order.issued;
+        order.issued += 1;
+        Slot {
+            pool: self,
+            ticket,
+            _worker: worker,
+        }
+    }
+}
+
+impl Slot<'_> {
+    /// Resolves once every round that started before this one has committed or failed.
+    pub async fn turn(&self) {
+        loop {
+            let changed = self.pool.turn_changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+            if self.pool.order.lock().unwrap().next == self.ticket {
+                return;
+            }
+            changed.await;
+        }
+    }
+}
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        let mut guard = self.pool.order.lock().unwrap();
+        let order = &mut *guard;
+        order.finished.insert(self.ticket);
+        while order.finished.remove(&order.next) {
+            order.next += 1;
+        }
+        drop(guard);
+        self.pool.turn_changed.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn commits_follow_start_order() {
+        let pool = WorkerPool::new(3);
+        let committed = Mutex::new(Vec::new());
+        let round = |name: &'static str, polling_ms: u64| {
+            let (pool, committed) = (&pool, &committed);
+            async move {
+                let slot = pool.enter().await;
+                tokio::time::sleep(Duration::from_millis(polling_ms)).await;
+                slot.turn().await;
+                committed.lock().unwrap().push(name);
+            }
+        };
+        // The first round polls slowest, yet commits first; the failed round doesn't block.
+        let failed = async {
+            let _slot = pool.enter().await;
+        };
+        tokio::join!(round("a", 60), failed, round("b", 10), round("c", 0));
+        assert_eq!(*committed.lock().unwrap(), ["a", "b", "c"]);
+    }
+}
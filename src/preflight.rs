@@ -0,0 +1,165 @@
+use futures::future::join_all;
+use std::sync::Arc;
+
+use crate::render::{paint, RenderOptions, COLOR_GREEN, COLOR_RED};
+use crate::solana::SolanaClient;
+use crate::store::LedgerStore;
+use crate::{AIProvider, AIResponse, ProviderError};
+
+/// Statement sent to every provider to prove it answers end to end.
+const PROBE_STATEMENT: &str = "Water is wet.";
+
+/// One line of the preflight report: what was checked and how it went.
+struct Check {
+    name: String,
+    outcome: Result<String, String>,
+}
+
+/// Sends the probe statement to every provider at once; one outcome per provider, in order.
+async fn probe_providers(providers: &[Box<dyn AIProvider>]) -> Vec<Result<String, String>> {
+    join_all(providers.iter().map(|p| p.validate(PROBE_STATEMENT, None)))
+        .await
+        .into_iter()
+        .map(|result| {
+            result
+                .map(|vote| format!("answered ({})", if vote.is_valid { "yes" } else { "no" }))
+                .map_err(|e| e.to_string())
+        })
+        .collect()
+}
+
+/// `--check-providers`: one line per provider on whether its reply parsed into the
+/// fields we rely on. Providers are built with `strict_json` on, so a changed
+/// response shape is a `Schema` error instead of whatever happened to parse.
+pub async fn check_providers(providers: &[Box<dyn AIProvider>]) -> bool {
+    let results = join_all(providers.iter().map(|p| p.validate(PROBE_STATEMENT, None))).await;
+    let opts = RenderOptions::default();
+    let mut passed = 0;
+    for (provider, result) in providers.iter().zip(&results) {
+        let (ok, report) = schema_report(result);
+        let mark = if ok { paint("ok  ", COLOR_GREEN, &opts) } else { paint("FAIL", COLOR_RED, &opts) };
+        println!("{} {:<40} {}", mark, provider.agent_name(), report);
+        passed += usize::from(ok);
+    }
+    println!("{} of {} providers match the schema", passed, providers.len());
+    passed == providers.len()
+}
+
+/// Whether a probe counts as a schema match, and what to print for it. A failure
+/// that isn't about the response shape (no key, HTTP error) fails the check too,
+/// since nothing could be verified, but is labeled as such.
+fn schema_report(result: &Result<AIResponse, ProviderError>) -> (bool, String) {
+    match result {
+        Ok(vote) => (true, format!("schema ok ({} answered)", vote.model)),
+        Err(ProviderError::Schema { path, problem, raw }) => {
+            (false, format!("schema mismatch at {}: {}\n     raw body: {}", path, problem, raw))
+        }
+        Err(e) => (false, format!("not checked: {}", e)),
+    }
+}
+
+/// Pings every provider, the Solana RPC and the store, prints a pass/fail report and
+/// returns whether everything passed. Nothing is recorded.
+pub async fn run(
+    providers: &[Box<dyn AIProvider>],
+    solana: Option<&SolanaClient>,
+    store: &Arc<dyn LedgerStore>,
+) -> bool {
+    let mut checks: Vec<Check> = probe_providers(providers)
+        .await
+        .into_iter()
+        .zip(providers)
+        .map(|(outcome, provider)| Check {
+            name: format!("provider {}", provider.agent_name()),
+            outcome,
+        })
+        .collect();
+
+    checks.push(Check {
+        name: "solana rpc".to_string(),
+        outcome: match solana {
+            Some(solana) => solana
+                .get_slot()
+                .await
+                .map(|slot| format!("slot {}", slot))
+                .map_err(|e| e.to_string()),
+            None => Ok("skipped (no --solana-url)".to_string()),
+        },
+    });
+
+    let probe = store.clone();
+    checks.push(Check {
+        name: "ledger store".to_string(),
+        outcome: tokio::task::spawn_blocking(move || probe.check_writable())
+            .await
+            .expect("store task panicked")
+            .map(|()| "writable".to_string())
+            .map_err(|e| e.to_string()),
+    });
+
+    let opts = RenderOptions::default();
+    for check in &checks {
+        match &check.outcome {
+            Ok(detail) => println!("{} {:<40} {}", paint("ok  ", COLOR_GREEN, &opts), check.name, detail),
+            Err(e) => println!("{} {:<40} {}", paint("FAIL", COLOR_RED, &opts), check.name, e),
+        }
+    }
+    let failed = checks.iter().filter(|c| c.outcome.is_err()).count();
+    println!("{} of {} checks passed", checks.len() - failed, checks.len());
+    failed == 0
+}
+
+/// `--live-banner`: probes every provider at startup and prints the model list with
+/// whether each one answered just now. Nothing is recorded.
+pub async fn print_banner(providers: &[Box<dyn AIProvider>]) {
+    let outcomes = probe_providers(providers).await;
+    let models: Vec<(&str, &str)> = providers.iter().map(|p| (p.agent_name(), p.model())).collect();
+    print!("{}", banner(&models, &outcomes, &RenderOptions::default()));
+}
+
+fn banner(models: &[(&str, &str)], outcomes: &[Result<String, String>], opts: &RenderOptions) -> String {
+    let up = outcomes.iter().filter(|o| o.is_ok()).count();
+    let mut out = format!("Models ({} of {} available):\n", up, outcomes.len());
+    for ((agent, model), outcome) in models.iter().zip(outcomes) {
+        let name = match crate::models::display_name(model) {
+            display if agent == model && display == *model => display.to_string(),
+            display if agent == model => format!("{} ({})", display, model),
+            _ => format!("{} ({})", agent, model),
+        };
+        match outcome {
+            Ok(_) => out.push_str(&format!("  {} {}\n", paint("\u{2713}", COLOR_GREEN, opts), name)),
+            Err(e) => out.push_str(&format!("  {} {}: {}\n", paint("\u{2717}", COLOR_RED, opts), name, e)),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_mismatches_show_the_raw_body() {
+        let mismatch = Err(ProviderError::Schema {
+            path: "$.choices".to_string(),
+            problem: "missing".to_string(),
+            raw: r#"{"id":"x","model":"m","output":"yes"}"#.to_string(),
+        });
+        let (ok, report) = schema_report(&mismatch);
+        assert!(!ok);
+        assert_eq!(report, "schema mismatch at $.choices: missing\n     raw body: {\"id\":\"x\",\"model\":\"m\",\"output\":\"yes\"}");
+        let (ok, report) = schema_report(&Err(ProviderError::MissingKey("X_API_KEY")));
+        assert_eq!((ok, report.as_str()), (false, "not checked: X_API_KEY not set"));
+    }
+
+    #[test]
+    fn banner_marks_each_model() {
+        let opts = RenderOptions { color: false, verbose: false };
+        let models = [("openai/gpt-4o", "openai/gpt-4o"), ("grok", "x/grok")];
+        let outcomes = [Ok("answered (yes)".to_string()), Err("XAI_API_KEY not set".to_string())];
+        assert_eq!(
+            banner(&models, &outcomes, &opts),
+            "Models (1 of 2 available):\n  \u{2713} GPT-4o (openai/gpt-4o)\n  \u{2717} grok (x/grok): XAI_API_KEY not set\n"
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A pipeline stage timed under `--profile`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Sending agent requests and reading their bodies, summed over agents.
+    AgentHttp,
+    /// Parsing agent reply bodies (JSON, or SSE chunks when streaming).
+    JsonParse,
+    Consensus,
+    /// Reading the slot to stamp on the record.
+    SolanaRpc,
+    /// Committing to the ledger and writing the store.
+    Persist,
+    /// A whole `run_round`, start to finish.
+    Round,
+}
+
+const STAGES: [Stage; 6] = [
+    Stage::AgentHttp,
+    Stage::JsonParse,
+    Stage::Consensus,
+    Stage::SolanaRpc,
+    Stage::Persist,
+    Stage::Round,
+];
+
+impl Stage {
+    fn label(self) -> &'static str {
+        match self {
+            Stage::AgentHttp => "agent HTTP",
+            Stage::JsonParse => "JSON parsing",
+            Stage::Consensus => "consensus",
+            Stage::SolanaRpc => "Solana RPC",
+            Stage::Persist => "persistence",
+            Stage::Round => "round total",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Timing {
+    calls: u64,
+    total: Duration,
+    max: Duration,
+}
+
+/// `--profile`: time spent per stage, accumulated over the session and reported
+/// when it ends.
+#[derive(Debug, Default)]
+pub struct Profile {
+    timings: Mutex<[Timing; STAGES.len()]>,
+}
+
+impl Profile {
+    pub fn record(&self, stage: Stage, elapsed: Duration) {
+        let i = STAGES.iter().position(|s| *s == stage).expect("every stage is listed");
+        let mut timings = self.timings.lock().unwrap();
+        let timing = &mut timings[i];
+        timing.calls += 1;
+        timing.total += elapsed;
+        timing.max = timing.max.max(elapsed);
+    }
+
+    /// One line per stage that ran: calls, total, mean and slowest, in milliseconds.
+    pub fn report(&self) -> String {
+        let timings = *self.timings.lock().unwrap();
+        let mut out = format!(
+            "{:<13} {:>6} {:>11} {:>9} {:>9}\n",
+            "stage", "calls", "total ms", "mean ms", "max ms"
+        );
+        for (stage, timing) in STAGES.iter().zip(timings) {
+            if timing.calls == 0 {
+                continue;
+            }
+            let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+            out.push_str(&format!(
+                "{:<13} {:>6} {:>11.1} {:>9.1} {:>9.1}\n",
+                stage.label(),
+                timing.calls,
+                ms(timing.total),
+                ms(timing.total) / timing.calls as f64,
+                ms(timing.max)
+            ));
+        }
+        out.push_str("Agents are queried in parallel, so agent HTTP can exceed the round total.\n");
+        out
+    }
+}
+
+/// Records the time since `started` against `stage`, when profiling.
+pub fn record(profile: Option<&Profile>, stage: Stage, started: Instant) {
+    if let Some(profile) = profile {
+        profile.record(stage, started.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_only_the_stages_that_ran() {
+        let profile = Profile::default();
+        profile.record(Stage::Consensus, Duration::from_millis(2));
+        profile.record(Stage::Consensus, Duration::from_millis(4));
+        let report = profile.report();
+        let consensus = report.lines().find(|l| l.starts_with("consensus")).unwrap();
+        let columns: Vec<&str> = consensus.split_whitespace().collect();
+        assert_eq!(columns, ["consensus", "2", "6.0", "3.0", "4.0"]);
+        assert!(!report.contains("Solana RPC"));
+    }
+}
@@ -0,0 +1,198 @@
+use serde_json::json;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::analytics::Agreement;
+use crate::config::OutputFormat;
+use crate::render::{self, RenderOptions};
+use crate::{redact, AIResponse, Abstention, Record};
+
+/// Prints agent progress and results during a console round, in the chosen format.
+/// `json` writes one event object per line for tooling. With a report file, records
+/// and summaries go there (uncolored) and the terminal keeps only the live progress;
+/// a `.jsonl` report file gets only the committed records, one bare JSON line each.
+pub struct Progress {
+    format: OutputFormat,
+    render: RenderOptions,
+    report: Option<(PathBuf, Mutex<File>)>,
+    /// The report file takes bare records rather than the rendered report.
+    records_only: bool,
+}
+
+/// A copy of `record` with its free text passed through `--redact`, for JSON output.
+pub fn redacted(record: &Record) -> Record {
+    let mut shown = record.clone();
+    shown.transaction.content = redact::apply(&record.transaction.content).into_owned();
+    if let Some(claim) = &mut shown.transaction.claim {
+        for part in [&mut claim.subject, &mut claim.predicate, &mut claim.object] {
+            *part = redact::apply(part).into_owned();
+        }
+    }
+    // Agents' justifications may quote the statement, and `details` repeats them.
+    shown.details = redact::apply(&record.details).into_owned();
+    for vote in &mut shown.votes {
+        if let Some(rationale) = &mut vote.rationale {
+            *rationale = redact::apply(rationale).into_owned();
+        }
+    }
+    if let Some(rationale) = &mut shown.consensus_rationale {
+        *rationale = redact::apply(rationale).into_owned();
+    }
+    if let Some(challenge) = &mut shown.devils_advocate {
+        challenge.argument = redact::apply(&challenge.argument).into_owned();
+    }
+    shown
+}
+
+impl Progress {
+    /// `no_color` is `--no-color`.
+    pub fn new(format: OutputFormat, output: Option<&Path>, no_color: bool) -> std::io::Result<Self> {
+        let mut render = RenderOptions::for_stdout(no_color);
+        render.color &= format == OutputFormat::Pretty && output.is_none();
+        let report = match output {
+            Some(path) => Some((path.to_path_buf(), Mutex::new(File::create(path)?))),
+            None => None,
+        };
+        let records_only = output.is_some_and(|path| path.extension().is_some_and(|ext| ext == "jsonl"));
+        Ok(Self { format, render, report, records_only })
+    }
+
+    /// Writes a line of the rendered report to the report file, or to stdout.
+    fn report(&self, text: &str) {
+        let Some((path, file)) = &self.report else {
+            println!("{}", text);
+            return;
+        };
+        if let Err(e) = writeln!(file.lock().unwrap(), "{}", text) {
+            tracing::warn!("could not write to {}: {}", path.display(), e);
+        }
+    }
+
+    pub fn querying(&self, agent_name: &str, model: &str) {
+        match self.format {
+            OutputFormat::Pretty => println!("  … {}", agent_label(agent_name, model)),
+            OutputFormat::Plain => println!("querying {}", agent_label(agent_name, model)),
+            OutputFormat::Json => emit(json!({ "event": "querying", "agent": agent_name, "model": model })),
+        }
+    }
+
+    pub fn voted(&self, vote: &AIResponse) {
+        let verdict = if vote.is_valid { "yes" } else { "no" };
+        let label = agent_label(&vote.agent_name, &vote.model);
+        let confidence = vote.confidence.map(|c| format!(" ({:.2})", c)).unwrap_or_default();
+        match self.format {
+            OutputFormat::Pretty => {
+                let verdict = render::vote_verdict(vote.is_valid, vote.confidence, &self.render);
+                println!("  ✓ {}: {}{}", label, verdict, confidence);
+            }
+            OutputFormat::Plain => println!("{}: {}{}", label, verdict, confidence),
+            OutputFormat::Json => emit(json!({
+                "event": "voted",
+                "agent": vote.agent_name,
+                "model": vote.model,
+                "vote": verdict,
+                "confidence": vote.confidence,
+                "attempts": vote.attempts,
+            })),
+        }
+    }
+
+    pub fn abstained(&self, abstention: &Abstention) {
+        let label = agent_label(&abstention.agent_name, &abstention.model);
+        match self.format {
+            OutputFormat::Pretty => println!("  ✗ {}: abstained ({})", label, abstention.reason),
+            OutputFormat::Plain => println!("{}: abstained ({})", label, abstention.reason),
+            OutputFormat::Json => emit(json!({
+                "event": "abstained",
+                "agent": abstention.agent_name,
+                "model": abstention.model,
+                "reason": abstention.reason,
+            })),
+        }
+    }
+
+    /// The committed record; `index` is its 1-based ledger position.
+    pub fn recorded(&self, index: usize, record: &Record) {
+        if let Some((path, _)) = self.report.as_ref().filter(|_| self.records_only) {
+            self.report(&serde_json::to_string(&redacted(record)).expect("records serialize"));
+            match self.format {
+                OutputFormat::Json => emit(json!({ "event": "recorded", "index": index, "id": record.id })),
+                _ => println!("Record {} written to {}", index, path.display()),
+            }
+            return;
+        }
+        match self.format {
+            OutputFormat::Json => {
+                self.report(&json!({ "event": "recorded", "index": index, "record": redacted(record) }).to_string())
+            }
+            _ => {
+                self.report(&render::render_record(index, record, &self.render));
+                if let Some((path, _)) = &self.report {
+                    println!("Record {} written to {}", index, path.display());
+                }
+            }
+        }
+    }
+
+    /// Agreement across the rounds of a `--repeat` run.
+    pub fn summary(&self, agreement: &Agreement) {
+        // A records-only file holds nothing else, so the summary stays on the terminal.
+        let report = |text: &str| match self.records_only {
+            true => println!("{}", text),
+            false => self.report(text),
+        };
+        match self.format {
+            OutputFormat::Json => report(&json!({ "event": "summary", "agreement": agreement }).to_string()),
+            _ => report(&format!(
+                "{} rounds: {} reached consensus, {:.0}% agree with the majority verdict; \
+                 truth score {:.1} ± {:.1}",
+                agreement.rounds,
+                agreement.consensus_rounds,
+                agreement.majority_share * 100.0,
+                agreement.mean_truth_score,
+                agreement.truth_score_stddev
+            )),
+        }
+    }
+
+    /// A round that recorded nothing.
+    pub fn failed(&self, message: &str) {
+        match self.format {
+            OutputFormat::Json => emit(json!({ "event": "failed", "error": message })),
+            _ => println!("{}", message),
+        }
+    }
+}
+
+fn agent_label(agent_name: &str, model: &str) -> String {
+    if agent_name == model {
+        agent_name.to_string()
+    } else {
+        format!("{} ({})", agent_name, model)
+    }
+}
+
+fn emit(event: serde_json::Value) {
+    println!("{}", event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_jsonl_report_file_gets_bare_records() {
+        let path = std::env::temp_dir().join(format!("truth-output-{}.jsonl", std::process::id()));
+        let progress = Progress::new(OutputFormat::Pretty, Some(&path), false).unwrap();
+        let record = crate::ledger::tests::record("Water is wet");
+        progress.recorded(1, &record);
+        progress.recorded(2, &record);
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<Record> = written.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].hash, record.hash);
+    }
+}
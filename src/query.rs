@@ -0,0 +1,68 @@
+use crate::config::Command;
+use crate::render::{self, RenderOptions};
+use crate::store::{self, SearchMode};
+use crate::Record;
+
+/// `show`, `list` and `search`: answers a query subcommand from the stored ledger's
+/// records, as the text to print. An unknown record is an error.
+pub fn run(command: &Command, records: &[Record], opts: &RenderOptions) -> Result<String, String> {
+    match command {
+        Command::Show { record } => {
+            let found = match record.parse::<usize>() {
+                Ok(n) => n.checked_sub(1).and_then(|i| records.get(i)).map(|r| (n, r)),
+                Err(_) => records
+                    .iter()
+                    .enumerate()
+                    .find(|(_, r)| r.id == *record || r.transaction.id == *record)
+                    .map(|(i, r)| (i + 1, r)),
+            };
+            let (index, found) = found.ok_or_else(|| format!("no record {}; the ledger has {}", record, records.len()))?;
+            Ok(render::render_record(index, found, &RenderOptions { verbose: true, ..*opts }))
+        }
+        Command::List { limit } => {
+            let skip = records.len().saturating_sub(*limit);
+            Ok(summaries(records.iter().enumerate().skip(skip), opts))
+        }
+        Command::Search { text } => {
+            let matching = records
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| store::scan_matches(&r.transaction.content, text, SearchMode::Phrase));
+            let out = summaries(matching, opts);
+            Ok(format!("{}{} matching records\n", out, out.lines().count()))
+        }
+        Command::Ask { .. } => unreachable!("ask is not a query"),
+    }
+}
+
+fn summaries<'a>(records: impl Iterator<Item = (usize, &'a Record)>, opts: &RenderOptions) -> String {
+    records.map(|(i, r)| render::render_summary(i + 1, r, opts) + "\n").collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::tests::record;
+
+    #[test]
+    fn shows_lists_and_searches_stored_records() {
+        let opts = RenderOptions { color: false, verbose: false };
+        let mut no = record("The Moon is cheese");
+        no.consensus = false;
+        let records = vec![record("Water is wet"), no, record("The sky is blue")];
+
+        let show = |arg: &str| run(&Command::Show { record: arg.to_string() }, &records, &opts);
+        assert!(show("2").unwrap().starts_with("Record 2 ("));
+        assert!(show(&records[2].id).unwrap().starts_with("Record 3 ("));
+        assert!(show(&records[0].transaction.id).unwrap().contains("Water is wet"));
+        assert_eq!(show("4").unwrap_err(), "no record 4; the ledger has 3");
+
+        let list = run(&Command::List { limit: 2 }, &records, &opts).unwrap();
+        let lines: Vec<&str> = list.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("    2  no   100  ") && lines[0].ends_with("  The Moon is cheese"), "{}", list);
+
+        let found = run(&Command::Search { text: "the MOON".to_string() }, &records, &opts).unwrap();
+        assert!(found.contains("The Moon is cheese") && found.ends_with("1 matching records\n"), "{}", found);
+    }
+}
@@ -0,0 +1,47 @@
+use regex::Regex;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+/// Shown in place of each `--redact` match.
+pub const MASK: &str = "[redacted]";
+
+static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+
+/// Sets the `--redact` patterns for the rest of the process; later calls are ignored.
+pub fn install(patterns: Vec<Regex>) {
+    let _ = PATTERNS.set(patterns);
+}
+
+fn apply_with<'a>(patterns: &[Regex], text: &'a str) -> Cow<'a, str> {
+    let mut out = Cow::Borrowed(text);
+    for pattern in patterns {
+        if let Cow::Owned(replaced) = pattern.replace_all(&out, MASK) {
+            out = Cow::Owned(replaced);
+        }
+    }
+    out
+}
+
+/// `text` as it may be displayed or logged: every `--redact` match masked. Stored
+/// records and prompts keep the full text.
+pub fn apply(text: &str) -> Cow<'_, str> {
+    match PATTERNS.get() {
+        Some(patterns) => apply_with(patterns, text),
+        None => Cow::Borrowed(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_every_match_of_every_pattern() {
+        let patterns = [Regex::new(r"\b\d{3}-\d{2}-\d{4}\b").unwrap(), Regex::new(r"(?i)alice").unwrap()];
+        assert_eq!(
+            apply_with(&patterns, "ALICE's SSN is 123-45-6789, not 987-65-4321"),
+            "[redacted]'s SSN is [redacted], not [redacted]"
+        );
+        assert!(matches!(apply_with(&patterns, "nothing here"), Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,44 @@
+/// Phrases `--detect-refusals` treats as a model declining to answer.
+pub const DEFAULT_PATTERNS: [&str; 12] = [
+    "i can't help with",
+    "i cannot help with",
+    "i can't assist",
+    "i cannot assist",
+    "i'm unable to",
+    "i am unable to",
+    "i'm not able to",
+    "i am not able to",
+    "i cannot provide",
+    "i can't provide",
+    "i must decline",
+    "as an ai",
+];
+
+/// The pattern a reply refused with, if any. Patterns are matched case-insensitively
+/// anywhere in the reply, but a reply that opens with a verdict ("no, I can't
+/// provide more detail") answered, so it isn't a refusal.
+pub fn refusal<'a>(reply: &str, patterns: &'a [String]) -> Option<&'a str> {
+    let reply = reply.to_lowercase().replace('\u{2019}', "'");
+    let first_word = reply.split(|c: char| !c.is_alphanumeric()).find(|w| !w.is_empty());
+    if matches!(first_word, Some("yes" | "no")) {
+        return None;
+    }
+    patterns.iter().map(String::as_str).find(|p| reply.contains(&p.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refusals_are_replies_without_a_verdict() {
+        let patterns: Vec<String> = DEFAULT_PATTERNS.iter().map(|p| p.to_string()).collect();
+        assert_eq!(refusal("I'm sorry, but I can't help with that.", &patterns), Some("i can't help with"));
+        assert_eq!(refusal("As an AI, I have no opinions.", &patterns), Some("as an ai"));
+        assert_eq!(refusal("I\u{2019}m unable to verify that", &patterns), Some("i'm unable to"));
+        assert_eq!(refusal("no, I can't provide more detail. 0.8", &patterns), None);
+        assert_eq!(refusal("\"Yes\" 0.9", &patterns), None);
+        assert_eq!(refusal("Not something I can assist... I cannot assist", &patterns), Some("i cannot assist"));
+        assert_eq!(refusal("The answer is unclear", &patterns), None);
+    }
+}
@@ -0,0 +1,45 @@
+use reqwest::{Client, Url};
+use std::time::Duration;
+
+use crate::cost;
+
+/// Largest questions file `--input-url` downloads.
+pub const MAX_QUESTIONS_BYTES: usize = 4 * 1024 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FetchError {
+    #[error("request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("server answered {0}")]
+    Status(reqwest::StatusCode),
+    #[error("file is larger than {} bytes", MAX_QUESTIONS_BYTES)]
+    TooLarge,
+    #[error("file is not UTF-8 text")]
+    NotText,
+}
+
+/// `--input-url`: downloads a questions file, in the `--input` format. The body is
+/// read in chunks and abandoned as soon as it passes `MAX_QUESTIONS_BYTES`, whatever
+/// Content-Length claims.
+pub async fn fetch_questions(client: &Client, url: &Url) -> Result<Vec<String>, FetchError> {
+    let mut response = client
+        .get(url.clone())
+        .timeout(Duration::from_secs(60))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(FetchError::Status(response.status()));
+    }
+    if response.content_length().is_some_and(|len| len > MAX_QUESTIONS_BYTES as u64) {
+        return Err(FetchError::TooLarge);
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > MAX_QUESTIONS_BYTES {
+            return Err(FetchError::TooLarge);
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let text = String::from_utf8(body).map_err(|_| FetchError::NotText)?;
+    Ok(cost::parse_questions(&text))
+}
@@ -0,0 +1,635 @@
+use serde::Serialize;
+use std::io::IsTerminal;
+
+use crate::{advocate, redact, AIResponse, Record};
+
+pub const COLOR_GREEN: &str = "\x1B[32m";
+pub const COLOR_RED: &str = "\x1B[31m";
+const COLOR_RESET: &str = "\x1B[0m";
+
+/// Display settings applied when turning stored records into text.
+#[derive(Debug, Clone, Copy)]
+pub struct RenderOptions {
+    /// Wrap yes/no in ANSI colors. Off when `NO_COLOR` is set.
+    pub color: bool,
+    /// List every agent's vote rather than just the tally.
+    pub verbose: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            color: std::env::var_os("NO_COLOR").is_none(),
+            verbose: false,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Options for text printed to stdout: colored only when it is a terminal and
+    /// neither `NO_COLOR` nor `--no-color` is set.
+    pub fn for_stdout(no_color: bool) -> Self {
+        let mut opts = Self::default();
+        opts.color &= !no_color && std::io::stdout().is_terminal();
+        opts
+    }
+}
+
+pub fn paint(text: &str, color: &str, opts: &RenderOptions) -> String {
+    if opts.color {
+        format!("{}{}{}", color, text, COLOR_RESET)
+    } else {
+        text.to_string()
+    }
+}
+
+/// 256-color shades from pale to saturated.
+const GREEN_SHADES: [u8; 5] = [194, 157, 120, 83, 46];
+const RED_SHADES: [u8; 5] = [224, 217, 210, 203, 196];
+
+/// ANSI color for a vote: green for yes, red for no, paler the closer its confidence
+/// is to a coin flip. A vote without a confidence counts as certain.
+pub fn confidence_color(is_valid: bool, confidence: Option<f32>) -> String {
+    let shades = if is_valid { GREEN_SHADES } else { RED_SHADES };
+    let strength = (confidence.unwrap_or(1.0).clamp(0.5, 1.0) - 0.5) * 2.0;
+    let shade = shades[((strength * (shades.len() - 1) as f32).round() as usize).min(shades.len() - 1)];
+    format!("\x1B[38;5;{}m", shade)
+}
+
+/// "yes"/"no" shaded by confidence.
+pub fn vote_verdict(is_valid: bool, confidence: Option<f32>, opts: &RenderOptions) -> String {
+    let verdict = if is_valid { "yes" } else { "no" };
+    paint(verdict, &confidence_color(is_valid, confidence), opts)
+}
+
+fn yes_no(valid: bool, opts: &RenderOptions) -> String {
+    if valid {
+        paint("yes", COLOR_GREEN, opts)
+    } else {
+        paint("no", COLOR_RED, opts)
+    }
+}
+
+/// An agent whose vote flipped between consecutive debate rounds.
+#[derive(Debug, PartialEq, Eq)]
+pub struct VoteChange<'a> {
+    pub agent_name: &'a str,
+    pub from: bool,
+    pub to: bool,
+    /// The 1-based round the new vote was cast in; the final votes are the last round.
+    pub round: usize,
+}
+
+/// Every flip across a record's debate rounds, in round then vote order. An agent
+/// that abstained in either round of a pair has nothing to compare.
+pub fn vote_changes(record: &Record) -> Vec<VoteChange<'_>> {
+    let rounds: Vec<&[AIResponse]> = record
+        .debate_rounds
+        .iter()
+        .map(Vec::as_slice)
+        .chain(std::iter::once(record.votes.as_slice()))
+        .collect();
+    let mut changes = Vec::new();
+    for (i, pair) in rounds.windows(2).enumerate() {
+        for vote in pair[1] {
+            let before = pair[0].iter().find(|v| v.agent_name == vote.agent_name);
+            if let Some(before) = before.filter(|b| b.is_valid != vote.is_valid) {
+                changes.push(VoteChange {
+                    agent_name: &vote.agent_name,
+                    from: before.is_valid,
+                    to: vote.is_valid,
+                    round: i + 2,
+                });
+            }
+        }
+    }
+    changes
+}
+
+/// Renders one record; `index` is its 1-based position in the ledger.
+pub fn render_record(index: usize, record: &Record, opts: &RenderOptions) -> String {
+    let mut out = String::new();
+    let yes = record.votes.iter().filter(|v| v.is_valid).count();
+    out.push_str(&format!("Record {} ({})\n", index, record.id));
+    out.push_str(&format!("  Statement: {}\n", redact::apply(&record.transaction.content)));
+    if let Some(claim) = &record.transaction.claim {
+        out.push_str(&format!("  Claim:     {}\n", redact::apply(&claim.triple())));
+    }
+    out.push_str(&format!(
+        "  Consensus: {} (truth score {:.0})\n",
+        yes_no(record.consensus, opts),
+        record.truth_score
+    ));
+    if !record.vetoed_by.is_empty() {
+        out.push_str(&format!("  Vetoed:    by {}\n", record.vetoed_by.join(", ")));
+    }
+    out.push_str(&format!("  Recorded:  {}\n", record.timestamp.to_rfc3339()));
+    if !record.debate_rounds.is_empty() {
+        let rounds: Vec<String> = record
+            .debate_rounds
+            .iter()
+            .enumerate()
+            .map(|(i, round)| {
+                let yes = round.iter().filter(|v| v.is_valid).count();
+                format!("{}: {} yes / {} no", i + 1, yes, round.len() - yes)
+            })
+            .collect();
+        out.push_str(&format!("  Debate:    {}\n", rounds.join("; ")));
+        for change in vote_changes(record) {
+            out.push_str(&format!(
+                "  Changed:   {}: {} \u{2192} {} (round {})\n",
+                change.agent_name,
+                yes_no(change.from, opts),
+                yes_no(change.to, opts),
+                change.round
+            ));
+        }
+    }
+    out.push_str(&format!("  Votes:     {} yes / {} no", yes, record.votes.len() - yes));
+    if !record.abstentions.is_empty() {
+        out.push_str(&format!(" / {} abstained", record.abstentions.len()));
+    }
+    out.push('\n');
+    if opts.verbose {
+        for vote in &record.votes {
+            let agent = if vote.agent_name == vote.model {
+                vote.agent_name.clone()
+            } else {
+                format!("{} ({})", vote.agent_name, vote.model)
+            };
+            let confidence = vote.confidence.map(|c| format!(" ({:.2})", c)).unwrap_or_default();
+            let attempts = match vote.attempts {
+                1 => String::new(),
+                n => format!(" after {} attempts", n),
+            };
+            let fingerprint = vote
+                .system_fingerprint
+                .as_ref()
+                .map(|f| format!(" [{}]", f))
+                .unwrap_or_default();
+            let verdict = vote_verdict(vote.is_valid, vote.confidence, opts);
+            out.push_str(&format!("    {}: {}{}{}{}\n", agent, verdict, confidence, attempts, fingerprint));
+            if let Some(rationale) = &vote.rationale {
+                out.push_str(&format!("      {}\n", redact::apply(rationale)));
+            }
+        }
+        for abstention in &record.abstentions {
+            out.push_str(&format!("    {}: abstained ({})\n", abstention.agent_name, abstention.reason));
+        }
+    }
+    if let Some(rationale) = &record.consensus_rationale {
+        out.push_str(&format!("  Rationale: {}\n", redact::apply(rationale)));
+    }
+    if let Some(challenge) = &record.devils_advocate {
+        out.push_str(&format!("  Advocate:  {}\n", challenge_summary(challenge)));
+    }
+    if let Some(label) = record.label {
+        out.push_str(&format!("  Label:     {}\n", label));
+    }
+    if record.pinned {
+        out.push_str("  Pinned:    yes\n");
+    }
+    for note in &record.human_notes {
+        out.push_str(&format!("  Note:      {} {}\n", note.at.format("%Y-%m-%d %H:%M"), note.text));
+    }
+    match (&record.solana_signature, record.solana_slot) {
+        (Some(signature), _) => out.push_str(&format!("  Solana:    anchored in {}\n", signature)),
+        (None, Some(slot)) => out.push_str(&format!("  Solana:    observed at slot {}\n", slot)),
+        (None, None) => out.push_str("  Solana:    not anchored\n"),
+    }
+    out.push_str(&format!("  Hash:      {}\n", record.hash));
+    out
+}
+
+/// One line for `list` and `search`: index, verdict, truth score, date and statement.
+pub fn render_summary(index: usize, record: &Record, opts: &RenderOptions) -> String {
+    // Padded outside the escapes, so colors don't throw the columns off.
+    let verdict = match record.consensus {
+        true => yes_no(true, opts),
+        false => format!("{} ", yes_no(false, opts)),
+    };
+    format!(
+        "{:>5}  {}  {:>3.0}  {}  {}",
+        index,
+        verdict,
+        record.truth_score,
+        record.timestamp.format("%Y-%m-%d %H:%M"),
+        redact::apply(&record.transaction.content)
+    )
+}
+
+pub fn render_ledger(records: &[Record], opts: &RenderOptions) -> String {
+    records
+        .iter()
+        .enumerate()
+        .map(|(i, record)| render_record(i + 1, record, opts))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn vote_label(vote: &AIResponse) -> String {
+    let mut confidence = vote.confidence.map(|c| format!(" ({:.2})", c)).unwrap_or_default();
+    if let Some(primary) = &vote.fallback_from {
+        confidence.push_str(&format!(", fallback for {}", primary));
+    }
+    if let Some(c) = &vote.self_consistency {
+        confidence.push_str(&format!(", {}/{} samples agree", c.agreeing, c.answered));
+        if c.answered < c.samples {
+            confidence.push_str(&format!(" ({} failed)", c.samples - c.answered));
+        }
+    }
+    let display = crate::models::display_name(&vote.model);
+    if vote.agent_name == vote.model && display == vote.model {
+        format!("{}{}", vote.agent_name, confidence)
+    } else if vote.agent_name == vote.model {
+        format!("{} [{}]{}", display, vote.model, confidence)
+    } else {
+        format!("{} [{}]{}", vote.agent_name, vote.model, confidence)
+    }
+}
+
+/// How a stored record reached its verdict, from its votes and provenance alone:
+/// the rule applied, the tally, who agreed and who dissented, each agent's stated
+/// reason and any `--explain-consensus` rationale. Records from before agents were
+/// asked for a reason carry only verdicts and confidences.
+pub fn render_why(index: usize, record: &Record, opts: &RenderOptions) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("Why record {} says {}\n", index, yes_no(record.consensus, opts)));
+    out.push_str(&format!("  Statement: {}\n", redact::apply(&record.transaction.content)));
+    let rule = match &record.provenance {
+        None => "not recorded (the record predates provenance)".to_string(),
+        Some(p) => {
+            let mut rule = match (p.quorum_providers, p.min_truth_score) {
+                (Some(quorum), _) => format!("{}: {} distinct providers must agree", p.consensus, quorum),
+                (_, Some(score)) => format!("{}: weighted truth score must reach {:.0}", p.consensus, score),
+                _ => p.consensus.clone(),
+            };
+            if !p.required_providers.is_empty() {
+                rule.push_str(&format!(", including {}", p.required_providers.join(", ")));
+            }
+            if let Some(quorum) = p.quorum {
+                rule.push_str(&format!(", {:.0}% of votes must say yes", quorum * 100.0));
+            }
+            if let Some(trust) = &p.trust_weights {
+                let weights: Vec<String> = trust.0.iter().map(|(m, w)| format!("{} {}", m, w)).collect();
+                rule.push_str(&format!(", votes weighted by trust ({}; others 1)", weights.join(", ")));
+            }
+            if !p.veto_models.is_empty() {
+                rule.push_str(&format!(", vetoed by a no from {}", p.veto_models.join(", ")));
+            }
+            if !p.required_yes_models.is_empty() {
+                rule.push_str(&format!(", needing a yes from {}", p.required_yes_models.join(", ")));
+            }
+            rule.push_str(&format!("; at least {} responses", p.min_responses));
+            if p.rounds > 1 {
+                rule.push_str(&format!("; {} debate rounds", p.rounds));
+            }
+            if p.error_policy.as_deref() == Some("no") {
+                rule.push_str("; failed agents counted as no");
+            }
+            if let Some(sha256) = &p.context_sha256 {
+                rule.push_str(&format!("; judged against context {}", &sha256[..12.min(sha256.len())]));
+            }
+            rule
+        }
+    };
+    out.push_str(&format!("  Rule:      {}\n", rule));
+    let yes = record.votes.iter().filter(|v| v.is_valid).count();
+    out.push_str(&format!(
+        "  Tally:     {} yes / {} no / {} abstained; truth score {:.0}\n",
+        yes,
+        record.votes.len() - yes,
+        record.abstentions.len(),
+        record.truth_score
+    ));
+    for (i, round) in record.debate_rounds.iter().enumerate() {
+        let yes = round.iter().filter(|v| v.is_valid).count();
+        out.push_str(&format!("  Round {}:   {} yes / {} no\n", i + 1, yes, round.len() - yes));
+    }
+    // Stored sides also name failed agents counted as "no"; older records only have votes.
+    let sides_stored = !record.majority_agents.is_empty() || !record.dissenting_agents.is_empty();
+    let (agreed, dissented): (Vec<String>, Vec<String>) = if !sides_stored {
+        let (agreed, dissented): (Vec<&AIResponse>, Vec<&AIResponse>) =
+            record.votes.iter().partition(|v| v.is_valid == record.consensus);
+        (agreed.into_iter().map(vote_label).collect(), dissented.into_iter().map(vote_label).collect())
+    } else {
+        let label = |agent: &String| match record.votes.iter().find(|v| &v.agent_name == agent) {
+            Some(vote) => vote_label(vote),
+            None => format!("{} (failed, counted as no)", agent),
+        };
+        (record.majority_agents.iter().map(label).collect(), record.dissenting_agents.iter().map(label).collect())
+    };
+    let list = |labels: Vec<String>| if labels.is_empty() { "none".to_string() } else { labels.join(", ") };
+    out.push_str(&format!("  Agreed:    {}\n", list(agreed)));
+    out.push_str(&format!("  Dissented: {}\n", list(dissented)));
+    if !record.vetoed_by.is_empty() {
+        out.push_str(&format!("  Vetoed:    by {}, overturning a majority yes\n", record.vetoed_by.join(", ")));
+    }
+    for abstention in &record.abstentions {
+        out.push_str(&format!("  Abstained: {} ({})\n", abstention.agent_name, abstention.reason));
+    }
+    for vote in &record.votes {
+        if let Some(rationale) = &vote.rationale {
+            out.push_str(&format!("  Reason:    {}: {}\n", vote.agent_name, redact::apply(rationale)));
+        }
+    }
+    match &record.consensus_rationale {
+        Some(rationale) => out.push_str(&format!("  Rationale: {}\n", redact::apply(rationale))),
+        None if record.votes.iter().any(|v| v.rationale.is_some()) => {}
+        None => out.push_str("  Rationale: none stored; agents gave only a verdict and a confidence\n"),
+    }
+    if let Some(challenge) = &record.devils_advocate {
+        out.push_str(&format!("  Advocate:  {}\n", challenge_summary(challenge)));
+    }
+    out
+}
+
+/// The devil's advocate's finding and its case, for `render_record` and `render_why`.
+fn challenge_summary(challenge: &advocate::Challenge) -> String {
+    let finding = if challenge.credible {
+        "credible counterargument"
+    } else {
+        "no credible counterargument, verdict strengthened"
+    };
+    format!("{} [{}]: {}", finding, challenge.model, redact::apply(&challenge.argument))
+}
+
+/// Width of each record's column in `render_compare`; longer values wrap.
+const COMPARE_WIDTH: usize = 36;
+const COMPARE_LABEL_WIDTH: usize = 11;
+
+/// `text` split into lines of at most `width` characters, breaking at spaces where
+/// it can.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        // A word longer than a whole line is cut wherever it has to be.
+        while word.len() > width {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            lines.push(word.drain(..width).collect());
+        }
+        let word: String = word.into_iter().collect();
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn tally(record: &Record) -> String {
+    let yes = record.votes.iter().filter(|v| v.is_valid).count();
+    let mut out = format!("{} yes / {} no", yes, record.votes.len() - yes);
+    if !record.abstentions.is_empty() {
+        out.push_str(&format!(" / {} abstained", record.abstentions.len()));
+    }
+    out
+}
+
+/// Two records side by side in aligned columns; indexes are 1-based ledger positions.
+pub fn render_compare(left: (usize, &Record), right: (usize, &Record), opts: &RenderOptions) -> String {
+    let records = [left.1, right.1];
+    let cells = |f: &dyn Fn(&Record) -> String| records.map(f);
+    let rows: Vec<(&str, [String; 2])> = vec![
+        ("Record", [format!("{}", left.0), format!("{}", right.0)]),
+        ("Statement", cells(&|r| redact::apply(&r.transaction.content).into_owned())),
+        ("Consensus", cells(&|r| if r.consensus { "yes" } else { "no" }.to_string())),
+        ("Truth", cells(&|r| format!("{:.0}", r.truth_score))),
+        ("Votes", cells(&tally)),
+        ("Recorded", cells(&|r| r.timestamp.format("%Y-%m-%d %H:%M:%S").to_string())),
+        ("Label", cells(&|r| r.label.map_or("-".to_string(), |l| l.to_string()))),
+        ("Solana", cells(&|r| r.solana_slot.map_or("not anchored".to_string(), |s| format!("slot {}", s)))),
+    ];
+
+    let mut out = String::new();
+    for (label, values) in rows {
+        let [left_lines, right_lines] = values.map(|v| wrap(&v, COMPARE_WIDTH));
+        for i in 0..left_lines.len().max(right_lines.len()) {
+            let label = if i == 0 { label } else { "" };
+            let cell = |lines: &[String], record: &Record| {
+                let text = lines.get(i).map(String::as_str).unwrap_or_default();
+                // Pad outside the color so escape codes don't skew the columns.
+                let padding = " ".repeat(COMPARE_WIDTH.saturating_sub(text.chars().count()));
+                if label == "Consensus" {
+                    let color = if record.consensus { COLOR_GREEN } else { COLOR_RED };
+                    format!("{}{}", paint(text, color, opts), padding)
+                } else {
+                    format!("{}{}", text, padding)
+                }
+            };
+            let line = format!(
+                "{:<label_width$} {} | {}",
+                label,
+                cell(&left_lines, records[0]),
+                cell(&right_lines, records[1]),
+                label_width = COMPARE_LABEL_WIDTH
+            );
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Widest bar `/chart` draws, in characters.
+const CHART_WIDTH: usize = 40;
+
+/// A record's tally, counted and confidence-weighted, for `/chart` and `/api/chart`.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct VoteChart {
+    pub yes: usize,
+    pub no: usize,
+    pub abstained: usize,
+    /// Sum of the yes votes' confidences; a vote without one counts fully, as in
+    /// the truth score.
+    pub yes_weight: f64,
+    pub no_weight: f64,
+}
+
+pub fn vote_chart(record: &Record) -> VoteChart {
+    let weight = |valid: bool| -> f64 {
+        record
+            .votes
+            .iter()
+            .filter(|v| v.is_valid == valid)
+            .map(|v| v.confidence.unwrap_or(1.0) as f64)
+            .sum()
+    };
+    VoteChart {
+        yes: record.votes.iter().filter(|v| v.is_valid).count(),
+        no: record.votes.iter().filter(|v| !v.is_valid).count(),
+        abstained: record.abstentions.len(),
+        yes_weight: weight(true),
+        no_weight: weight(false),
+    }
+}
+
+/// `value` of `max` as a run of `#`, at least one for anything above zero.
+fn bar(value: f64, max: f64) -> String {
+    if value <= 0.0 || max <= 0.0 {
+        return String::new();
+    }
+    let len = ((value / max) * CHART_WIDTH as f64).round() as usize;
+    "#".repeat(len.clamp(1, CHART_WIDTH))
+}
+
+/// An ASCII bar chart of a record's yes/no/abstain counts, then of the yes and no
+/// votes weighted by confidence. Bars in each chart scale to its largest value.
+pub fn render_chart(index: usize, record: &Record, opts: &RenderOptions) -> String {
+    let chart = vote_chart(record);
+    let mut out = String::new();
+    out.push_str(&format!("Votes on record {}, which says {}\n", index, yes_no(record.consensus, opts)));
+    let max = chart.yes.max(chart.no).max(chart.abstained) as f64;
+    for (label, count, color) in [("yes", chart.yes, Some(COLOR_GREEN)), ("no", chart.no, Some(COLOR_RED)), ("abstained", chart.abstained, None)] {
+        let bar = bar(count as f64, max);
+        let bar = match color {
+            Some(color) if !bar.is_empty() => paint(&bar, color, opts),
+            _ => bar,
+        };
+        out.push_str(&format!("  {:<10} {} {}\n", label, bar, count));
+    }
+    out.push_str("Weighted by confidence\n");
+    let max = chart.yes_weight.max(chart.no_weight);
+    for (label, weight, color) in [("yes", chart.yes_weight, COLOR_GREEN), ("no", chart.no_weight, COLOR_RED)] {
+        let bar = bar(weight, max);
+        let bar = if bar.is_empty() { bar } else { paint(&bar, color, opts) };
+        out.push_str(&format!("  {:<10} {} {:.2}\n", label, bar, weight));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn confidence_shades_run_from_pale_to_saturated() {
+        assert_eq!(confidence_color(true, Some(0.5)), "\x1B[38;5;194m");
+        assert_eq!(confidence_color(true, Some(1.0)), "\x1B[38;5;46m");
+        assert_eq!(confidence_color(true, None), "\x1B[38;5;46m");
+        assert_eq!(confidence_color(false, Some(0.75)), "\x1B[38;5;210m");
+        // Below a coin flip is as pale as it gets.
+        assert_eq!(confidence_color(false, Some(0.1)), "\x1B[38;5;224m");
+    }
+
+    #[test]
+    fn wrap_breaks_at_spaces_and_cuts_overlong_words() {
+        assert_eq!(wrap("the quick brown fox", 9), ["the quick", "brown fox"]);
+        assert_eq!(wrap("abcdefghij k", 4), ["abcd", "efgh", "ij k"]);
+        assert_eq!(wrap("", 4), [""]);
+    }
+
+    #[test]
+    fn compare_aligns_both_records_in_columns() {
+        let opts = RenderOptions { color: false, verbose: false };
+        let mut right = crate::ledger::tests::record("The moon is made of cheese and other dairy products");
+        right.consensus = false;
+        let out = render_compare((3, &crate::ledger::tests::record("Water is wet")), (7, &right), &opts);
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines[0].starts_with("Record      3") && lines[0].ends_with("| 7"));
+        assert!(lines[1].starts_with("Statement   Water is wet"));
+        // The long statement wraps onto a second line under the right-hand column.
+        assert!(lines[2].trim_start().starts_with('|'));
+        let bar = lines[0].find('|').unwrap();
+        assert!(lines.iter().all(|l| l.find('|') == Some(bar)), "{}", out);
+        assert!(out.contains("yes") && lines.iter().any(|l| l.starts_with("Consensus") && l.ends_with("| no")));
+    }
+
+    #[test]
+    fn chart_scales_bars_to_the_largest_count() {
+        let opts = RenderOptions { color: false, verbose: false };
+        let mut record = crate::ledger::tests::record("Water is wet");
+        let vote = record.votes[0].clone();
+        record.votes = vec![vote.clone(), vote.clone(), AIResponse { is_valid: false, confidence: Some(0.5), ..vote }];
+        record.votes[0].confidence = None;
+        let chart = vote_chart(&record);
+        assert_eq!((chart.yes, chart.no, chart.abstained), (2, 1, 0));
+        assert!((chart.yes_weight - 1.9).abs() < 1e-6 && (chart.no_weight - 0.5).abs() < 1e-6);
+
+        let out = render_chart(4, &record, &opts);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "Votes on record 4, which says yes");
+        assert_eq!(lines[1], format!("  yes        {} 2", "#".repeat(40)));
+        assert_eq!(lines[2], format!("  no         {} 1", "#".repeat(20)));
+        assert_eq!(lines[3], "  abstained   0");
+        assert_eq!(lines[6], format!("  no         {} 0.50", "#".repeat(11)));
+    }
+
+    #[test]
+    fn why_names_the_rule_and_the_dissenters() {
+        let opts = RenderOptions { color: false, verbose: false };
+        let mut record = crate::ledger::tests::record("Water is wet");
+        let vote = record.votes[0].clone();
+        record.votes.push(AIResponse {
+            agent_name: "agent-2".to_string(),
+            model: "x/grok".to_string(),
+            is_valid: false,
+            confidence: Some(0.55),
+            ..vote
+        });
+        let out = render_why(4, &record, &opts);
+        assert!(out.starts_with("Why record 4 says yes\n"), "{}", out);
+        assert!(out.contains("  Rule:      not recorded"), "{}", out);
+        assert!(out.contains("  Tally:     1 yes / 1 no / 0 abstained"), "{}", out);
+        assert!(out.contains("  Agreed:    agent [openai/gpt-4o] (0.90)\n"), "{}", out);
+        assert!(out.contains("  Dissented: agent-2 [x/grok] (0.55)\n"), "{}", out);
+
+        record.majority_agents = vec!["agent".to_string()];
+        record.dissenting_agents = vec!["agent-2".to_string(), "agent-3".to_string()];
+        let out = render_why(4, &record, &opts);
+        assert!(out.contains("  Dissented: agent-2 [x/grok] (0.55), agent-3 (failed, counted as no)\n"), "{}", out);
+        assert!(out.contains("  Rationale: none stored"), "{}", out);
+
+        record.votes[1].rationale = Some("Water is what makes things wet.".to_string());
+        let out = render_why(4, &record, &opts);
+        assert!(out.contains("  Reason:    agent-2: Water is what makes things wet.\n"), "{}", out);
+        assert!(!out.contains("  Rationale:"), "{}", out);
+
+        record.devils_advocate = Some(advocate::Challenge {
+            model: "openai/gpt-4o-mini".to_string(),
+            credible: false,
+            argument: "Wetness is what water does to other things.".to_string(),
+        });
+        let out = render_why(4, &record, &opts);
+        assert!(out.ends_with(
+            "  Advocate:  no credible counterargument, verdict strengthened [openai/gpt-4o-mini]: Wetness is what water does to other things.\n"
+        ), "{}", out);
+    }
+
+    #[test]
+    fn debate_flips_are_listed_by_round() {
+        let opts = RenderOptions { color: false, verbose: false };
+        let mut record = crate::ledger::tests::record("Water is wet");
+        let vote = |agent: &str, is_valid: bool| AIResponse {
+            agent_name: agent.to_string(),
+            is_valid,
+            ..record.votes[0].clone()
+        };
+        let round_1 = vec![vote("Agent 1", true), vote("Agent 3", false)];
+        let round_2 = vec![vote("Agent 1", true), vote("Agent 3", true)];
+        record.votes = vec![vote("Agent 1", false)];
+        record.debate_rounds = vec![round_1, round_2];
+        let changes = vote_changes(&record);
+        assert_eq!(changes.len(), 2);
+        assert_eq!((changes[0].agent_name, changes[0].from, changes[0].round), ("Agent 3", false, 2));
+        assert_eq!((changes[1].agent_name, changes[1].to, changes[1].round), ("Agent 1", false, 3));
+        let out = render_record(1, &record, &opts);
+        assert!(out.contains("  Changed:   Agent 3: no \u{2192} yes (round 2)\n"), "{}", out);
+    }
+
+    #[test]
+    fn no_color_renders_plain_verdicts() {
+        let opts = RenderOptions { color: false, verbose: true };
+        assert_eq!(vote_verdict(true, Some(0.6), &opts), "yes");
+    }
+}
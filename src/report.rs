@@ -0,0 +1,170 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::analytics::{self, AccuracyReport};
+use crate::Record;
+
+/// How one model voted over the session.
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct ModelSummary {
+    pub model: String,
+    pub votes: usize,
+    pub abstentions: usize,
+    /// Votes on the recorded verdict's side.
+    pub agreed: usize,
+    /// `agreed / votes`; `None` without votes.
+    pub agreement: Option<f64>,
+}
+
+/// `--report`: what one run recorded, written on exit.
+#[derive(Debug, Serialize)]
+pub struct SessionReport {
+    pub started: DateTime<Utc>,
+    pub finished: DateTime<Utc>,
+    pub duration_secs: f64,
+    /// Records committed during the session; each holds one transaction.
+    pub transactions: usize,
+    pub consensus_passed: usize,
+    pub consensus_failed: usize,
+    pub models: Vec<ModelSummary>,
+    /// Against the labels, when any of the session's records carries one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accuracy: Option<AccuracyReport>,
+    /// Projected from prompt sizes and list prices, like `--estimate`; providers'
+    /// bills are authoritative.
+    pub projected_cost_usd: f64,
+    pub head_hash: String,
+}
+
+/// Summarizes `records`, those committed during the session, with `cost` the
+/// projected USD of one record.
+pub fn summarize(
+    records: &[&Record],
+    started: DateTime<Utc>,
+    head_hash: String,
+    cost: impl Fn(&Record) -> f64,
+) -> SessionReport {
+    let finished = Utc::now();
+    let mut models: BTreeMap<&str, ModelSummary> = BTreeMap::new();
+    for record in records {
+        for vote in &record.votes {
+            let model = summary(&mut models, &vote.model);
+            model.votes += 1;
+            model.agreed += usize::from(vote.is_valid == record.consensus);
+            model.agreement = Some(model.agreed as f64 / model.votes as f64);
+        }
+        for abstention in &record.abstentions {
+            summary(&mut models, &abstention.model).abstentions += 1;
+        }
+    }
+    let accuracy = analytics::accuracy(records.iter().copied());
+    let consensus_passed = records.iter().filter(|r| r.consensus).count();
+    SessionReport {
+        started,
+        finished,
+        duration_secs: (finished - started).num_milliseconds() as f64 / 1000.0,
+        transactions: records.len(),
+        consensus_passed,
+        consensus_failed: records.len() - consensus_passed,
+        models: models.into_values().collect(),
+        accuracy: (accuracy.labeled_records > 0).then_some(accuracy),
+        projected_cost_usd: records.iter().fold(0.0, |usd, r| usd + cost(r)),
+        head_hash,
+    }
+}
+
+fn summary<'a, 'b>(models: &'b mut BTreeMap<&'a str, ModelSummary>, model: &'a str) -> &'b mut ModelSummary {
+    models.entry(model).or_insert_with(|| ModelSummary { model: model.to_string(), ..Default::default() })
+}
+
+fn percent(share: Option<f64>) -> String {
+    share.map_or("-".to_string(), |s| format!("{:.1}%", s * 100.0))
+}
+
+pub fn to_markdown(report: &SessionReport) -> String {
+    let mut out = String::from("# Session report\n\n");
+    out.push_str(&format!(
+        "- Started: {}\n- Finished: {} ({:.1} s)\n",
+        report.started.to_rfc3339(),
+        report.finished.to_rfc3339(),
+        report.duration_secs
+    ));
+    out.push_str(&format!(
+        "- Transactions: {} ({} reached consensus, {} did not)\n",
+        report.transactions, report.consensus_passed, report.consensus_failed
+    ));
+    out.push_str(&format!("- Projected cost: ${:.4}\n", report.projected_cost_usd));
+    out.push_str(&format!("- Chain head: `{}`\n\n", report.head_hash));
+
+    out.push_str("## Models\n\n| Model | Votes | Abstained | Agreed with verdict | Accuracy |\n|---|---|---|---|---|\n");
+    for model in &report.models {
+        let accuracy = report
+            .accuracy
+            .as_ref()
+            .and_then(|a| a.models.iter().find(|m| m.model == model.model))
+            .and_then(|m| m.accuracy);
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            model.model,
+            model.votes,
+            model.abstentions,
+            percent(model.agreement),
+            percent(accuracy)
+        ));
+    }
+    if let Some(accuracy) = &report.accuracy {
+        out.push_str(&format!(
+            "\n{} labeled records: votes {} right, verdicts {} right.\n",
+            accuracy.labeled_records,
+            percent(accuracy.overall.accuracy),
+            percent(accuracy.verdicts.accuracy)
+        ));
+    }
+    out
+}
+
+/// Writes `report` to `path`: JSON for a `.json` path, Markdown otherwise.
+pub fn write(path: &Path, report: &SessionReport) -> std::io::Result<()> {
+    let body = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::to_string_pretty(report).expect("reports serialize")
+    } else {
+        to_markdown(report)
+    };
+    std::fs::write(path, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::tests::record;
+    use crate::Abstention;
+
+    #[test]
+    fn summarizes_verdicts_and_per_model_agreement() {
+        let passed = record("Water is wet");
+        let mut failed = record("The moon is cheese");
+        failed.consensus = false;
+        failed.label = Some(false);
+        failed.abstentions.push(Abstention {
+            agent_name: "x/grok".to_string(),
+            model: "x/grok".to_string(),
+            reason: "timeout".to_string(),
+            refusal: false,
+        });
+        let started = Utc::now();
+        let report = summarize(&[&passed, &failed], started, "abc".to_string(), |_| 0.25);
+        assert_eq!((report.transactions, report.consensus_passed, report.consensus_failed), (2, 1, 1));
+        assert_eq!(report.projected_cost_usd, 0.5);
+        let gpt = &report.models[0];
+        assert_eq!((gpt.votes, gpt.agreed, gpt.agreement), (2, 1, Some(0.5)));
+        assert_eq!((report.models[1].model.as_str(), report.models[1].abstentions), ("x/grok", 1));
+        assert_eq!(report.accuracy.as_ref().map(|a| a.labeled_records), Some(1));
+
+        let markdown = to_markdown(&report);
+        assert!(markdown.contains("- Transactions: 2 (1 reached consensus, 1 did not)"));
+        assert!(markdown.contains("| openai/gpt-4o | 2 | 0 | 50.0% | 0.0% |"), "{}", markdown);
+        assert!(markdown.contains("- Chain head: `abc`"));
+    }
+}
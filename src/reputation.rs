@@ -0,0 +1,172 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::Record;
+
+/// One model's track record. The score starts at 0.5 and moves towards the share of
+/// rounds in which the model answered and agreed with the verdict; an abstention
+/// counts as a round it didn't agree in. Under `--half-life-days` each round counts
+/// half as much for every half-life it is older than the model's latest round.
+#[derive(Debug, Default, Clone, Serialize, PartialEq)]
+pub struct ModelReputation {
+    pub model: String,
+    pub agreed: usize,
+    pub disagreed: usize,
+    pub abstained: usize,
+    pub score: f64,
+    /// Agreed and total rounds, decayed to `as_of`.
+    #[serde(skip)]
+    weighted_agreed: f64,
+    #[serde(skip)]
+    weighted_rounds: f64,
+    #[serde(skip)]
+    as_of: Option<DateTime<Utc>>,
+}
+
+impl ModelReputation {
+    fn add(&mut self, at: DateTime<Utc>, agreed: bool, half_life_days: Option<f64>) {
+        let weight = match self.as_of {
+            // An imported record older than what is already counted.
+            Some(as_of) if at < as_of => decay(as_of - at, half_life_days),
+            as_of => {
+                let factor = as_of.map_or(1.0, |as_of| decay(at - as_of, half_life_days));
+                self.weighted_agreed *= factor;
+                self.weighted_rounds *= factor;
+                self.as_of = Some(at);
+                1.0
+            }
+        };
+        self.weighted_rounds += weight;
+        if agreed {
+            self.weighted_agreed += weight;
+        }
+        // Laplace smoothing: one imaginary agreement and one miss.
+        self.score = (self.weighted_agreed + 1.0) / (self.weighted_rounds + 2.0);
+    }
+}
+
+/// How much a round `age` old still counts; always 1 without a half-life.
+pub fn decay(age: chrono::Duration, half_life_days: Option<f64>) -> f64 {
+    match half_life_days {
+        Some(days) => 0.5f64.powf(age.num_seconds() as f64 / (days * 86_400.0)),
+        None => 1.0,
+    }
+}
+
+/// Reputation of every model seen in the ledger. It is derived from the recorded
+/// votes and abstentions, so it carries across sessions with the ledger itself.
+#[derive(Debug, Default)]
+pub struct ReputationBook {
+    models: BTreeMap<String, ModelReputation>,
+    half_life_days: Option<f64>,
+}
+
+impl ReputationBook {
+    pub fn from_records(records: &[Record], half_life_days: Option<f64>) -> Self {
+        let mut book = Self {
+            half_life_days,
+            ..Self::default()
+        };
+        for record in records {
+            book.observe(record);
+        }
+        book
+    }
+
+    fn entry(&mut self, model: &str) -> &mut ModelReputation {
+        self.models.entry(model.to_string()).or_insert_with(|| ModelReputation {
+            model: model.to_string(),
+            ..Default::default()
+        })
+    }
+
+    /// Updates the models that took part in a committed record.
+    pub fn observe(&mut self, record: &Record) {
+        let half_life_days = self.half_life_days;
+        for vote in &record.votes {
+            let entry = self.entry(&vote.model);
+            let agreed = vote.is_valid == record.consensus;
+            if agreed {
+                entry.agreed += 1;
+            } else {
+                entry.disagreed += 1;
+            }
+            entry.add(record.timestamp, agreed, half_life_days);
+        }
+        for abstention in &record.abstentions {
+            let entry = self.entry(&abstention.model);
+            entry.abstained += 1;
+            entry.add(record.timestamp, false, half_life_days);
+        }
+    }
+
+    /// A model's vote weight under weighted-majority; 0.5 for a model with no history.
+    pub fn weight(&self, model: &str) -> f64 {
+        self.models.get(model).map_or(0.5, |r| r.score)
+    }
+
+    /// Every model's reputation, best first.
+    pub fn ranked(&self) -> Vec<ModelReputation> {
+        let mut ranked: Vec<ModelReputation> = self.models.values().cloned().collect();
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.model.cmp(&b.model)));
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::tests::record;
+    use crate::{AIResponse, Abstention};
+
+    #[test]
+    fn abstentions_and_dissent_cost_reputation() {
+        let mut dissent = record("b");
+        let vote = dissent.votes[0].clone();
+        dissent.votes.push(AIResponse {
+            model: "x/grok".to_string(),
+            is_valid: false,
+            ..vote
+        });
+        dissent.abstentions.push(Abstention {
+            agent_name: "cohere".to_string(),
+            model: "cohere/command-r-plus".to_string(),
+            reason: "timeout".to_string(),
+            refusal: false,
+        });
+        let book = ReputationBook::from_records(&[record("a"), dissent], None);
+
+        // gpt-4o agreed twice: (2 + 1) / (2 + 2).
+        assert_eq!(book.weight("openai/gpt-4o"), 0.75);
+        assert!((book.weight("x/grok") - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(book.weight("cohere/command-r-plus"), book.weight("x/grok"));
+        assert_eq!(book.weight("never/seen"), 0.5);
+        assert_eq!(book.ranked()[0].model, "openai/gpt-4o");
+    }
+
+    #[test]
+    fn a_half_life_lets_recent_dissent_outweigh_old_agreement() {
+        let start = record("a").timestamp;
+        let mut records: Vec<Record> = (0..10)
+            .map(|i| {
+                let mut r = record(&format!("old {}", i));
+                r.timestamp = start + chrono::Duration::hours(i);
+                r
+            })
+            .collect();
+        for i in 0..3 {
+            let mut r = record(&format!("new {}", i));
+            r.timestamp = start + chrono::Duration::days(90) + chrono::Duration::hours(i);
+            r.votes[0].is_valid = !r.consensus;
+            records.push(r);
+        }
+        // (10 + 1) / (13 + 2) without decay.
+        let flat = ReputationBook::from_records(&records, None).weight("openai/gpt-4o");
+        assert!((flat - 11.0 / 15.0).abs() < 1e-12);
+        let decayed = ReputationBook::from_records(&records, Some(30.0)).weight("openai/gpt-4o");
+        assert!(decayed < 0.4, "{}", decayed);
+        assert_eq!(ReputationBook::from_records(&records, Some(30.0)).ranked()[0].agreed, 10);
+        assert!((decay(chrono::Duration::days(30), Some(30.0)) - 0.5).abs() < 1e-12);
+    }
+}
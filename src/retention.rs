@@ -0,0 +1,77 @@
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::ledger::ChainError;
+use crate::store::StoreError;
+use crate::{persist, AppState, LEDGER};
+
+/// How often `--retention-days` re-applies the window while running.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, thiserror::Error)]
+pub enum PurgeError {
+    #[error(transparent)]
+    Chain(#[from] ChainError),
+    #[error("purge not persisted: {0}")]
+    Store(#[from] StoreError),
+    /// `--append-only` forbids removing records.
+    #[error("the ledger is append-only; records cannot be purged")]
+    AppendOnly,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PurgeSummary {
+    pub purged: usize,
+    pub remaining: usize,
+    /// Head of the re-linked chain.
+    pub head_hash: String,
+}
+
+/// Removes every unpinned record older than `days` days and re-links the rest from
+/// genesis, so every remaining hash changes. The store is rewritten to match; if that
+/// fails the in-memory ledger is left as it was.
+pub async fn purge(state: &AppState, days: u64) -> Result<PurgeSummary, PurgeError> {
+    if state.config.append_only {
+        return Err(PurgeError::AppendOnly);
+    }
+    let _commit = state.commit_lock.lock().await;
+    let cutoff = Utc::now() - chrono::Duration::days(days as i64);
+    let (retained, purged) = LEDGER.read().await.retained_since(cutoff)?;
+    if purged > 0 {
+        let records = retained.records().to_vec();
+        persist(&state.store, move |store| store.replace_all(&records)).await?;
+        *LEDGER.write().await = retained;
+        tracing::info!("purged {} records older than {} days", purged, days);
+    }
+    let ledger = LEDGER.read().await;
+    if purged > 0 {
+        state.audit(|| {
+            format!(
+                "purge: {} unpinned records older than {} days removed; {} remain, head {}",
+                purged,
+                days,
+                ledger.records().len(),
+                ledger.head_hash()
+            )
+        });
+    }
+    Ok(PurgeSummary {
+        purged,
+        remaining: ledger.records().len(),
+        head_hash: ledger.head_hash().to_string(),
+    })
+}
+
+/// Applies the retention window now and then every `SWEEP_INTERVAL`.
+pub async fn run(state: Arc<AppState>, days: u64) {
+    let mut ticker = tokio::time::interval(SWEEP_INTERVAL);
+    loop {
+        ticker.tick().await;
+        if let Err(e) = purge(&state, days).await {
+            tracing::warn!("retention purge failed: {}", e);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::time::Duration;
+
+/// The longest wait between attempts, however many have failed.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// A longer `Retry-After` is cut to this, so one agent can't stall a round for minutes.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// `--max-attempts`/`--backoff-ms`: how provider calls retry transient failures
+/// (rate limits, 5xx responses, dropped connections and truncated bodies).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Requests per call, the first included.
+    pub max_attempts: u32,
+    /// The wait after the first failure; it doubles with each one after.
+    pub base: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_attempts: 3, base: Duration::from_millis(500) }
+    }
+}
+
+impl RetryPolicy {
+    /// The wait after failed attempt `attempt` (1-based), before the next one: the
+    /// exponential step scaled into its upper half by `jitter` in 0..1, so agents
+    /// that failed together don't retry together. A server's `Retry-After` wins.
+    pub fn delay(&self, attempt: u32, jitter: f64, retry_after: Option<Duration>) -> Duration {
+        if let Some(wait) = retry_after {
+            return wait.min(MAX_RETRY_AFTER);
+        }
+        let step = self.base.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1))).min(MAX_BACKOFF);
+        step.mul_f64(0.5 + jitter.clamp(0.0, 1.0) / 2.0)
+    }
+}
+
+/// A response's `Retry-After`, given either as seconds or as an HTTP date.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+    Some((at - Utc::now()).to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backs_off_exponentially_with_jitter_unless_told_how_long() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(1, 1.0, None), Duration::from_millis(500));
+        assert_eq!(policy.delay(1, 0.0, None), Duration::from_millis(250));
+        assert_eq!(policy.delay(3, 1.0, None), Duration::from_millis(2000));
+        assert_eq!(policy.delay(20, 1.0, None), MAX_BACKOFF);
+        assert_eq!(policy.delay(1, 0.5, Some(Duration::from_secs(7))), Duration::from_secs(7));
+        assert_eq!(policy.delay(1, 0.5, Some(Duration::from_secs(600))), MAX_RETRY_AFTER);
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, "12".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(12)));
+        headers.insert(RETRY_AFTER, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+    }
+}
@@ -0,0 +1,112 @@
+use async_trait::async_trait;
+use std::time::Duration;
+
+use crate::{AIProvider, AIResponse, ProviderError};
+
+/// `--model-sla` with `--fallback-model`: gives the slot's model `sla` to answer and,
+/// when it doesn't, cancels it and asks the backup instead. A vote from the backup
+/// names the primary in `fallback_from`. Failures of the primary within the SLA are
+/// its own, with no fallback: only slowness is covered.
+pub struct SlaProvider {
+    primary: Box<dyn AIProvider>,
+    backup: Box<dyn AIProvider>,
+    sla: Duration,
+}
+
+impl SlaProvider {
+    pub fn new(primary: Box<dyn AIProvider>, backup: Box<dyn AIProvider>, sla: Duration) -> Self {
+        Self { primary, backup, sla }
+    }
+}
+
+#[async_trait]
+impl AIProvider for SlaProvider {
+    fn agent_name(&self) -> &str {
+        self.primary.agent_name()
+    }
+
+    fn model(&self) -> &str {
+        self.primary.model()
+    }
+
+    async fn validate(&self, text: &str, debate: Option<&str>) -> Result<AIResponse, ProviderError> {
+        if let Ok(result) = tokio::time::timeout(self.sla, self.primary.validate(text, debate)).await {
+            return result;
+        }
+        tracing::warn!(
+            "{} missed its {} ms SLA; asking {}",
+            self.primary.model(),
+            self.sla.as_millis(),
+            self.backup.model()
+        );
+        match self.backup.validate(text, debate).await {
+            Ok(vote) => Ok(AIResponse {
+                fallback_from: Some(self.primary.model().to_string()),
+                ..vote
+            }),
+            Err(e) => Err(ProviderError::FallbackFailed {
+                model: self.backup.model().to_string(),
+                sla_ms: self.sla.as_millis() as u64,
+                source: Box::new(e),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Slow {
+        model: &'static str,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl AIProvider for Slow {
+        fn agent_name(&self) -> &str {
+            "agent-1"
+        }
+
+        fn model(&self) -> &str {
+            self.model
+        }
+
+        async fn validate(&self, _text: &str, _debate: Option<&str>) -> Result<AIResponse, ProviderError> {
+            tokio::time::sleep(self.delay).await;
+            if self.model == "down/model" {
+                return Err(ProviderError::Malformed("empty".to_string()));
+            }
+            Ok(AIResponse {
+                agent_name: "agent-1".to_string(),
+                model: self.model.to_string(),
+                is_valid: true,
+                confidence: None,
+                attempts: 1,
+                lang: None,
+                system_fingerprint: None,
+                fallback_from: None,
+                self_consistency: None,
+                rationale: None,
+            })
+        }
+    }
+
+    fn slot(primary_ms: u64, backup: &'static str) -> SlaProvider {
+        let primary = Slow { model: "openai/gpt-4o", delay: Duration::from_millis(primary_ms) };
+        let backup = Slow { model: backup, delay: Duration::ZERO };
+        SlaProvider::new(Box::new(primary), Box::new(backup), Duration::from_millis(50))
+    }
+
+    #[tokio::test]
+    async fn a_slow_primary_falls_back_to_the_backup() {
+        let vote = slot(0, "x/grok").validate("s", None).await.unwrap();
+        assert_eq!((vote.model.as_str(), vote.fallback_from), ("openai/gpt-4o", None));
+
+        let vote = slot(10_000, "x/grok").validate("s", None).await.unwrap();
+        assert_eq!((vote.model.as_str(), vote.fallback_from.as_deref()), ("x/grok", Some("openai/gpt-4o")));
+
+        let err = slot(10_000, "down/model").validate("s", None).await.unwrap_err();
+        assert_eq!(err.to_string(), "missed its 50 ms SLA; fallback down/model failed: unusable response: empty");
+    }
+}
@@ -0,0 +1,366 @@
+use base64::Engine;
+use clap::ValueEnum;
+use ed25519_dalek::{Signer, SigningKey};
+use reqwest::Client;
+use serde_json::json;
+use std::path::Path;
+use std::time::Duration;
+
+/// How many times a memo submission is sent when the RPC times out or drops.
+const SEND_ATTEMPTS: u32 = 3;
+
+/// SPL Memo program; a memo instruction writes arbitrary UTF-8 into the transaction log.
+const MEMO_PROGRAM_ID: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SolanaError {
+    #[error("Solana RPC unreachable: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Solana RPC error: {0}")]
+    Rpc(String),
+    #[error("Solana keypair: {0}")]
+    Keypair(String),
+    #[error("fee payer {0} has no SOL to pay for the anchor; fund it first (on devnet: `solana airdrop 1 {0}`)")]
+    InsufficientFunds(String),
+}
+
+/// `--commitment`: how settled the cluster state we read and build on must be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Commitment {
+    Processed,
+    Confirmed,
+    #[default]
+    Finalized,
+}
+
+impl Commitment {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Commitment::Processed => "processed",
+            Commitment::Confirmed => "confirmed",
+            Commitment::Finalized => "finalized",
+        }
+    }
+}
+
+/// Whether an RPC error says the fee payer can't cover the transaction: the
+/// simulation reports an account that was never funded, or too little in it.
+fn is_insufficient_funds(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    message.contains("no record of a prior credit") || message.contains("insufficient funds")
+}
+
+/// Fee payer and signer for memo transactions, read from a Solana CLI keypair file
+/// (a JSON array of 64 bytes: secret key then public key).
+pub struct Keypair {
+    signing: SigningKey,
+}
+
+impl Keypair {
+    pub fn read(path: &Path) -> Result<Self, SolanaError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| SolanaError::Keypair(format!("{}: {}", path.display(), e)))?;
+        let bytes: Vec<u8> = serde_json::from_str(&text)
+            .map_err(|e| SolanaError::Keypair(format!("{}: {}", path.display(), e)))?;
+        let secret: [u8; 32] = bytes
+            .get(..32)
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| SolanaError::Keypair(format!("{}: expected 64 bytes", path.display())))?;
+        Ok(Self {
+            signing: SigningKey::from_bytes(&secret),
+        })
+    }
+
+    pub fn pubkey(&self) -> [u8; 32] {
+        self.signing.verifying_key().to_bytes()
+    }
+
+    /// The public key as Solana addresses are written (base58).
+    pub fn address(&self) -> String {
+        bs58::encode(self.pubkey()).into_string()
+    }
+}
+
+/// Appends `len` in Solana's compact-u16 encoding (7 bits per byte, low bits first).
+fn push_compact_len(out: &mut Vec<u8>, mut len: usize) {
+    loop {
+        let byte = (len & 0x7f) as u8;
+        len >>= 7;
+        if len == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn decode_pubkey(base58: &str) -> Result<[u8; 32], SolanaError> {
+    bs58::decode(base58)
+        .into_vec()
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| SolanaError::Rpc(format!("not a 32-byte base58 value: {}", base58)))
+}
+
+/// Legacy transaction message with one memo instruction signed by `payer`.
+fn memo_message(payer: &[u8; 32], blockhash: &[u8; 32], memo: &[u8]) -> Vec<u8> {
+    let memo_program = decode_pubkey(MEMO_PROGRAM_ID).expect("static program id");
+    let mut message = vec![
+        1, // required signatures: the payer
+        0, // read-only signed accounts
+        1, // read-only unsigned accounts: the memo program
+    ];
+    push_compact_len(&mut message, 2);
+    message.extend_from_slice(payer);
+    message.extend_from_slice(&memo_program);
+    message.extend_from_slice(blockhash);
+    push_compact_len(&mut message, 1);
+    message.push(1); // program id index
+    push_compact_len(&mut message, 1);
+    message.push(0); // the payer signs the memo
+    push_compact_len(&mut message, memo.len());
+    message.extend_from_slice(memo);
+    message
+}
+
+/// The first successful transaction in a `getSignaturesForAddress` result whose memo
+/// contains `marker`.
+fn matching_signature(entries: &serde_json::Value, marker: &str) -> Option<String> {
+    entries.as_array()?.iter().find_map(|entry| {
+        let landed = entry["err"].is_null();
+        let matches = entry["memo"].as_str().is_some_and(|memo| memo.contains(marker));
+        (landed && matches).then(|| entry["signature"].as_str().map(str::to_string))?
+    })
+}
+
+/// Where a submitted transaction stands on the cluster.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Landed {
+    pub slot: Option<u64>,
+    pub confirmation: Option<String>,
+}
+
+/// Reads one entry of a `getSignatureStatuses` result.
+fn landed_from(signature: &str, status: &serde_json::Value) -> Result<Option<Landed>, SolanaError> {
+    if status.is_null() {
+        return Ok(None);
+    }
+    if !status["err"].is_null() {
+        return Err(SolanaError::Rpc(format!("transaction {} failed: {}", signature, status["err"])));
+    }
+    Ok(Some(Landed {
+        slot: status["slot"].as_u64(),
+        confirmation: status["confirmationStatus"].as_str().map(str::to_string),
+    }))
+}
+
+/// Minimal JSON-RPC client for the handful of Solana calls the ledger needs.
+pub struct SolanaClient {
+    client: Client,
+    url: String,
+    /// Needed only to write memos; reads work without it.
+    signer: Option<Keypair>,
+    commitment: Commitment,
+}
+
+impl SolanaClient {
+    pub fn new(url: &str, proxy: Option<&reqwest::Proxy>) -> Self {
+        Self {
+            // Keep this short: an unreachable cluster shouldn't stall a validation round.
+            client: crate::client_builder(proxy)
+                .timeout(Duration::from_secs(5))
+                .build()
+                .expect("static client configuration"),
+            url: url.to_string(),
+            signer: None,
+            commitment: Commitment::default(),
+        }
+    }
+
+    pub fn with_signer(mut self, signer: Keypair) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    pub fn with_commitment(mut self, commitment: Commitment) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    fn signer(&self) -> Result<&Keypair, SolanaError> {
+        self.signer
+            .as_ref()
+            .ok_or_else(|| SolanaError::Keypair("no keypair configured".to_string()))
+    }
+
+    async fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value, SolanaError> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: serde_json::Value = self.client.post(&self.url).json(&body).send().await?.json().await?;
+        if let Some(message) = response["error"]["message"].as_str() {
+            return Err(SolanaError::Rpc(message.to_string()));
+        }
+        Ok(response["result"].clone())
+    }
+
+    pub async fn get_slot(&self) -> Result<u64, SolanaError> {
+        self.call("getSlot", json!([{ "commitment": self.commitment.as_str() }]))
+            .await?
+            .as_u64()
+            .ok_or_else(|| SolanaError::Rpc("getSlot returned a non-integer".to_string()))
+    }
+
+    /// The signer's balance in lamports.
+    pub async fn balance(&self) -> Result<u64, SolanaError> {
+        let address = self.signer()?.address();
+        let result = self
+            .call("getBalance", json!([address, { "commitment": self.commitment.as_str() }]))
+            .await?;
+        result["value"]
+            .as_u64()
+            .ok_or_else(|| SolanaError::Rpc("getBalance returned no value".to_string()))
+    }
+
+    async fn latest_blockhash(&self) -> Result<[u8; 32], SolanaError> {
+        let result = self
+            .call("getLatestBlockhash", json!([{ "commitment": self.commitment.as_str() }]))
+            .await?;
+        let blockhash = result["value"]["blockhash"]
+            .as_str()
+            .ok_or_else(|| SolanaError::Rpc("getLatestBlockhash returned no blockhash".to_string()))?;
+        decode_pubkey(blockhash)
+    }
+
+    /// Signs and submits a memo transaction, returning its signature (base58).
+    pub async fn send_memo(&self, memo: &str) -> Result<String, SolanaError> {
+        let signer = self.signer()?;
+        let blockhash = self.latest_blockhash().await?;
+        let message = memo_message(&signer.pubkey(), &blockhash, memo.as_bytes());
+        let signature = signer.signing.sign(&message).to_bytes();
+
+        let mut transaction = Vec::with_capacity(1 + 64 + message.len());
+        push_compact_len(&mut transaction, 1);
+        transaction.extend_from_slice(&signature);
+        transaction.extend_from_slice(&message);
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&transaction);
+
+        let params = json!([encoded, { "encoding": "base64", "preflightCommitment": self.commitment.as_str() }]);
+        let result = match self.call("sendTransaction", params).await {
+            Err(SolanaError::Rpc(message)) if is_insufficient_funds(&message) => {
+                return Err(SolanaError::InsufficientFunds(signer.address()))
+            }
+            result => result?,
+        };
+        result
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| SolanaError::Rpc("sendTransaction returned no signature".to_string()))
+    }
+
+    /// The slot a transaction landed in and its commitment level ("processed",
+    /// "confirmed" or "finalized"), or `None` if the cluster doesn't know it (yet).
+    pub async fn landed(&self, signature: &str) -> Result<Option<Landed>, SolanaError> {
+        let result = self
+            .call(
+                "getSignatureStatuses",
+                json!([[signature], { "searchTransactionHistory": true }]),
+            )
+            .await?;
+        landed_from(signature, &result["value"][0])
+    }
+
+    /// Signature of a recent transaction from our signer whose memo contains `marker`.
+    pub async fn find_memo(&self, marker: &str) -> Result<Option<String>, SolanaError> {
+        let address = self.signer()?.address();
+        let entries = self
+            .call("getSignaturesForAddress", json!([address, { "limit": 100 }]))
+            .await?;
+        Ok(matching_signature(&entries, marker))
+    }
+
+    /// Like `send_memo`, but safe to retry: `marker` (a hash the memo contains) is
+    /// looked up on chain before every attempt, and a transaction that already
+    /// landed is returned instead of being written twice.
+    pub async fn send_memo_once(&self, memo: &str, marker: &str) -> Result<String, SolanaError> {
+        let mut attempt = 1;
+        loop {
+            if let Some(signature) = self.find_memo(marker).await? {
+                tracing::info!("memo for {} already landed in {}", marker, signature);
+                return Ok(signature);
+            }
+            match self.send_memo(memo).await {
+                Err(SolanaError::Http(e)) if attempt < SEND_ATTEMPTS => {
+                    tracing::warn!("memo submission failed ({}), checking before retrying", e);
+                    attempt += 1;
+                    // Give a transaction that did go through time to show up.
+                    tokio::time::sleep(Duration::from_secs(2)).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact_len_uses_seven_bit_groups() {
+        for (len, expected) in [
+            (0, vec![0x00]),
+            (0x7f, vec![0x7f]),
+            (0x80, vec![0x80, 0x01]),
+            (0x3fff, vec![0xff, 0x7f]),
+            (0x4000, vec![0x80, 0x80, 0x01]),
+        ] {
+            let mut out = Vec::new();
+            push_compact_len(&mut out, len);
+            assert_eq!(out, expected, "{}", len);
+        }
+    }
+
+    #[test]
+    fn landed_reads_slot_and_confirmation() {
+        let status = json!({ "slot": 42, "confirmationStatus": "confirmed", "err": null });
+        assert_eq!(
+            landed_from("s", &status).unwrap(),
+            Some(Landed { slot: Some(42), confirmation: Some("confirmed".to_string()) })
+        );
+        assert_eq!(landed_from("s", &serde_json::Value::Null).unwrap(), None);
+        assert!(landed_from("s", &json!({ "slot": 1, "err": { "InstructionError": [0, "x"] } })).is_err());
+    }
+
+    #[test]
+    fn matching_signature_skips_failed_and_unrelated_memos() {
+        let entries = json!([
+            { "signature": "a", "err": null, "memo": "[42] something else" },
+            { "signature": "b", "err": { "InstructionError": [0, "Custom"] }, "memo": "[60] root123" },
+            { "signature": "c", "err": null, "memo": "[60] truth-terminal anchor root123 (2 records)" },
+        ]);
+        assert_eq!(matching_signature(&entries, "root123").as_deref(), Some("c"));
+        assert_eq!(matching_signature(&entries, "missing"), None);
+    }
+
+    #[test]
+    fn unfunded_payers_are_recognized() {
+        assert!(is_insufficient_funds(
+            "Transaction simulation failed: Attempt to debit an account but found no record of a prior credit."
+        ));
+        assert!(is_insufficient_funds("Transaction results in an account (0) with insufficient funds for rent"));
+        assert!(!is_insufficient_funds("Blockhash not found"));
+        let err = SolanaError::InsufficientFunds("Payer111".to_string());
+        assert!(err.to_string().starts_with("fee payer Payer111 has no SOL"));
+    }
+
+    #[test]
+    fn memo_message_lists_payer_then_memo_program() {
+        let payer = [7u8; 32];
+        let blockhash = [9u8; 32];
+        let message = memo_message(&payer, &blockhash, b"hi");
+        assert_eq!(&message[..4], &[1, 0, 1, 2]);
+        assert_eq!(&message[4..36], &payer);
+        assert_eq!(&message[36..68], &decode_pubkey(MEMO_PROGRAM_ID).unwrap());
+        assert_eq!(&message[68..100], &blockhash);
+        assert_eq!(&message[100..], &[1, 1, 1, 0, 2, b'h', b'i']);
+    }
+}
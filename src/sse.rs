@@ -0,0 +1,86 @@
+/// Reassembles a chat-completions `stream: true` response from its SSE chunks.
+///
+/// Chunks may split lines anywhere, so bytes are buffered until a full line arrives.
+/// Each `data:` event carries a JSON delta whose `choices[0].delta.content` is
+/// appended to the message; `data: [DONE]` ends the stream.
+#[derive(Debug, Default)]
+pub struct SseAssembler {
+    buffer: Vec<u8>,
+    content: String,
+    fingerprint: Option<String>,
+    done: bool,
+}
+
+impl SseAssembler {
+    /// Feeds raw bytes and returns the content deltas completed by them.
+    pub fn push(&mut self, chunk: &[u8]) -> Result<Vec<String>, String> {
+        self.buffer.extend_from_slice(chunk);
+        let mut deltas = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let Some(data) = line.trim().strip_prefix("data:") else {
+                continue;
+            };
+            let data = data.trim();
+            if data == "[DONE]" {
+                self.done = true;
+                continue;
+            }
+            let event: serde_json::Value =
+                serde_json::from_str(data).map_err(|e| format!("bad stream event: {}", e))?;
+            if let Some(error) = event.get("error") {
+                return Err(error["message"].as_str().map_or_else(|| error.to_string(), str::to_string));
+            }
+            if let Some(fingerprint) = event["system_fingerprint"].as_str() {
+                self.fingerprint.get_or_insert_with(|| fingerprint.to_string());
+            }
+            if let Some(delta) = event["choices"][0]["delta"]["content"].as_str() {
+                self.content.push_str(delta);
+                deltas.push(delta.to_string());
+            }
+        }
+        Ok(deltas)
+    }
+
+    /// Whether the `[DONE]` sentinel has been seen.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// The first `system_fingerprint` the stream carried.
+    pub fn fingerprint(&self) -> Option<&str> {
+        self.fingerprint.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SseAssembler;
+
+    #[test]
+    fn reassembles_deltas_split_across_chunks() {
+        let mut sse = SseAssembler::default();
+        let deltas = sse
+            .push(b"data: {\"system_fingerprint\":\"fp_1\",\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\ndata: {\"choices\":[{\"delta\":{\"content\":\"Ye\"}}]}\n\nda")
+            .unwrap();
+        assert_eq!(deltas, ["Ye"]);
+        sse.push(b"ta: {\"choices\":[{\"delta\":{\"content\":\"s 0.9\"}}]}\n\n").unwrap();
+        assert!(!sse.is_done());
+        sse.push(b": keep-alive\n\ndata: [DONE]\n\n").unwrap();
+        assert!(sse.is_done());
+        assert_eq!(sse.content(), "Yes 0.9");
+        assert_eq!(sse.fingerprint(), Some("fp_1"));
+    }
+
+    #[test]
+    fn reports_in_stream_errors() {
+        let mut sse = SseAssembler::default();
+        let err = sse.push(b"data: {\"error\":{\"message\":\"overloaded\"}}\n").unwrap_err();
+        assert_eq!(err, "overloaded");
+    }
+}
@@ -0,0 +1,566 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use std::fs;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::anchor::Anchor;
+use crate::Record;
+
+#[derive(Debug, thiserror::Error)]
+pub enum StoreError {
+    #[error("ledger file error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("ledger file is not valid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A JSON-lines file with an unreadable line, e.g. one cut off by a crash mid-append.
+    #[error("{path}: line {line} is not a valid entry: {source}")]
+    Line {
+        path: PathBuf,
+        line: usize,
+        source: serde_json::Error,
+    },
+    #[error("sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+}
+
+/// How `/api/search` matches its query against statements.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    /// Every word must appear (as a prefix, in any order).
+    #[default]
+    All,
+    /// Any of the words may appear.
+    Any,
+    /// The words must appear together, in order.
+    Phrase,
+}
+
+/// Linear-scan fallback for stores without an index: case-insensitive matching of
+/// whole words as substrings, in ledger order.
+pub fn scan_matches(content: &str, query: &str, mode: SearchMode) -> bool {
+    let content = content.to_lowercase();
+    let query = query.to_lowercase();
+    let mut words = query.split_whitespace();
+    match mode {
+        SearchMode::All => words.all(|w| content.contains(w)),
+        SearchMode::Any => words.any(|w| content.contains(w)),
+        SearchMode::Phrase => content.contains(query.trim()),
+    }
+}
+
+/// FTS5 MATCH expression for a user query; every word is quoted so operators
+/// and punctuation in it are taken literally.
+fn fts_query(query: &str, mode: SearchMode) -> String {
+    let quote = |s: &str| format!("\"{}\"", s.replace('"', "\"\""));
+    match mode {
+        SearchMode::Phrase => quote(query.trim()),
+        SearchMode::All | SearchMode::Any => {
+            let joiner = if mode == SearchMode::All { " AND " } else { " OR " };
+            query
+                .split_whitespace()
+                .map(|w| format!("{}*", quote(w)))
+                .collect::<Vec<_>>()
+                .join(joiner)
+        }
+    }
+}
+
+/// Where committed records are kept between restarts.
+///
+/// The in-memory `Ledger` owns chaining, verification and all reads; a store only
+/// has to remember records in commit order and hand them back at startup. Methods
+/// block on I/O, so async callers run them through `spawn_blocking`.
+pub trait LedgerStore: Send + Sync {
+    fn append(&self, record: &Record) -> Result<(), StoreError>;
+    fn load(&self) -> Result<Vec<Record>, StoreError>;
+    /// Replaces the stored record with the same id (used for post-commit annotations).
+    fn update(&self, record: &Record) -> Result<(), StoreError>;
+    /// Confirms the backing storage accepts writes, without changing any records.
+    fn check_writable(&self) -> Result<(), StoreError>;
+    fn append_anchor(&self, anchor: &Anchor) -> Result<(), StoreError>;
+    /// Replaces the stored anchor with the same signature.
+    fn update_anchor(&self, anchor: &Anchor) -> Result<(), StoreError>;
+    fn load_anchors(&self) -> Result<Vec<Anchor>, StoreError>;
+
+    /// Replaces every stored record with `records`, in one atomic write.
+    fn replace_all(&self, records: &[Record]) -> Result<(), StoreError>;
+
+    /// Ids of records whose statement matches, best match first, or `None` if this
+    /// store keeps no search index and the caller should scan.
+    fn search(&self, _query: &str, _mode: SearchMode, _limit: usize) -> Result<Option<Vec<String>>, StoreError> {
+        Ok(None)
+    }
+}
+
+/// Reads records from a ledger file written by `JsonFileStore` (one record per line),
+/// a bare JSON array, or `/api/export` (a snapshot object with a `records` field).
+pub fn read_ledger_file(path: &Path) -> Result<Vec<Record>, StoreError> {
+    let bytes = decode(path, fs::read(path)?)?;
+    let mut value: serde_json::Value = match serde_json::from_slice(&bytes) {
+        Ok(value) => value,
+        Err(_) => return parse_lines(path, &bytes),
+    };
+    if let Some(records) = value.get_mut("records") {
+        value = records.take();
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+fn parse_lines<T: serde::de::DeserializeOwned>(path: &Path, bytes: &[u8]) -> Result<Vec<T>, StoreError> {
+    let mut items = Vec::new();
+    for (i, line) in String::from_utf8_lossy(bytes).lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let item = serde_json::from_str(line).map_err(|source| StoreError::Line {
+            path: path.to_path_buf(),
+            line: i + 1,
+            source,
+        })?;
+        items.push(item);
+    }
+    Ok(items)
+}
+
+/// Files ending in `.gz` are stored gzip-compressed.
+fn is_gzip(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "gz")
+}
+
+fn encode(path: &Path, bytes: Vec<u8>) -> Result<Vec<u8>, StoreError> {
+    if !is_gzip(path) {
+        return Ok(bytes);
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&bytes)?;
+    Ok(encoder.finish()?)
+}
+
+/// Undoes `encode`. Compressed files may hold several gzip members, one per append.
+fn decode(path: &Path, bytes: Vec<u8>) -> Result<Vec<u8>, StoreError> {
+    if !is_gzip(path) {
+        return Ok(bytes);
+    }
+    let mut out = Vec::new();
+    MultiGzDecoder::new(&bytes[..]).read_to_end(&mut out)?;
+    Ok(out)
+}
+
+/// Appends one JSON line to `path`, creating the file if needed. On a `.gz` path
+/// the line is written as its own gzip member, so nothing already on disk is rewritten.
+fn append_line(path: &Path, item: &impl serde::Serialize) -> Result<(), StoreError> {
+    let mut line = serde_json::to_vec(item)?;
+    line.push(b'\n');
+    let line = encode(path, line)?;
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(&line)?;
+    file.sync_data()?;
+    Ok(())
+}
+
+/// Replaces `path` with `items` as JSON Lines, atomically via a temporary file.
+fn rewrite_lines(path: &Path, items: &[impl serde::Serialize]) -> Result<(), StoreError> {
+    let mut out = Vec::new();
+    for item in items {
+        out.extend(serde_json::to_vec(item)?);
+        out.push(b'\n');
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, encode(path, out)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Reads a JSON Lines file, treating a missing file as empty.
+fn read_lines<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Vec<T>, StoreError> {
+    match fs::read(path) {
+        Ok(bytes) => parse_lines(path, &decode(path, bytes)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// ======== Memory Store ========
+
+/// Persists nothing: the in-memory `Ledger` is the only copy, lost on exit.
+#[derive(Default)]
+pub struct MemoryStore;
+
+impl LedgerStore for MemoryStore {
+    fn append(&self, _record: &Record) -> Result<(), StoreError> {
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Vec<Record>, StoreError> {
+        Ok(Vec::new())
+    }
+
+    fn update(&self, _record: &Record) -> Result<(), StoreError> {
+        Ok(())
+    }
+
+    fn check_writable(&self) -> Result<(), StoreError> {
+        Ok(())
+    }
+
+    fn append_anchor(&self, _anchor: &Anchor) -> Result<(), StoreError> {
+        Ok(())
+    }
+
+    fn update_anchor(&self, _anchor: &Anchor) -> Result<(), StoreError> {
+        Ok(())
+    }
+
+    fn load_anchors(&self) -> Result<Vec<Anchor>, StoreError> {
+        Ok(Vec::new())
+    }
+
+    fn replace_all(&self, _records: &[Record]) -> Result<(), StoreError> {
+        Ok(())
+    }
+}
+
+// ======== JSON File Store ========
+
+/// One JSON record per line. Appends add a line; only `update` rewrites the file,
+/// atomically via a temporary file. A `.gz` path is kept gzip-compressed. Anchors
+/// go to a sibling, uncompressed `.anchors.jsonl` file.
+pub struct JsonFileStore {
+    path: PathBuf,
+    // Keeps appends from interleaving with an update's rewrite.
+    lock: Mutex<()>,
+}
+
+impl JsonFileStore {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn read(&self) -> Result<Vec<Record>, StoreError> {
+        read_lines(&self.path)
+    }
+
+    fn anchors_path(&self) -> PathBuf {
+        let base = if is_gzip(&self.path) { self.path.with_extension("") } else { self.path.clone() };
+        base.with_extension("anchors.jsonl")
+    }
+}
+
+impl LedgerStore for JsonFileStore {
+    fn append(&self, record: &Record) -> Result<(), StoreError> {
+        let _guard = self.lock.lock().unwrap();
+        append_line(&self.path, record)
+    }
+
+    fn load(&self) -> Result<Vec<Record>, StoreError> {
+        let _guard = self.lock.lock().unwrap();
+        self.read()
+    }
+
+    fn update(&self, record: &Record) -> Result<(), StoreError> {
+        let _guard = self.lock.lock().unwrap();
+        let records: Vec<Record> = self
+            .read()?
+            .into_iter()
+            .map(|stored| if stored.id == record.id { record.clone() } else { stored })
+            .collect();
+        rewrite_lines(&self.path, &records)
+    }
+
+    fn check_writable(&self) -> Result<(), StoreError> {
+        let _guard = self.lock.lock().unwrap();
+        fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        Ok(())
+    }
+
+    fn append_anchor(&self, anchor: &Anchor) -> Result<(), StoreError> {
+        let _guard = self.lock.lock().unwrap();
+        append_line(&self.anchors_path(), anchor)
+    }
+
+    fn update_anchor(&self, anchor: &Anchor) -> Result<(), StoreError> {
+        let _guard = self.lock.lock().unwrap();
+        let anchors: Vec<Anchor> = read_lines(&self.anchors_path())?
+            .into_iter()
+            .map(|stored: Anchor| if stored.signature == anchor.signature { anchor.clone() } else { stored })
+            .collect();
+        rewrite_lines(&self.anchors_path(), &anchors)
+    }
+
+    fn load_anchors(&self) -> Result<Vec<Anchor>, StoreError> {
+        let _guard = self.lock.lock().unwrap();
+        read_lines(&self.anchors_path())
+    }
+
+    fn replace_all(&self, records: &[Record]) -> Result<(), StoreError> {
+        let _guard = self.lock.lock().unwrap();
+        rewrite_lines(&self.path, records)
+    }
+}
+
+// ======== SQLite Store ========
+
+/// Fixed-width UTC, so lexical order in the timestamp index matches time order.
+fn sql_timestamp(time: &DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S%.9fZ").to_string()
+}
+
+/// One row per record with the indexed fields broken out and the full record as JSON.
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    pub fn open(path: &Path) -> Result<Self, StoreError> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS records (
+                 seq       INTEGER PRIMARY KEY AUTOINCREMENT,
+                 id        TEXT NOT NULL UNIQUE,
+                 content   TEXT NOT NULL,
+                 consensus INTEGER NOT NULL,
+                 timestamp TEXT NOT NULL,
+                 body      TEXT NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS records_timestamp ON records (timestamp);
+             CREATE TABLE IF NOT EXISTS anchors (
+                 seq       INTEGER PRIMARY KEY AUTOINCREMENT,
+                 signature TEXT NOT NULL UNIQUE,
+                 body      TEXT NOT NULL
+             );
+             CREATE VIRTUAL TABLE IF NOT EXISTS records_fts USING fts5 (
+                 id UNINDEXED,
+                 content,
+                 tokenize = 'porter unicode61'
+             );
+             INSERT INTO records_fts (id, content)
+                 SELECT id, content FROM records
+                 WHERE id NOT IN (SELECT id FROM records_fts);",
+        )?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn select(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<Record>, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(sql)?;
+        let bodies = stmt.query_map(params, |row| row.get::<_, String>(0))?;
+        let mut records = Vec::new();
+        for body in bodies {
+            records.push(serde_json::from_str(&body?)?);
+        }
+        Ok(records)
+    }
+}
+
+/// Inserts a record row and its search index entry.
+fn insert_record(tx: &rusqlite::Transaction, record: &Record) -> Result<(), StoreError> {
+    tx.execute(
+        "INSERT INTO records (id, content, consensus, timestamp, body) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            record.id,
+            record.transaction.content,
+            record.consensus,
+            sql_timestamp(&record.timestamp),
+            serde_json::to_string(record)?,
+        ],
+    )?;
+    tx.execute(
+        "INSERT INTO records_fts (id, content) VALUES (?1, ?2)",
+        params![record.id, record.transaction.content],
+    )?;
+    Ok(())
+}
+
+impl LedgerStore for SqliteStore {
+    fn append(&self, record: &Record) -> Result<(), StoreError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        insert_record(&tx, record)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Vec<Record>, StoreError> {
+        self.select("SELECT body FROM records ORDER BY seq", [])
+    }
+
+    fn update(&self, record: &Record) -> Result<(), StoreError> {
+        self.conn.lock().unwrap().execute(
+            "UPDATE records SET body = ?1 WHERE id = ?2",
+            params![serde_json::to_string(record)?, record.id],
+        )?;
+        Ok(())
+    }
+
+    fn check_writable(&self) -> Result<(), StoreError> {
+        // Takes the write lock, so a read-only file or a locked database fails here.
+        self.conn.lock().unwrap().execute_batch("BEGIN IMMEDIATE; ROLLBACK;")?;
+        Ok(())
+    }
+
+    fn append_anchor(&self, anchor: &Anchor) -> Result<(), StoreError> {
+        self.conn.lock().unwrap().execute(
+            "INSERT INTO anchors (signature, body) VALUES (?1, ?2)",
+            params![anchor.signature, serde_json::to_string(anchor)?],
+        )?;
+        Ok(())
+    }
+
+    fn update_anchor(&self, anchor: &Anchor) -> Result<(), StoreError> {
+        self.conn.lock().unwrap().execute(
+            "UPDATE anchors SET body = ?1 WHERE signature = ?2",
+            params![serde_json::to_string(anchor)?, anchor.signature],
+        )?;
+        Ok(())
+    }
+
+    fn load_anchors(&self) -> Result<Vec<Anchor>, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT body FROM anchors ORDER BY seq")?;
+        let bodies = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut anchors = Vec::new();
+        for body in bodies {
+            anchors.push(serde_json::from_str(&body?)?);
+        }
+        Ok(anchors)
+    }
+
+    fn replace_all(&self, records: &[Record]) -> Result<(), StoreError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute_batch("DELETE FROM records; DELETE FROM records_fts;")?;
+        for record in records {
+            insert_record(&tx, record)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn search(&self, query: &str, mode: SearchMode, limit: usize) -> Result<Option<Vec<String>>, StoreError> {
+        let expression = fts_query(query, mode);
+        if expression.is_empty() {
+            return Ok(Some(Vec::new()));
+        }
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id FROM records_fts WHERE records_fts MATCH ?1 ORDER BY bm25(records_fts) LIMIT ?2",
+        )?;
+        let ids = stmt.query_map(params![expression, limit as i64], |row| row.get::<_, String>(0))?;
+        Ok(Some(ids.collect::<Result<_, _>>()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::tests::record;
+
+    #[test]
+    fn json_file_store_appends_lines_and_updates_in_place() {
+        let path = std::env::temp_dir().join(format!("ledger-{}.jsonl", uuid::Uuid::new_v4()));
+        let store = JsonFileStore::new(&path);
+        assert!(store.load().unwrap().is_empty());
+
+        store.append(&record("a")).unwrap();
+        store.append(&record("b")).unwrap();
+        let mut labeled = record("a");
+        labeled.label = Some(true);
+        store.update(&labeled).unwrap();
+
+        let loaded = store.load().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+        assert_eq!(loaded[0].label, Some(true));
+        assert_eq!(loaded[1].transaction.content, "b");
+        assert_eq!(read_ledger_file(&path).unwrap().len(), 2);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn a_cut_off_line_names_the_file_and_line() {
+        let path = std::env::temp_dir().join(format!("ledger-{}.jsonl", uuid::Uuid::new_v4()));
+        let store = JsonFileStore::new(&path);
+        store.append(&record("a")).unwrap();
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"\n{\"id\":\"b\",\"trans").unwrap();
+        let err = store.load().unwrap_err().to_string();
+        assert!(err.starts_with(&format!("{}: line 3 is not a valid entry", path.display())), "{}", err);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn gz_path_is_compressed_across_appends_and_updates() {
+        let path = std::env::temp_dir().join(format!("ledger-{}.jsonl.gz", uuid::Uuid::new_v4()));
+        let store = JsonFileStore::new(&path);
+        store.append(&record("a")).unwrap();
+        store.append(&record("b")).unwrap();
+        assert_eq!(&fs::read(&path).unwrap()[..2], [0x1f, 0x8b]);
+        assert_eq!(store.load().unwrap().len(), 2);
+
+        let mut labeled = record("b");
+        labeled.label = Some(false);
+        store.update(&labeled).unwrap();
+        store.append(&record("c")).unwrap();
+        let loaded = read_ledger_file(&path).unwrap();
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(loaded[1].label, Some(false));
+        assert_eq!(store.anchors_path(), path.with_extension("").with_extension("anchors.jsonl"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn sqlite_store_round_trips_in_commit_order() {
+        let store = SqliteStore::open(Path::new(":memory:")).unwrap();
+        store.append(&record("b")).unwrap();
+        store.append(&record("a")).unwrap();
+        assert!(store.append(&record("a")).is_err());
+        let ids: Vec<String> = store.load().unwrap().into_iter().map(|r| r.id).collect();
+        assert_eq!(ids, ["id-b", "id-a"]);
+    }
+
+    #[test]
+    fn sqlite_search_ranks_and_supports_modes() {
+        let store = SqliteStore::open(Path::new(":memory:")).unwrap();
+        store.append(&record("the moon is made of cheese")).unwrap();
+        store.append(&record("cheese is made from milk")).unwrap();
+        store.append(&record("water boils at 100 C")).unwrap();
+
+        let search = |q, mode| store.search(q, mode, 10).unwrap().unwrap();
+        assert_eq!(search("chee", SearchMode::All).len(), 2);
+        assert_eq!(search("moon chee", SearchMode::All), ["id-the moon is made of cheese"]);
+        assert_eq!(search("moon water", SearchMode::Any).len(), 2);
+        assert_eq!(search("made of cheese", SearchMode::Phrase), ["id-the moon is made of cheese"]);
+        // Quotes and FTS operators in the query are literal.
+        assert!(search("\"NEAR( OR", SearchMode::All).is_empty());
+    }
+
+    #[test]
+    fn sqlite_replace_all_rewrites_records_and_index() {
+        let store = SqliteStore::open(Path::new(":memory:")).unwrap();
+        store.append(&record("old statement")).unwrap();
+        store.replace_all(&[record("new statement")]).unwrap();
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].transaction.content, "new statement");
+        assert!(store.search("old", SearchMode::All, 10).unwrap().unwrap().is_empty());
+    }
+
+    #[test]
+    fn scan_matches_mirrors_the_search_modes() {
+        assert!(scan_matches("The Moon is cheese", "moon CHEESE", SearchMode::All));
+        assert!(!scan_matches("The Moon is cheese", "moon milk", SearchMode::All));
+        assert!(scan_matches("The Moon is cheese", "moon milk", SearchMode::Any));
+        assert!(!scan_matches("The Moon is cheese", "cheese moon", SearchMode::Phrase));
+    }
+}
@@ -0,0 +1,131 @@
+use serde_json::Value;
+
+/// Where a response departs from the chat-completions schema, for `--strict-json`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Deviation {
+    /// JSON path of the offending field, e.g. `$.choices[0].message.content`.
+    pub path: String,
+    pub problem: String,
+}
+
+/// Checks a 2xx chat-completions body against the fields this tool relies on and
+/// the types the API documents for the rest. Extra fields are allowed, since
+/// providers add their own; missing, null or mistyped ones are not.
+pub fn check_completion(body: &Value) -> Result<(), Deviation> {
+    let root = object(body, "$")?;
+    required(root, "$", "id", is_string)?;
+    required(root, "$", "model", is_string)?;
+    optional(root, "$", "object", is_string)?;
+    optional(root, "$", "created", Value::is_u64)?;
+    optional(root, "$", "system_fingerprint", |v| v.is_null() || v.is_string())?;
+    let choices = match root.get("choices") {
+        None => return Err(deviation("$.choices", "missing")),
+        Some(Value::Array(choices)) if choices.is_empty() => return Err(deviation("$.choices", "empty array")),
+        Some(Value::Array(choices)) => choices,
+        Some(other) => return Err(mistyped("$.choices", "array", other)),
+    };
+    for (i, choice) in choices.iter().enumerate() {
+        let path = format!("$.choices[{}]", i);
+        let choice = object(choice, &path)?;
+        optional(choice, &path, "index", Value::is_u64)?;
+        optional(choice, &path, "finish_reason", |v| v.is_null() || v.is_string())?;
+        let message_path = format!("{}.message", path);
+        let message = object(choice.get("message").unwrap_or(&Value::Null), &message_path)?;
+        required(message, &message_path, "role", is_string)?;
+        required(message, &message_path, "content", is_string)?;
+    }
+    if let Some(usage) = root.get("usage") {
+        let usage = object(usage, "$.usage")?;
+        for field in ["prompt_tokens", "completion_tokens", "total_tokens"] {
+            optional(usage, "$.usage", field, Value::is_u64)?;
+        }
+    }
+    Ok(())
+}
+
+type Object = serde_json::Map<String, Value>;
+
+fn is_string(value: &Value) -> bool {
+    value.is_string()
+}
+
+fn object<'a>(value: &'a Value, path: &str) -> Result<&'a Object, Deviation> {
+    match value {
+        Value::Object(object) => Ok(object),
+        Value::Null => Err(deviation(path, "missing")),
+        other => Err(mistyped(path, "object", other)),
+    }
+}
+
+fn required(object: &Object, path: &str, field: &str, valid: fn(&Value) -> bool) -> Result<(), Deviation> {
+    let path = format!("{}.{}", path, field);
+    match object.get(field) {
+        None => Err(deviation(&path, "missing")),
+        Some(value) if valid(value) => Ok(()),
+        Some(value) => Err(mistyped(&path, expected(field), value)),
+    }
+}
+
+fn optional(object: &Object, path: &str, field: &str, valid: fn(&Value) -> bool) -> Result<(), Deviation> {
+    match object.get(field) {
+        Some(value) if !valid(value) => Err(mistyped(&format!("{}.{}", path, field), expected(field), value)),
+        _ => Ok(()),
+    }
+}
+
+/// The documented type of a checked field, for error messages.
+fn expected(field: &str) -> &'static str {
+    match field {
+        "created" | "index" | "prompt_tokens" | "completion_tokens" | "total_tokens" => "non-negative integer",
+        "finish_reason" | "system_fingerprint" => "string or null",
+        _ => "string",
+    }
+}
+
+fn deviation(path: &str, problem: &str) -> Deviation {
+    Deviation {
+        path: path.to_string(),
+        problem: problem.to_string(),
+    }
+}
+
+fn mistyped(path: &str, expected: &str, found: &Value) -> Deviation {
+    let mut shown = found.to_string();
+    if shown.len() > 80 {
+        let cut = (0..=77).rev().find(|&i| shown.is_char_boundary(i)).unwrap_or(0);
+        shown.truncate(cut);
+        shown.push_str("...");
+    }
+    deviation(path, &format!("expected {}, found {}", expected, shown))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reports_the_path_of_the_first_deviation() {
+        let mut body = json!({
+            "id": "gen-1",
+            "model": "openai/gpt-4o",
+            "choices": [{ "index": 0, "finish_reason": null, "message": { "role": "assistant", "content": "yes 0.9" } }],
+            "usage": { "prompt_tokens": 12, "completion_tokens": 3, "total_tokens": 15 },
+            "provider": "OpenAI"
+        });
+        assert_eq!(check_completion(&body), Ok(()));
+
+        body["choices"][0]["message"]["content"] = Value::Null;
+        let found = check_completion(&body).unwrap_err();
+        assert_eq!(found.path, "$.choices[0].message.content");
+        assert_eq!(found.problem, "expected string, found null");
+
+        body["choices"] = json!([]);
+        assert_eq!(check_completion(&body).unwrap_err(), deviation("$.choices", "empty array"));
+        body["choices"] = json!([{ "message": { "role": "assistant", "content": "no" } }]);
+        body["usage"]["total_tokens"] = json!("15");
+        let found = check_completion(&body).unwrap_err();
+        assert_eq!((found.path.as_str(), found.problem.as_str()), ("$.usage.total_tokens", "expected non-negative integer, found \"15\""));
+        assert_eq!(check_completion(&json!([])).unwrap_err().path, "$");
+    }
+}
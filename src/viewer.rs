@@ -0,0 +1,247 @@
+use std::io::Write;
+use std::path::Path;
+use tokio::io::BufReader;
+
+use crate::config::{Config, OutputFormat};
+use crate::input::{Line, LineReader};
+use crate::ledger::{self, Ledger};
+use crate::render::{self, RenderOptions};
+use crate::{analytics, reputation, store};
+
+const HELP: &str = "commands: /show [n], /why <n>, /chart <n>, /robustness <n>, /compare <a> <b>, /search <words>, /stats, /accuracy, /verify, /head, /prove <n>, \
+                    /check-proof <proof JSON>, /schema, /quit";
+
+/// A viewer command. Anything else is refused: the viewer never records or edits.
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    /// Every record, or the one at a 1-based index.
+    Show(Option<usize>),
+    /// How the record at a 1-based index reached its verdict.
+    Why(usize),
+    /// An ASCII bar chart of the record's votes, by 1-based index.
+    Chart(usize),
+    /// Bootstrap stability of the record's verdict, by 1-based index.
+    Robustness(usize),
+    /// Two records side by side, by 1-based index.
+    Compare(usize, usize),
+    Search(String),
+    Stats,
+    /// Vote accuracy against the labels, per model and overall.
+    Accuracy,
+    Verify,
+    Head,
+    /// The Merkle proof for a record, by 1-based index.
+    Prove(usize),
+    /// A proof as printed by `/prove`, checked against its own root and leaf.
+    CheckProof(ledger::MerkleProof),
+    Schema,
+    Help,
+    Quit,
+}
+
+fn parse_command(input: &str) -> Result<Command, String> {
+    let input = input.trim();
+    let (name, arg) = input.split_once(' ').map_or((input, ""), |(n, a)| (n, a.trim()));
+    match name {
+        "/show" if arg.is_empty() => Ok(Command::Show(None)),
+        "/show" => arg
+            .parse()
+            .map(|n| Command::Show(Some(n)))
+            .map_err(|_| format!("'{}' is not a record number", arg)),
+        "/why" => arg
+            .parse()
+            .map(Command::Why)
+            .map_err(|_| "usage: /why <n>".to_string()),
+        "/chart" => arg
+            .parse()
+            .map(Command::Chart)
+            .map_err(|_| "usage: /chart <n>".to_string()),
+        "/robustness" => arg
+            .parse()
+            .map(Command::Robustness)
+            .map_err(|_| "usage: /robustness <n>".to_string()),
+        "/compare" => match arg.split_whitespace().map(str::parse).collect::<Result<Vec<usize>, _>>() {
+            Ok(indexes) if indexes.len() == 2 => Ok(Command::Compare(indexes[0], indexes[1])),
+            _ => Err("usage: /compare <a> <b>".to_string()),
+        },
+        "/search" if arg.is_empty() => Err("usage: /search <words>".to_string()),
+        "/search" => Ok(Command::Search(arg.to_string())),
+        "/stats" => Ok(Command::Stats),
+        "/accuracy" => Ok(Command::Accuracy),
+        "/verify" => Ok(Command::Verify),
+        "/head" => Ok(Command::Head),
+        "/prove" => arg
+            .parse()
+            .map(Command::Prove)
+            .map_err(|_| "usage: /prove <n>".to_string()),
+        "/check-proof" => serde_json::from_str(arg)
+            .map(Command::CheckProof)
+            .map_err(|e| format!("usage: /check-proof <proof JSON from /prove> ({})", e)),
+        "/schema" => Ok(Command::Schema),
+        "/help" | "" => Ok(Command::Help),
+        "/quit" | "/exit" => Ok(Command::Quit),
+        _ if name.starts_with('/') => Err(format!("'{}' is not available in the read-only viewer", name)),
+        _ => Err("the read-only viewer doesn't validate statements".to_string()),
+    }
+}
+
+/// `--readonly`: loads and verifies a ledger file, then answers query commands from
+/// stdin. No provider is built, no store is opened and nothing is written.
+pub async fn run(path: &Path, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let ledger = Ledger::from_records(store::read_ledger_file(path)?)?;
+    let records = ledger.records();
+    let mut opts = RenderOptions::for_stdout(config.no_color);
+    opts.color &= config.format == OutputFormat::Pretty;
+    opts.verbose = true;
+    println!("{}: {} records, chain verified. {}", path.display(), records.len(), HELP);
+    // Weighted strategies are replayed with the reputations this ledger itself earns.
+    let reputation = reputation::ReputationBook::from_records(records, config.half_life_days);
+
+    let mut lines = LineReader::new(BufReader::new(tokio::io::stdin()));
+    loop {
+        print!("readonly> ");
+        std::io::stdout().flush()?;
+        let input = match lines.next_line().await? {
+            None => return Ok(()),
+            Some(Line::Text(input)) => input,
+            Some(_) => {
+                println!("Unreadable input ignored.");
+                continue;
+            }
+        };
+        match parse_command(&input) {
+            Ok(Command::Show(None)) => println!("{}", render::render_ledger(records, &opts)),
+            Ok(Command::Show(Some(n))) => match n.checked_sub(1).and_then(|i| records.get(i)) {
+                Some(record) => println!("{}", render::render_record(n, record, &opts)),
+                None => println!("No record {}; the ledger has {}.", n, records.len()),
+            },
+            Ok(Command::Why(n)) => match n.checked_sub(1).and_then(|i| records.get(i)) {
+                Some(record) => print!("{}", render::render_why(n, record, &opts)),
+                None => println!("No record {}; the ledger has {}.", n, records.len()),
+            },
+            Ok(Command::Chart(n)) => match n.checked_sub(1).and_then(|i| records.get(i)) {
+                Some(record) => print!("{}", render::render_chart(n, record, &opts)),
+                None => println!("No record {}; the ledger has {}.", n, records.len()),
+            },
+            Ok(Command::Robustness(n)) => match n.checked_sub(1).and_then(|i| records.get(i)) {
+                Some(record) => {
+                    let weight = |model: &str| reputation.weight(model);
+                    let report = crate::robustness_report(n, record, config, &weight, crate::DEFAULT_RESAMPLES, None);
+                    println!(
+                        "Record {} ({}) holds in {:.1}% ± {:.1} of {} resamples of its {} votes",
+                        n,
+                        if report.recorded { "yes" } else { "no" },
+                        report.stability * 100.0,
+                        report.std_error * 100.0,
+                        report.resamples,
+                        report.votes
+                    );
+                }
+                None => println!("No record {}; the ledger has {}.", n, records.len()),
+            },
+            Ok(Command::Compare(a, b)) => {
+                let get = |n: usize| n.checked_sub(1).and_then(|i| records.get(i)).map(|r| (n, r));
+                match (get(a), get(b)) {
+                    (Some(left), Some(right)) => print!("{}", render::render_compare(left, right, &opts)),
+                    _ => println!("No record {}; the ledger has {}.", if get(a).is_none() { a } else { b }, records.len()),
+                }
+            }
+            Ok(Command::Search(words)) => {
+                let mut found = 0;
+                for (i, record) in records.iter().enumerate() {
+                    if store::scan_matches(&record.transaction.content, &words, store::SearchMode::All) {
+                        println!("{}", render::render_record(i + 1, record, &opts));
+                        found += 1;
+                    }
+                }
+                println!("{} matching records", found);
+            }
+            Ok(Command::Stats) => {
+                let summary = analytics::agreement(records);
+                println!(
+                    "{} records, {} reached consensus, mean truth score {:.1} ± {:.1}; {} labeled, {} pinned",
+                    summary.rounds,
+                    summary.consensus_rounds,
+                    summary.mean_truth_score,
+                    summary.truth_score_stddev,
+                    records.iter().filter(|r| r.label.is_some()).count(),
+                    records.iter().filter(|r| r.pinned).count()
+                );
+            }
+            Ok(Command::Accuracy) => {
+                let report = analytics::accuracy(records);
+                println!("{} labeled records", report.labeled_records);
+                for row in std::iter::once(&report.verdicts).chain([&report.overall]).chain(&report.models) {
+                    match row.accuracy {
+                        Some(accuracy) => println!("  {:<30} {:>5.1}% ({}/{})", row.model, accuracy * 100.0, row.correct, row.votes),
+                        None => println!("  {:<30} {:>6}", row.model, "-"),
+                    }
+                }
+            }
+            Ok(Command::Verify) => {
+                match ledger::verify_chain(records) {
+                    Ok(()) => println!("Chain verified: {} records, head {}", records.len(), ledger.head_hash()),
+                    Err(e) => println!("Chain broken: {}", e),
+                }
+                for anomaly in ledger::timestamp_anomalies(records) {
+                    println!("Anomaly: {}", anomaly);
+                }
+            }
+            Ok(Command::Head) => println!(
+                "{} records; head {}; Merkle root {}",
+                records.len(),
+                ledger.head_hash(),
+                ledger.merkle_root()
+            ),
+            Ok(Command::Prove(n)) => match ledger.merkle_proof(n) {
+                Some(proof) => println!("{}", serde_json::to_string(&proof)?),
+                None => println!("No record {}; the ledger has {}.", n, records.len()),
+            },
+            Ok(Command::CheckProof(proof)) => {
+                if proof.verifies() {
+                    println!("Proof verifies: leaf {} is under root {}", proof.leaf, proof.root);
+                } else {
+                    println!("Proof does not verify against root {}", proof.root);
+                }
+            }
+            Ok(Command::Schema) => println!("{}", serde_json::to_string_pretty(&crate::ledger_schema())?),
+            Ok(Command::Help) => println!("{}", HELP),
+            Ok(Command::Quit) => return Ok(()),
+            Err(message) => println!("{}; {}", message, HELP),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_query_commands() {
+        assert_eq!(parse_command("/show"), Ok(Command::Show(None)));
+        assert_eq!(parse_command(" /show 3 "), Ok(Command::Show(Some(3))));
+        assert_eq!(parse_command("/search moon cheese"), Ok(Command::Search("moon cheese".to_string())));
+        assert_eq!(parse_command("/verify"), Ok(Command::Verify));
+        assert_eq!(parse_command("/accuracy"), Ok(Command::Accuracy));
+        assert_eq!(parse_command("/schema"), Ok(Command::Schema));
+        assert_eq!(parse_command("/head"), Ok(Command::Head));
+        assert_eq!(parse_command("/compare 3 7"), Ok(Command::Compare(3, 7)));
+        assert!(parse_command("/compare 3").is_err());
+        assert_eq!(parse_command("/prove 2"), Ok(Command::Prove(2)));
+        assert_eq!(parse_command("/why 4"), Ok(Command::Why(4)));
+        assert_eq!(parse_command("/robustness 2"), Ok(Command::Robustness(2)));
+        assert_eq!(parse_command("/chart 5"), Ok(Command::Chart(5)));
+        assert!(parse_command("/robustness").is_err());
+        let proof = r#"/check-proof {"index":1,"leaf":"a","root":"a","steps":[]}"#;
+        assert!(matches!(parse_command(proof), Ok(Command::CheckProof(p)) if p.verifies()));
+        assert!(parse_command("/check-proof {").is_err());
+    }
+
+    #[test]
+    fn refuses_submissions_and_mutations() {
+        assert!(parse_command("The earth is round").is_err());
+        assert!(parse_command("/pin 2").unwrap_err().contains("not available"));
+        assert!(parse_command("/show two").is_err());
+        assert!(parse_command("/search").is_err());
+    }
+}
@@ -0,0 +1,82 @@
+use std::path::{Path, PathBuf};
+
+use crate::config::StoreKind;
+
+const MAX_NAME_LEN: usize = 64;
+
+/// Checks a `--workspace` name, as a clap value parser. Names become directory
+/// names, so only letters, digits, `-` and `_` are allowed.
+pub fn parse_name(name: &str) -> Result<String, String> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    match valid {
+        true => Ok(name.to_string()),
+        false => Err(format!(
+            "'{}' is not a workspace name (1-{} letters, digits, '-' or '_')",
+            name, MAX_NAME_LEN
+        )),
+    }
+}
+
+/// The store file of workspace `name`: each workspace is a directory under `dir`
+/// holding its own chain, from its own genesis.
+pub fn store_path(dir: &Path, name: &str, kind: StoreKind) -> PathBuf {
+    dir.join(name).join(store_file(kind))
+}
+
+/// The file name a store of `kind` is kept in, inside a workspace or `--data-dir`.
+pub fn store_file(kind: StoreKind) -> &'static str {
+    match kind {
+        StoreKind::Sqlite => "ledger.db",
+        StoreKind::Json | StoreKind::Memory => "ledger.jsonl",
+    }
+}
+
+/// The workspaces under `dir`, sorted; none if it doesn't exist yet.
+pub fn list(dir: &Path) -> std::io::Result<Vec<String>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut names = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type()?.is_dir() && parse_name(&name).is_ok() {
+            names.push(name);
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Creates an empty workspace; fails with `AlreadyExists` if it is taken.
+pub fn create(dir: &Path, name: &str) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    std::fs::create_dir(dir.join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workspaces_are_isolated_directories() {
+        assert!(parse_name("client-a_2").is_ok());
+        for bad in ["", "../x", "a b", "a.b", &"x".repeat(65)] {
+            assert!(parse_name(bad).is_err(), "{}", bad);
+        }
+
+        let dir = std::env::temp_dir().join(format!("truth-workspaces-{}", std::process::id()));
+        assert!(list(&dir).unwrap().is_empty());
+        create(&dir, "beta").unwrap();
+        create(&dir, "alpha").unwrap();
+        assert_eq!(create(&dir, "alpha").unwrap_err().kind(), std::io::ErrorKind::AlreadyExists);
+        std::fs::write(dir.join("stray.txt"), "").unwrap();
+        assert_eq!(list(&dir).unwrap(), ["alpha", "beta"]);
+        assert_eq!(store_path(&dir, "alpha", StoreKind::Sqlite), dir.join("alpha").join("ledger.db"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}